@@ -232,5 +232,139 @@ pub async fn create_database(db: &MongoDb) {
     .await
     .expect("Failed to create ratelimit_events index.");
 
+    db.run_command(
+        doc! {
+            "createIndexes": "events",
+            "indexes": [
+                {
+                    "key": {
+                        "search_location": "2dsphere"
+                    },
+                    "name": "event_search_location"
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create events search location index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_promo_codes",
+            "indexes": [
+                {
+                    "key": {
+                        "event_id": 1,
+                        "code": 1
+                    },
+                    "name": "event_promo_code",
+                    "unique": true
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_promo_codes index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_feedback",
+            "indexes": [
+                {
+                    "key": {
+                        "event_id": 1,
+                        "guest_id": 1
+                    },
+                    "name": "event_feedback_guest",
+                    "unique": true
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_feedback index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_activity_days",
+            "indexes": [
+                {
+                    "key": {
+                        "event_id": 1
+                    },
+                    "name": "event_activity_event"
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_activity_days index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_tickets",
+            "indexes": [
+                {
+                    "key": {
+                        "status": 1,
+                        "created_at": 1
+                    },
+                    "name": "event_ticket_status"
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_tickets index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_tickets",
+            "indexes": [
+                {
+                    "key": {
+                        "holder": 1
+                    },
+                    "name": "event_ticket_holder",
+                    "unique": true,
+                    "partialFilterExpression": {
+                        "holder": { "$exists": true }
+                    }
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_tickets holder index.");
+
+    db.run_command(
+        doc! {
+            "createIndexes": "event_guests",
+            "indexes": [
+                {
+                    "key": {
+                        "event_id": 1,
+                        "user_id": 1
+                    },
+                    "name": "event_guest_user",
+                    "unique": true,
+                    "partialFilterExpression": {
+                        "user_id": { "$exists": true },
+                        "status": { "$in": ["Pending", "Approved", "Rejected", "Waitlisted"] }
+                    }
+                }
+            ]
+        },
+        None,
+    )
+    .await
+    .expect("Failed to create event_guests user index.");
+
     info!("Created database.");
 }
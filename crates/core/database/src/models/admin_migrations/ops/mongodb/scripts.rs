@@ -1,4 +1,8 @@
-use std::{collections::HashSet, ops::BitXor, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::BitXor,
+    time::Duration,
+};
 
 use crate::{
     mongodb::{
@@ -7,6 +11,8 @@ use crate::{
     },
     MongoDb, DISCRIMINATOR_SEARCH_SPACE,
 };
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use futures::StreamExt;
 use rand::seq::SliceRandom;
 use revolt_permissions::DEFAULT_WEBHOOK_PERMISSIONS;
@@ -19,7 +25,7 @@ struct MigrationInfo {
     revision: i32,
 }
 
-pub const LATEST_REVISION: i32 = 26;
+pub const LATEST_REVISION: i32 = 36;
 
 pub async fn migrate_database(db: &MongoDb) {
     let migrations = db.col::<Document>("migrations");
@@ -1023,8 +1029,307 @@ pub async fn run_migrations(db: &MongoDb, revision: i32) -> i32 {
             .expect("Failed to create events index.");
     }
 
+    if revision <= 27 {
+        info!(
+            "Running migration [revision 27 / 18-10-2026]: Convert event dates to BSON datetimes."
+        );
+
+        let events = db.col::<Document>("events");
+        let mut cursor = events
+            .find(
+                doc! {
+                    "$or": [
+                        { "start_date": { "$type": "string" } },
+                        { "end_date": { "$type": "string" } },
+                        { "created_at": { "$type": "string" } },
+                        { "timezone": { "$not": { "$type": "string" } } }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to fetch events.");
+
+        while let Some(Ok(event)) = cursor.next().await {
+            let id = event.get_str("_id").unwrap_or_default().to_string();
+            let tz: Tz = event
+                .get_str("timezone")
+                .ok()
+                .and_then(|tz| tz.parse().ok())
+                .unwrap_or(Tz::UTC);
+
+            // Fall back to the time the event was created, taken from its ULID
+            let fallback = ulid::Ulid::from_string(&id)
+                .map(|ulid| ulid.timestamp_ms() as i64)
+                .unwrap_or_else(|_| Utc::now().timestamp_millis());
+
+            let mut set = doc! {
+                "timezone": tz.name()
+            };
+
+            for key in ["start_date", "end_date", "created_at"] {
+                if let Ok(value) = event.get_str(key) {
+                    let millis = parse_legacy_event_date(value, &tz).unwrap_or_else(|| {
+                        warn!(
+                            "Could not parse {key} `{value}` on event {id}, using creation time."
+                        );
+                        fallback
+                    });
+
+                    set.insert(key, DateTime::from_millis(millis));
+                }
+            }
+
+            events
+                .update_one(doc! { "_id": &id }, doc! { "$set": set }, None)
+                .await
+                .expect("Failed to update event.");
+        }
+    }
+
+    if revision <= 28 {
+        info!("Running migration [revision 28 / 18-10-2026]: Add geospatial search index to `events`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "events",
+                    "indexes": [
+                        {
+                            "key": {
+                                "search_location": "2dsphere"
+                            },
+                            "name": "event_search_location"
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create events search location index.");
+    }
+
+    if revision <= 29 {
+        info!("Running migration [revision 29 / 18-10-2026]: Add unique index to `event_promo_codes`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_promo_codes",
+                    "indexes": [
+                        {
+                            "key": {
+                                "event_id": 1,
+                                "code": 1
+                            },
+                            "name": "event_promo_code",
+                            "unique": true
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_promo_codes index.");
+    }
+
+    if revision <= 30 {
+        info!(
+            "Running migration [revision 30 / 18-10-2026]: Add unique index to `event_feedback`."
+        );
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_feedback",
+                    "indexes": [
+                        {
+                            "key": {
+                                "event_id": 1,
+                                "guest_id": 1
+                            },
+                            "name": "event_feedback_guest",
+                            "unique": true
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_feedback index.");
+    }
+
+    if revision <= 31 {
+        info!("Running migration [revision 31 / 18-10-2026]: Add event index to `event_activity_days`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_activity_days",
+                    "indexes": [
+                        {
+                            "key": {
+                                "event_id": 1
+                            },
+                            "name": "event_activity_event"
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_activity_days index.");
+    }
+
+    if revision <= 32 {
+        info!("Running migration [revision 32 / 18-10-2026]: Add status index to `event_tickets`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_tickets",
+                    "indexes": [
+                        {
+                            "key": {
+                                "status": 1,
+                                "created_at": 1
+                            },
+                            "name": "event_ticket_status"
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_tickets index.");
+    }
+
+    if revision <= 33 {
+        info!("Running migration [revision 33 / 18-10-2026]: Add unique holder index to `event_tickets`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_tickets",
+                    "indexes": [
+                        {
+                            "key": {
+                                "holder": 1
+                            },
+                            "name": "event_ticket_holder",
+                            "unique": true,
+                            "partialFilterExpression": {
+                                "holder": { "$exists": true }
+                            }
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_tickets holder index.");
+    }
+
+    if revision <= 34 {
+        info!("Running migration [revision 34 / 18-10-2026]: Count the seats taken at each event.");
+
+        let mut guests: HashMap<String, Vec<Document>> = HashMap::new();
+        let mut cursor = db
+            .col::<Document>("event_guests")
+            .find(doc! {}, None)
+            .await
+            .expect("Failed to fetch event guests.");
+
+        while let Some(Ok(guest)) = cursor.next().await {
+            let event_id = guest.get_str("event_id").unwrap_or_default().to_string();
+            guests.entry(event_id).or_default().push(guest);
+        }
+
+        for (event_id, guests) in guests {
+            let approved: HashSet<&str> = guests
+                .iter()
+                .filter(|guest| matches!(guest.get_str("status"), Ok("Approved")))
+                .filter_map(|guest| guest.get_str("_id").ok())
+                .collect();
+
+            // Approved guests take a seat, as do the pending plus-ones of approved guests
+            let seats = guests
+                .iter()
+                .filter(|guest| match guest.get_str("status") {
+                    Ok("Approved") => true,
+                    Ok("Pending") => guest
+                        .get_str("plus_one_of")
+                        .map_or(false, |main_id| approved.contains(main_id)),
+                    _ => false,
+                })
+                .count() as i32;
+
+            db.col::<Document>("events")
+                .update_one(
+                    doc! { "_id": &event_id },
+                    doc! { "$set": { "seats_taken": seats } },
+                    None,
+                )
+                .await
+                .expect("Failed to update event.");
+        }
+    }
+
+    if revision <= 35 {
+        info!("Running migration [revision 35 / 18-10-2026]: Add unique user index to `event_guests`.");
+
+        db.db()
+            .run_command(
+                doc! {
+                    "createIndexes": "event_guests",
+                    "indexes": [
+                        {
+                            "key": {
+                                "event_id": 1,
+                                "user_id": 1
+                            },
+                            "name": "event_guest_user",
+                            "unique": true,
+                            "partialFilterExpression": {
+                                "user_id": { "$exists": true },
+                                "status": { "$in": ["Pending", "Approved", "Rejected", "Waitlisted"] }
+                            }
+                        }
+                    ]
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create event_guests user index.");
+    }
+
     // Need to migrate fields on attachments, change `user_id`, `object_id`, etc to `parent`.
 
     // Reminder to update LATEST_REVISION when adding new migrations.
     LATEST_REVISION.max(revision)
 }
+
+/// Parse a date previously stored as a free-form string on an event
+///
+/// Dates without an offset are read as local time in the event's timezone.
+fn parse_legacy_event_date(value: &str, tz: &Tz) -> Option<i64> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(date.timestamp_millis());
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .and_then(|naive| tz.from_local_datetime(&naive).earliest())
+    .map(|date| date.timestamp_millis())
+}
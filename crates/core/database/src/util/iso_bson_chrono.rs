@@ -44,3 +44,23 @@ impl<'de> Visitor<'de> for DateTimeFallbackVisitor {
         chrono_datetime_as_bson_datetime::deserialize(serde::de::value::MapAccessDeserializer::new(map))
     }
 }
+
+pub fn serialize_optional<S>(value: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) => serialize(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_optional<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "deserialize")] DateTime<Utc>);
+
+    Option::<Wrapper>::deserialize(deserializer).map(|value| value.map(|Wrapper(date)| date))
+}
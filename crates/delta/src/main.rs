@@ -27,6 +27,9 @@ async fn rocket() -> _ {
     // Ensure environment variables are present
     revolt_quark::variables::delta::preflight_checks();
 
+    // Resolve the payment provider before taking any requests
+    revolt_quark::payments::init();
+
     // --- Setup MongoDb using the public re-export ---
     // Note the change: we use `revolt_database::MongoDb::init(...)` instead of referencing the private drivers module.
     let mongodb_uri = env::var("MONGODB").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
@@ -81,6 +81,9 @@ pub async fn req(db: &Db, user: User, info: Json<DataCreateGroup>) -> Result<Jso
 
         nsfw: info.nsfw.unwrap_or(false),
         hide_title: info.hide_title.unwrap_or(false),
+
+        event: None,
+        archived: false,
     };
 
     group.create(db).await?;
@@ -16,10 +16,8 @@ pub async fn req(db: &Db, user: User, target: Ref, member: Ref) -> Result<EmptyR
     let channel = target.as_channel(db).await?;
 
     match &channel {
-        Channel::Group {
-            owner, recipients, ..
-        } => {
-            if &user.id != owner {
+        Channel::Group { recipients, .. } => {
+            if !channel.is_group_owner(db, &user.id).await? {
                 return Error::from_permission(Permission::ManageChannel);
             }
 
@@ -0,0 +1,43 @@
+use chrono::Utc;
+use revolt_quark::models::event::{Event, EventViewer};
+use revolt_quark::models::events::analytics::{EventActivityKind, EventAnalytics};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+
+/// Fetch an event's engagement analytics
+///
+/// Shows how many people made it through each step from viewing the event
+/// to checking in, along with the same steps broken down per day.
+#[openapi(tag = "Events")]
+#[get("/<id>/analytics")]
+pub async fn get_event_analytics(
+    db: &State<Database>,
+    user: User,
+    id: String,
+) -> Result<Json<EventAnalytics>> {
+    let event = db.fetch_event(EventViewer::System, &id).await?;
+    event.throw_permission(&user.id, EventPermission::ViewAnalytics)?;
+
+    let activity = db.fetch_event_activity(&event.id).await?;
+    let saves = db.count_event_saves(&event.id).await?;
+    let guests = db.get_event_guests(&event.id).await?;
+    let history = db.fetch_event_status_history(&event.id).await?;
+    let tickets = db.fetch_tickets(&event.id).await?;
+
+    Ok(Json(EventAnalytics::build(
+        &event, &activity, saves, &guests, &history, &tickets,
+    )))
+}
+
+// Helper function to count engagement on an event towards today, in the event's timezone
+//
+// Analytics are best effort, so failing to record them never fails the request.
+pub(crate) async fn record_activity(db: &Database, event: &Event, kind: EventActivityKind) {
+    let date = Utc::now()
+        .with_timezone(&event.tz())
+        .format("%Y-%m-%d")
+        .to_string();
+
+    db.record_event_activity(&event.id, &date, kind).await.ok();
+}
@@ -0,0 +1,159 @@
+use chrono::Utc;
+use revolt_quark::models::events::calendar::{render_calendar, CalendarFeed};
+use revolt_quark::models::user::User;
+use revolt_quark::variables::delta::PUBLIC_URL;
+use revolt_quark::{Database, Error, Result};
+use revolt_rocket_okapi::revolt_okapi::openapi3::{self, MediaType, RefOr};
+use rocket::http::ContentType;
+use rocket::response::{self, Responder};
+use rocket::{serde::json::Json, Request, State};
+use schemars::schema::{InstanceType, SchemaObject, SingleOrVec};
+use serde::Serialize;
+
+/// Length of calendar feed tokens
+const FEED_TOKEN_LENGTH: usize = 48;
+
+pub struct CalendarFile(String);
+
+impl<'r> Responder<'r, 'static> for CalendarFile {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        (ContentType::new("text", "calendar"), self.0).respond_to(req)
+    }
+}
+
+impl revolt_rocket_okapi::response::OpenApiResponderInner for CalendarFile {
+    fn responses(
+        _gen: &mut revolt_rocket_okapi::gen::OpenApiGenerator,
+    ) -> std::result::Result<openapi3::Responses, revolt_rocket_okapi::OpenApiError> {
+        let mut responses = schemars::Map::new();
+        let mut content = schemars::Map::new();
+
+        content.insert(
+            "text/calendar".to_owned(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    instance_type: Some(SingleOrVec::Single(Box::new(InstanceType::String))),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+
+        responses.insert(
+            "200".to_string(),
+            RefOr::Object(openapi3::Response {
+                description: "iCalendar Document".to_string(),
+                content,
+                ..Default::default()
+            }),
+        );
+
+        Ok(openapi3::Responses {
+            responses,
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CalendarFeedResponse {
+    /// URL calendar apps can subscribe to
+    url: String,
+}
+
+impl From<CalendarFeed> for CalendarFeedResponse {
+    fn from(feed: CalendarFeed) -> Self {
+        Self {
+            url: format!(
+                "{}/events/calendar/feed/{}/events.ics",
+                *PUBLIC_URL, feed.token
+            ),
+        }
+    }
+}
+
+/// Export an event as an iCalendar file
+#[openapi(tag = "Events")]
+#[get("/<event_id>/calendar.ics")]
+pub async fn export_event_calendar(
+    db: &State<Database>,
+    user: Option<User>,
+    event_id: String,
+) -> Result<CalendarFile> {
+    let user_id = user.as_ref().map(|user| user.id.as_str());
+    // Hidden addresses are already left out for anyone who may not see them
+    let event = db.fetch_event(user_id.into(), &event_id).await?;
+
+    Ok(CalendarFile(render_calendar(
+        &event.title,
+        [event.to_ical(true)],
+    )))
+}
+
+/// Fetch the current user's calendar feed, creating it if needed
+#[openapi(tag = "Events")]
+#[get("/calendar/feed")]
+pub async fn get_calendar_feed(
+    db: &State<Database>,
+    user: User,
+) -> Result<Json<CalendarFeedResponse>> {
+    let feed = match db.fetch_calendar_feed(&user.id).await {
+        Ok(feed) => feed,
+        Err(Error::NotFound) => create_calendar_feed(db, &user).await?,
+        Err(error) => return Err(error),
+    };
+
+    Ok(Json(feed.into()))
+}
+
+/// Replace the current user's calendar feed URL, revoking the old one
+#[openapi(tag = "Events")]
+#[post("/calendar/feed")]
+pub async fn reset_calendar_feed(
+    db: &State<Database>,
+    user: User,
+) -> Result<Json<CalendarFeedResponse>> {
+    let feed = create_calendar_feed(db, &user).await?;
+    Ok(Json(feed.into()))
+}
+
+/// Revoke the current user's calendar feed
+#[openapi(tag = "Events")]
+#[delete("/calendar/feed")]
+pub async fn revoke_calendar_feed(db: &State<Database>, user: User) -> Result<()> {
+    db.delete_calendar_feed(&user.id).await
+}
+
+/// Subscribe to a user's events as an iCalendar feed
+///
+/// Covers events the user saved, is an approved guest of or hosts.
+#[openapi(tag = "Events")]
+#[get("/calendar/feed/<token>/events.ics")]
+pub async fn subscribe_calendar_feed(db: &State<Database>, token: String) -> Result<CalendarFile> {
+    let feed = db.fetch_calendar_feed_by_token(&token).await?;
+    let events = db.fetch_calendar_events(&feed.id).await?;
+    let attending = db.fetch_attending_event_ids(&feed.id).await?;
+
+    Ok(CalendarFile(render_calendar(
+        "Kimani Events",
+        events.iter().map(|event| {
+            // Hidden addresses are only shared with staff and approved guests
+            let show_address =
+                !event.hide_address || event.is_staff(&feed.id) || attending.contains(&event.id);
+
+            event.to_ical(show_address)
+        }),
+    )))
+}
+
+// Helper function to issue a new calendar feed token for a user
+async fn create_calendar_feed(db: &Database, user: &User) -> Result<CalendarFeed> {
+    let feed = CalendarFeed {
+        id: user.id.clone(),
+        token: nanoid::nanoid!(FEED_TOKEN_LENGTH),
+        created_at: Utc::now(),
+    };
+
+    db.save_calendar_feed(&feed).await?;
+    Ok(feed)
+}
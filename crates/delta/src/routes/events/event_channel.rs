@@ -0,0 +1,137 @@
+use revolt_quark::models::channels::channel::Channel;
+use revolt_quark::models::events::event::{EventViewer, PartialEvent};
+use revolt_quark::models::events::guest::{EventGuest, GuestStatus};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use ulid::Ulid;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCreateEventChannel {
+    /// Group name, defaults to the event title
+    #[validate(length(min = 1, max = 32))]
+    pub name: Option<String>,
+    /// Group description
+    #[validate(length(min = 0, max = 1024))]
+    pub description: Option<String>,
+}
+
+/// Create a group channel for the event
+///
+/// Staff and approved guests with an account are added straight away,
+/// other guests join or leave the group as their status changes. The
+/// group is archived once the event has ended.
+#[openapi(tag = "Events")]
+#[post("/<event_id>/channel", data = "<data>")]
+pub async fn create_event_channel(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataCreateEventChannel>,
+) -> Result<Json<Channel>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    if event.channel_id.is_some() {
+        return Err(Error::EventChannelExists);
+    }
+
+    let guests = db.get_event_guests(&event_id).await?;
+    let mut recipients = event.staff_ids();
+    for id in guests
+        .iter()
+        .filter(|guest| guest.status == GuestStatus::Approved)
+        .filter_map(|guest| guest.user_id.as_ref())
+    {
+        if !recipients.contains(id) {
+            recipients.push(id.clone());
+        }
+    }
+
+    let group = Channel::Group {
+        id: Ulid::new().to_string(),
+
+        name: data
+            .name
+            .unwrap_or_else(|| event.title.chars().take(32).collect()),
+        owner: user.id,
+        description: data.description,
+        recipients,
+
+        icon: None,
+        last_message_id: None,
+
+        permissions: None,
+        password: None,
+
+        nsfw: false,
+        hide_title: false,
+
+        event: Some(event.id.clone()),
+        archived: false,
+    };
+
+    group.create(db).await?;
+    db.update_event(
+        &event.id,
+        &PartialEvent {
+            channel_id: Some(group.id().to_string()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok(Json(group))
+}
+
+// Helper function to add or remove a guest from the event group as their status changes
+pub(crate) async fn sync_event_channel(db: &Database, by: &str, guest: &EventGuest) -> Result<()> {
+    let user_id = match &guest.user_id {
+        Some(user_id) => user_id,
+        None => return Ok(()),
+    };
+
+    let event = db.fetch_event(EventViewer::System, &guest.event_id).await?;
+    let channel_id = match &event.channel_id {
+        Some(channel_id) => channel_id,
+        None => return Ok(()),
+    };
+
+    // Staff stay in the group whatever their own guest status is
+    if event.is_staff(user_id) {
+        return Ok(());
+    }
+
+    let mut channel = match db.fetch_channel(channel_id).await {
+        Ok(channel) => channel,
+        Err(Error::NotFound) => return Ok(()),
+        Err(error) => return Err(error),
+    };
+
+    let is_member = match &channel {
+        Channel::Group {
+            recipients,
+            archived: false,
+            ..
+        } => recipients.contains(user_id),
+        _ => return Ok(()),
+    };
+
+    match guest.status {
+        GuestStatus::Approved if !is_member => channel.add_user_to_group(db, user_id, by).await,
+        GuestStatus::Rejected | GuestStatus::Cancelled if is_member => {
+            // Guests withdrawing themselves leave rather than get removed
+            let by = Some(by).filter(|by| *by != user_id.as_str());
+            channel.remove_user_from_group(db, user_id, by, false).await
+        }
+        _ => Ok(()),
+    }
+}
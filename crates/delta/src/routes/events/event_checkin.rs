@@ -0,0 +1,57 @@
+use super::event_guests::publish_guest_update;
+use revolt_quark::models::event::EventViewer;
+use revolt_quark::models::events::guest::{verify_checkin_pass, EventGuest, GuestStatus};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCheckIn {
+    /// Scanned check-in pass
+    #[validate(length(min = 1, max = 256))]
+    pub pass: String,
+}
+
+/// Check in a guest by scanning their pass
+#[openapi(tag = "Events")]
+#[post("/<event_id>/checkin", data = "<data>")]
+pub async fn check_in_guest(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataCheckIn>,
+) -> Result<Json<EventGuest>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    // Only staff allowed to check guests in can scan passes
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.throw_permission(&user.id, EventPermission::CheckIn)?;
+
+    let (pass_event_id, guest_id) =
+        verify_checkin_pass(&data.pass).ok_or(Error::InvalidCheckInPass)?;
+
+    if pass_event_id != event.id {
+        return Err(Error::CheckInPassForOtherEvent);
+    }
+
+    let guest = db
+        .get_guest(&event.id, &guest_id)
+        .await
+        .map_err(|_| Error::InvalidCheckInPass)?;
+
+    if guest.status != GuestStatus::Approved {
+        return Err(Error::InvalidCheckInPass);
+    }
+
+    db.check_in_guest(&event.id, &guest.id, &user.id).await?;
+
+    let guest = db.get_guest(&event.id, &guest.id).await?;
+    publish_guest_update(&guest, true).await;
+    Ok(Json(guest))
+}
@@ -0,0 +1,65 @@
+use super::event_create::publish_event_create;
+use revolt_quark::models::event::{
+    parse_event_date, validate_event_dates, Event, EventStatus, EventViewer,
+};
+use revolt_quark::models::user::User;
+use revolt_quark::tasks::event_reminders;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCloneEvent {
+    /// Title of the copy, defaults to the original's
+    #[validate(length(min = 1, max = 100))]
+    pub title: Option<String>,
+    /// Start date and time (ISO 8601, local to the event's timezone if no offset is given)
+    pub start_date: String,
+    /// End date and time (ISO 8601, local to the event's timezone if no offset is given)
+    pub end_date: String,
+    /// Create the copy as a draft, only visible to staff until published
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// Clone an event
+///
+/// Copies the venue, ticket configuration, staff, sponsors and every other
+/// detail of the event to new dates. Guests, tickets sold and invitations
+/// are not carried over.
+#[openapi(tag = "Events")]
+#[post("/<id>/clone", data = "<data>")]
+pub async fn clone_event(
+    db: &State<Database>,
+    user: User,
+    id: String,
+    data: Json<DataCloneEvent>,
+) -> Result<Json<Event>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let original = db.fetch_event(EventViewer::System, &id).await?;
+    original.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    let tz = original.tz();
+    let start_date = parse_event_date("start_date", &data.start_date, &tz)?;
+    let end_date = parse_event_date("end_date", &data.end_date, &tz)?;
+    validate_event_dates(&start_date, &end_date)?;
+
+    let mut event = original.duplicate(start_date, end_date);
+    if let Some(title) = data.title {
+        event.title = title;
+    }
+
+    if data.draft {
+        event.status = EventStatus::Draft;
+    }
+
+    db.insert_event(&event).await?;
+    event_reminders::schedule(db, &event).await?;
+    publish_event_create(&event).await;
+
+    Ok(Json(event))
+}
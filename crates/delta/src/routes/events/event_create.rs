@@ -1,12 +1,35 @@
 use chrono::Utc;
-use revolt_quark::models::event::{Event, EventType, TicketConfig};
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::{
+    parse_event_date, parse_timezone, validate_event_dates, Event, EventStatus, EventType,
+    GeoPoint, TicketConfig,
+};
+use revolt_quark::models::events::questionnaire::RsvpQuestion;
+use revolt_quark::models::events::series::{EventSeries, RecurrenceRule};
 use revolt_quark::models::user::User;
+use revolt_quark::tasks::event_reminders;
 use revolt_quark::{Database, Error, Result};
 use rocket::{serde::json::Json, State};
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 use validator::Validate;
 
+#[derive(Validate, Serialize, Deserialize, JsonSchema, Clone, Copy)]
+pub struct DataCoordinates {
+    /// Latitude in degrees
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: f64,
+    /// Longitude in degrees
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: f64,
+}
+
+impl From<DataCoordinates> for GeoPoint {
+    fn from(data: DataCoordinates) -> Self {
+        GeoPoint::new(data.latitude, data.longitude)
+    }
+}
+
 #[derive(Validate, Serialize, Deserialize, JsonSchema)]
 pub struct DataCreateEvent {
     /// Event title
@@ -14,9 +37,9 @@ pub struct DataCreateEvent {
     pub title: String,
     /// Event type
     pub event_type: EventType,
-    /// Start date and time
+    /// Start date and time (ISO 8601, local to `timezone` if no offset is given)
     pub start_date: String,
-    /// End date and time
+    /// End date and time (ISO 8601, local to `timezone` if no offset is given)
     pub end_date: String,
     /// City where event is held
     pub city: String,
@@ -24,6 +47,9 @@ pub struct DataCreateEvent {
     pub area: String,
     /// Full address
     pub address: String,
+    /// Coordinates of the venue, used to find events nearby
+    #[validate]
+    pub location: Option<DataCoordinates>,
     /// Event description
     #[validate(length(min = 0, max = 2000))]
     pub description: Option<String>,
@@ -35,6 +61,7 @@ pub struct DataCreateEvent {
     pub allow_plus_one: bool,
     /// Maximum number of +1 guests allowed
     #[serde(default)]
+    #[validate(range(min = 0))]
     pub allow_plus_one_amount: Option<i32>,
     /// Require full information for +1 guests
     #[serde(default)]
@@ -45,8 +72,20 @@ pub struct DataCreateEvent {
     /// Show events to non-members
     #[serde(default)]
     pub show_to_non_members: bool,
+    /// Don't send reminders to guests ahead of the event
+    #[serde(default)]
+    pub disable_reminders: bool,
+    /// Show the aggregated guest ratings to anyone who can see the event
+    #[serde(default)]
+    pub public_ratings: bool,
+    /// Maximum number of guests (including plus-ones), unlimited if not set
+    #[validate(range(min = 1))]
+    pub capacity: Option<i32>,
     /// Event hosts (user IDs)
     pub hosts: Vec<String>,
+    /// Event managers (user IDs)
+    #[serde(default)]
+    pub managers: Vec<String>,
     /// Event sponsors (user IDs)
     #[serde(default)]
     pub sponsors: Vec<String>,
@@ -56,6 +95,9 @@ pub struct DataCreateEvent {
     /// Payment type
     #[serde(default)]
     pub payment_type: Option<String>,
+    /// Questions guests are asked when they RSVP
+    #[serde(default)]
+    pub questions: Vec<RsvpQuestion>,
     /// Ticket configuration
     pub ticket_config: TicketConfig,
     /// Attachment URLs
@@ -68,11 +110,21 @@ pub struct DataCreateEvent {
     pub thumbnail: Option<String>,
     /// Country where event is held
     pub country: String,
-    /// Timezone
+    /// IANA timezone (e.g. "Africa/Nairobi")
     pub timezone: String,
+    /// Repeat the event on a recurrence rule, creating one event per occurrence
+    pub recurrence: Option<RecurrenceRule>,
+    /// Create the event as a draft, only visible to staff until published
+    #[serde(default)]
+    pub draft: bool,
+    /// Publish the draft at this date and time (ISO 8601, local to `timezone` if no offset is given)
+    pub publish_at: Option<String>,
 }
 
 /// Create a new event
+///
+/// Events with a recurrence rule are expanded into a series of
+/// occurrences, and the first occurrence is returned.
 #[openapi(tag = "Events")]
 #[post("/create", data = "<data>")]
 pub async fn create_event(
@@ -102,17 +154,41 @@ pub async fn create_event(
         });
     }
 
-    let date = Utc::now().to_rfc3339();
+    RsvpQuestion::validate_all(&data.questions)?;
+
+    let tz = parse_timezone(&data.timezone)?;
+    let start_date = parse_event_date("start_date", &data.start_date, &tz)?;
+    let end_date = parse_event_date("end_date", &data.end_date, &tz)?;
+    validate_event_dates(&start_date, &end_date)?;
+
+    if let Some(rule) = &data.recurrence {
+        rule.check(&start_date, &tz)?;
+    }
+
+    // Scheduling publication implies the event starts out as a draft
+    let publish_at = data
+        .publish_at
+        .as_deref()
+        .map(|date| parse_event_date("publish_at", date, &tz))
+        .transpose()?;
+
+    let status = if data.draft || publish_at.is_some() {
+        EventStatus::Draft
+    } else {
+        EventStatus::Published
+    };
+
     let mut event = Event {
         id: Ulid::new().to_string(),
         created_by: Some(user.id),
         title: data.title,
         event_type: Some(data.event_type),
-        start_date: data.start_date,
-        end_date: data.end_date,
+        start_date,
+        end_date,
         city: data.city.clone(),
         area: data.area.clone(),
         address: data.address.clone(),
+        location: data.location.map(Into::into),
         description: data.description.clone(),
         hide_address: data.hide_address,
         allow_plus_one: data.allow_plus_one,
@@ -120,30 +196,90 @@ pub async fn create_event(
         requires_plus_one_info: data.requires_plus_one_info,
         requires_rsvp_approval: data.requires_rsvp_approval,
         show_to_non_members: data.show_to_non_members,
+        disable_reminders: data.disable_reminders,
+        public_ratings: data.public_ratings,
+        capacity: data.capacity,
+        channel_id: None,
         hosts: data.hosts.clone(),
+        managers: data.managers.clone(),
+        staff_permissions: Default::default(),
         sponsors: data.sponsors.clone(),
         currency: data.currency.clone(),
         payment_type: data.payment_type.clone(),
+        questions: data.questions.clone(),
         ticket_config: data.ticket_config.clone(),
+        ticket_counts: Default::default(),
+        seats_taken: 0,
         attachments: data.attachments.clone(),
         gallery: data.gallery.clone(),
         thumbnail: data.thumbnail,
         country: Some(data.country.clone()),
-        created_at: date,
+        created_at: Utc::now(),
+        series_id: None,
+        sequence: 0,
+        status,
+        publish_at,
         is_saved: None,
         host_details: None,
         sponsor_details: None,
         guests: None,
         guest_stats: None,
-        timezone: Some(data.timezone.clone()),
+        distance: None,
+        timezone: tz.name().to_string(),
         invited_count: None,
     };
 
-    // Set default timezone if not provided
-    if event.timezone.is_none() {
-        event.timezone = Some("UTC".to_string());
+    let rule = match data.recurrence {
+        Some(rule) => rule,
+        None => {
+            db.insert_event(&event).await?;
+            event_reminders::schedule(db, &event).await?;
+            publish_event_create(&event).await;
+            return Ok(Json(event));
+        }
+    };
+
+    let series = EventSeries {
+        id: Ulid::new().to_string(),
+        created_by: event.created_by.clone(),
+        rule,
+        created_at: event.created_at,
+    };
+
+    let occurrences = series.rule.occurrences(&start_date, &tz);
+    if occurrences.is_empty() {
+        return Err(Error::InvalidRequest {
+            code: "invalid_recurrence".to_string(),
+            errors: vec!["recurrence: rule does not produce any occurrences".to_string()],
+        });
     }
 
-    db.insert_event(&event).await?;
-    Ok(Json(event))
+    db.insert_event_series(&series).await?;
+    event.series_id = Some(series.id.clone());
+
+    let duration = end_date - start_date;
+    let mut events: Vec<Event> = occurrences
+        .into_iter()
+        .map(|start_date| Event {
+            id: Ulid::new().to_string(),
+            start_date,
+            end_date: start_date + duration,
+            ..event.clone()
+        })
+        .collect();
+
+    for occurrence in &events {
+        db.insert_event(occurrence).await?;
+        event_reminders::schedule(db, occurrence).await?;
+        publish_event_create(occurrence).await;
+    }
+
+    Ok(Json(events.remove(0)))
+}
+
+// Helper function to let the staff of a new event know about it
+pub(crate) async fn publish_event_create(event: &Event) {
+    for id in event.staff_ids() {
+        EventV1::EventCreate(event.clone()).private(id).await;
+    }
 }
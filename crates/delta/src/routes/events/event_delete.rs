@@ -1,10 +1,20 @@
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::EventViewer;
+use revolt_quark::models::user::User;
 use revolt_quark::{Database, Result};
 use rocket::State;
 
 /// Delete event
+///
+/// Only the event's creator can delete it.
 #[openapi(tag = "Events")]
 #[delete("/<id>")]
-pub async fn delete_event(db: &State<Database>, id: String) -> Result<()> {
+pub async fn delete_event(db: &State<Database>, user: User, id: String) -> Result<()> {
+    let event = db.fetch_event(EventViewer::System, &id).await?;
+    event.throw_owner(&user.id)?;
+
     db.delete_event(&id).await?;
+    db.replace_event_reminders(&id, &[]).await?;
+    EventV1::EventDelete { id: id.clone() }.p(id).await;
     Ok(())
 }
@@ -1,6 +1,14 @@
-use revolt_quark::models::event::{Event, EventType, PartialEvent, TicketConfig};
+use super::event_create::DataCoordinates;
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::{
+    parse_event_date, parse_timezone, validate_event_dates, Event, EventType, EventViewer,
+    PartialEvent, TicketConfig,
+};
+use revolt_quark::models::events::questionnaire::RsvpQuestion;
+use revolt_quark::models::events::series::EditScope;
 use revolt_quark::models::user::User;
-use revolt_quark::{Database, Result};
+use revolt_quark::tasks::event_reminders;
+use revolt_quark::{Database, Error, EventPermission, Result};
 use rocket::{serde::json::Json, State};
 use serde::Deserialize;
 use validator::Validate;
@@ -11,13 +19,19 @@ impl From<DataEditEvent> for PartialEvent {
             id: None,
             created_by: None,
             created_at: None,
+            series_id: None,
+            sequence: None,
+            status: None,
+            publish_at: None,
             title: data.title,
             event_type: data.event_type,
-            start_date: data.start_date,
-            end_date: data.end_date,
+            // Dates are resolved against the event timezone by the route
+            start_date: None,
+            end_date: None,
             city: data.city,
             area: data.area,
             address: data.address,
+            location: data.location.map(Into::into),
             description: data.description,
             hide_address: data.hide_address,
             allow_plus_one: data.allow_plus_one,
@@ -25,9 +39,18 @@ impl From<DataEditEvent> for PartialEvent {
             requires_plus_one_info: data.requires_plus_one_info,
             requires_rsvp_approval: data.requires_rsvp_approval,
             show_to_non_members: data.show_to_non_members,
+            disable_reminders: data.disable_reminders,
+            public_ratings: data.public_ratings,
+            capacity: data.capacity,
+            channel_id: None,
             hosts: data.hosts,
+            managers: data.managers,
+            staff_permissions: None,
             sponsors: data.sponsors,
+            questions: data.questions,
             ticket_config: data.ticket_config,
+            ticket_counts: None,
+            seats_taken: None,
             currency: data.currency,
             payment_type: data.payment_type,
             attachments: data.attachments,
@@ -37,6 +60,7 @@ impl From<DataEditEvent> for PartialEvent {
             sponsor_details: None,
             guests: None,
             guest_stats: None,
+            distance: None,
             thumbnail: data.thumbnail,
             country: data.country,
             timezone: data.timezone,
@@ -53,16 +77,31 @@ pub struct DataEditEvent {
     pub city: Option<String>,
     pub area: Option<String>,
     pub address: Option<String>,
+    /// Coordinates of the venue
+    #[validate]
+    pub location: Option<DataCoordinates>,
     pub description: Option<String>,
     pub hide_address: Option<bool>,
     pub allow_plus_one: Option<bool>,
+    #[validate(range(min = 0))]
     pub allow_plus_one_amount: Option<i32>,
     pub requires_plus_one_info: Option<bool>,
     pub requires_rsvp_approval: Option<bool>,
     pub show_to_non_members: Option<bool>,
+    /// Don't send reminders to guests ahead of the event
+    pub disable_reminders: Option<bool>,
+    /// Show the aggregated guest ratings to anyone who can see the event
+    pub public_ratings: Option<bool>,
+    /// Maximum number of guests (including plus-ones)
+    #[validate(range(min = 1))]
+    pub capacity: Option<i32>,
     pub hosts: Option<Vec<String>>,
+    /// Event managers (user IDs)
+    pub managers: Option<Vec<String>>,
     pub sponsors: Option<Vec<String>>,
     pub ticket_config: Option<TicketConfig>,
+    /// Questions guests are asked when they RSVP, answers to removed questions are kept
+    pub questions: Option<Vec<RsvpQuestion>>,
     /// Attachment URLs
     pub attachments: Option<Vec<String>>,
     /// Gallery image URLs
@@ -73,22 +112,124 @@ pub struct DataEditEvent {
     pub thumbnail: Option<String>,
     /// Country where event is held
     pub country: Option<String>,
-    /// Timezone
+    /// IANA timezone (e.g. "Africa/Nairobi")
     pub timezone: Option<String>,
 }
 
 /// Update event
+///
+/// Occurrences of a series are edited on their own by default, pass
+/// `scope=Future` to apply the edit to every later occurrence as well.
 #[openapi(tag = "Events")]
-#[patch("/<id>", data = "<data>")]
+#[patch("/<id>?<scope>", data = "<data>")]
 pub async fn update_event(
     db: &State<Database>,
-    user: Option<User>,
+    user: User,
     id: String,
+    scope: Option<EditScope>,
     data: Json<DataEditEvent>,
 ) -> Result<Json<Event>> {
-    db.update_event(&id, &data.into_inner().into()).await?;
-    let mut event = db
-        .fetch_event(user.as_ref().map(|u| u.id.as_str()), &id)
-        .await?;
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+    if let Some(questions) = &data.questions {
+        RsvpQuestion::validate_all(questions)?;
+    }
+
+    let existing = db.fetch_event(EventViewer::System, &id).await?;
+    existing.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    // Only the creator decides who is on the event's staff
+    let staff_changed = data.hosts.is_some() || data.managers.is_some();
+    if staff_changed {
+        existing.throw_owner(&user.id)?;
+    }
+
+    // Resolve dates in the new timezone if it is being changed
+    let tz = match &data.timezone {
+        Some(timezone) => parse_timezone(timezone)?,
+        None => existing.tz(),
+    };
+
+    let start_date = data
+        .start_date
+        .as_deref()
+        .map(|date| parse_event_date("start_date", date, &tz))
+        .transpose()?;
+
+    let end_date = data
+        .end_date
+        .as_deref()
+        .map(|date| parse_event_date("end_date", date, &tz))
+        .transpose()?;
+
+    validate_event_dates(
+        start_date.as_ref().unwrap_or(&existing.start_date),
+        end_date.as_ref().unwrap_or(&existing.end_date),
+    )?;
+
+    let mut partial: PartialEvent = data.into();
+    partial.timezone = partial.timezone.map(|_| tz.name().to_string());
+
+    // Edits to a series can carry over to every later occurrence
+    let occurrences = match (scope, &existing.series_id) {
+        (Some(EditScope::Future), Some(series_id)) => db
+            .fetch_series_events(EventViewer::System, series_id)
+            .await?
+            .into_iter()
+            .filter(|occurrence| occurrence.start_date >= existing.start_date)
+            .collect(),
+        _ => vec![existing.clone()],
+    };
+
+    for occurrence in occurrences {
+        let mut partial = partial.clone();
+
+        // Date changes shift each occurrence by the same amount
+        partial.start_date =
+            start_date.map(|date| occurrence.start_date + (date - existing.start_date));
+        partial.end_date = end_date.map(|date| occurrence.end_date + (date - existing.end_date));
+
+        // Let calendar subscribers know the event changed
+        partial.sequence = Some(occurrence.sequence + 1);
+
+        // Permissions assigned to removed staff are dropped with them
+        if staff_changed {
+            let hosts = partial.hosts.as_ref().unwrap_or(&occurrence.hosts);
+            let managers = partial.managers.as_ref().unwrap_or(&occurrence.managers);
+            partial.staff_permissions = Some(
+                occurrence
+                    .staff_permissions
+                    .iter()
+                    .filter(|(id, _)| hosts.contains(id) || managers.contains(id))
+                    .map(|(id, permissions)| (id.clone(), *permissions))
+                    .collect(),
+            );
+        }
+
+        db.update_event(&occurrence.id, &partial).await?;
+
+        // Reschedule reminders against the updated start date
+        let updated = db.fetch_event(EventViewer::System, &occurrence.id).await?;
+        event_reminders::schedule(db, &updated).await?;
+
+        EventV1::EventUpdate {
+            id: occurrence.id.clone(),
+            data: partial,
+        }
+        .p(occurrence.id.clone())
+        .await;
+
+        // New staff are not subscribed to the event yet
+        for id in updated
+            .staff_ids()
+            .into_iter()
+            .filter(|id| !occurrence.is_staff(id))
+        {
+            EventV1::EventCreate(updated.clone()).private(id).await;
+        }
+    }
+
+    let event = db.fetch_event(EventViewer::User(&user.id), &id).await?;
     Ok(Json(event))
 }
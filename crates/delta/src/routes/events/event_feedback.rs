@@ -0,0 +1,192 @@
+use chrono::Utc;
+use revolt_quark::models::event::EventViewer;
+use revolt_quark::models::events::feedback::{EventFeedback, FeedbackSummary};
+use revolt_quark::models::events::guest::EventGuest;
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataFeedback {
+    /// Rating from 1 to 5
+    #[validate(range(min = 1, max = 5))]
+    pub rating: i32,
+    /// Free text comment
+    #[validate(length(min = 1, max = 2000))]
+    pub comment: Option<String>,
+    /// Hide who left the feedback from the event's staff
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct FeedbackEntry {
+    /// Feedback Id
+    id: String,
+    /// Guest who left the feedback, hidden if anonymous
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guest_id: Option<String>,
+    /// Name of the guest who left the feedback, hidden if anonymous
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// Rating from 1 to 5
+    rating: i32,
+    /// Free text comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    /// Whether the feedback was left anonymously
+    anonymous: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct FeedbackResponse {
+    /// Aggregated ratings
+    summary: FeedbackSummary,
+    /// Every piece of feedback left
+    feedback: Vec<FeedbackEntry>,
+}
+
+/// Leave feedback on an event
+///
+/// Open to guests who were approved or checked in, from the moment the
+/// event ends until the feedback window closes. Leaving feedback again
+/// replaces what you left before.
+#[openapi(tag = "Events")]
+#[put("/<event_id>/feedback", data = "<data>")]
+pub async fn submit_feedback(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataFeedback>,
+) -> Result<Json<EventFeedback>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.check_feedback_open()?;
+
+    let guests = db.get_event_guests(&event.id).await?;
+    let guest = find_attendee(&guests, &user.id).ok_or(Error::NotEventAttendee)?;
+
+    let previous = match db.fetch_event_feedback(&event.id, &guest.id).await {
+        Ok(feedback) => Some(feedback),
+        Err(Error::NotFound) => None,
+        Err(error) => return Err(error),
+    };
+
+    let feedback = EventFeedback {
+        id: previous
+            .as_ref()
+            .map(|feedback| feedback.id.clone())
+            .unwrap_or_else(|| Ulid::new().to_string()),
+        event_id: event.id,
+        guest_id: guest.id.clone(),
+        user_id: Some(user.id),
+        rating: data.rating,
+        comment: data.comment,
+        anonymous: data.anonymous,
+        created_at: previous
+            .as_ref()
+            .map(|feedback| feedback.created_at)
+            .unwrap_or_else(Utc::now),
+        edited_at: previous.map(|_| Utc::now()),
+    };
+
+    db.save_event_feedback(&feedback).await?;
+    Ok(Json(feedback))
+}
+
+/// Fetch the feedback you left on an event
+#[openapi(tag = "Events")]
+#[get("/<event_id>/feedback/@me")]
+pub async fn get_my_feedback(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<EventFeedback>> {
+    let guests = db.get_event_guests(&event_id).await?;
+    let guest = find_attendee(&guests, &user.id).ok_or(Error::NotFound)?;
+
+    let feedback = db.fetch_event_feedback(&event_id, &guest.id).await?;
+    Ok(Json(feedback))
+}
+
+/// Fetch the feedback left on an event
+///
+/// Guests who left feedback anonymously are not named.
+#[openapi(tag = "Events")]
+#[get("/<event_id>/feedback")]
+pub async fn get_event_feedback(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<FeedbackResponse>> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ViewAnalytics)?;
+
+    let feedback = db.fetch_event_feedbacks(&event.id).await?;
+    let guests = db.get_event_guests(&event.id).await?;
+
+    Ok(Json(FeedbackResponse {
+        summary: FeedbackSummary::from_feedback(&feedback),
+        feedback: feedback
+            .into_iter()
+            .map(|feedback| {
+                let (guest_id, name) = if feedback.anonymous {
+                    (None, None)
+                } else {
+                    let name = guests
+                        .iter()
+                        .find(|guest| guest.id == feedback.guest_id)
+                        .map(|guest| guest.name.clone());
+
+                    (Some(feedback.guest_id), name)
+                };
+
+                FeedbackEntry {
+                    id: feedback.id,
+                    guest_id,
+                    name,
+                    rating: feedback.rating,
+                    comment: feedback.comment,
+                    anonymous: feedback.anonymous,
+                }
+            })
+            .collect(),
+    }))
+}
+
+/// Fetch the aggregated ratings of an event
+///
+/// Available to anyone who can see the event if the host chose to show
+/// ratings publicly, otherwise only to staff who can view analytics.
+#[openapi(tag = "Events")]
+#[get("/<event_id>/feedback/summary")]
+pub async fn get_feedback_summary(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<FeedbackSummary>> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    if !event.public_ratings {
+        event.throw_permission(&user.id, EventPermission::ViewAnalytics)?;
+    }
+
+    let feedback = db.fetch_event_feedbacks(&event.id).await?;
+    Ok(Json(FeedbackSummary::from_feedback(&feedback)))
+}
+
+// Helper function to find the guest entry a user attended an event under
+fn find_attendee<'a>(guests: &'a [EventGuest], user_id: &str) -> Option<&'a EventGuest> {
+    guests
+        .iter()
+        .find(|guest| guest.user_id.as_deref() == Some(user_id) && guest.attended())
+}
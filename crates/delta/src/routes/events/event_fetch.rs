@@ -1,7 +1,19 @@
-use revolt_quark::models::event::EventGuestStats;
+use super::event_analytics::record_activity;
+use revolt_quark::models::event::{EventGuestStats, EventViewer};
+use revolt_quark::models::events::analytics::EventActivityKind;
 use revolt_quark::models::events::guest::{EventGuest, GuestStatus};
-use revolt_quark::{models::event::Event, models::user::User, Database, Result};
+use revolt_quark::models::events::series::EventSeries;
+use revolt_quark::{models::event::Event, models::user::User, Database, Error, Result};
 use rocket::{serde::json::Json, State};
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+pub struct EventSeriesResponse {
+    /// Series the event belongs to
+    series: EventSeries,
+    /// Every occurrence of the series, earliest first
+    events: Vec<Event>,
+}
 
 /// Get event by id
 #[openapi(tag = "Events")]
@@ -12,9 +24,14 @@ pub async fn get_event(
     id: String,
 ) -> Result<Json<Event>> {
     let mut event = db
-        .fetch_event(user.as_ref().map(|u| u.id.as_str()), &id)
+        .fetch_event(user.as_ref().map(|u| u.id.as_str()).into(), &id)
         .await?;
 
+    // Views by the staff would only skew the event's analytics
+    if !user.as_ref().map_or(false, |user| event.is_staff(&user.id)) {
+        record_activity(db, &event, EventActivityKind::View).await;
+    }
+
     // Calculate guest statistics
     let guests = db.get_event_guests(&id).await?;
     let stats = EventGuestStats {
@@ -31,8 +48,33 @@ pub async fn get_event(
             .iter()
             .filter(|g| matches!(g.status, GuestStatus::Rejected))
             .count() as i32,
+        total_waitlisted: guests
+            .iter()
+            .filter(|g| matches!(g.status, GuestStatus::Waitlisted))
+            .count() as i32,
     };
 
     event.guest_stats = Some(stats);
     Ok(Json(event))
 }
+
+/// Get the series an event belongs to along with all of its occurrences
+#[openapi(tag = "Events")]
+#[get("/<id>/series")]
+pub async fn get_event_series(
+    db: &State<Database>,
+    user: Option<User>,
+    id: String,
+) -> Result<Json<EventSeriesResponse>> {
+    let event = db
+        .fetch_event(user.as_ref().map(|u| u.id.as_str()).into(), &id)
+        .await?;
+
+    let series_id = event.series_id.ok_or(Error::NotFound)?;
+    let series = db.fetch_event_series(&series_id).await?;
+    let events = db
+        .fetch_series_events(user.as_ref().map(|u| u.id.as_str()).into(), &series_id)
+        .await?;
+
+    Ok(Json(EventSeriesResponse { series, events }))
+}
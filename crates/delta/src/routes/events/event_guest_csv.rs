@@ -0,0 +1,405 @@
+use super::event_guests::{publish_guest_update, validation_errors_to_strings, DataCreateGuest};
+use revolt_quark::models::event::{Event, EventViewer};
+use revolt_quark::models::events::guest::{EventGuest, GuestStatus};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use revolt_rocket_okapi::revolt_okapi::openapi3::{self, MediaType, RefOr};
+use rocket::http::ContentType;
+use rocket::response::{self, Responder, Response};
+use rocket::{serde::json::Json, Request, State};
+use schemars::schema::{InstanceType, SchemaObject, SingleOrVec};
+use serde::Serialize;
+use ulid::Ulid;
+use validator::Validate;
+
+/// Columns of an exported guest list
+///
+/// Imports read the same columns, `status` and `checked_in_at` are ignored.
+/// Exports add a column for each of the event's RSVP questions after these.
+static COLUMNS: [&str; 6] = [
+    "name",
+    "email",
+    "phone",
+    "status",
+    "plus_one_of",
+    "checked_in_at",
+];
+
+pub struct CsvFile(String);
+
+impl<'r> Responder<'r, 'static> for CsvFile {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        Response::build_from(self.0.respond_to(req)?)
+            .header(ContentType::CSV)
+            .raw_header("Content-Disposition", "attachment; filename=\"guests.csv\"")
+            .ok()
+    }
+}
+
+impl revolt_rocket_okapi::response::OpenApiResponderInner for CsvFile {
+    fn responses(
+        _gen: &mut revolt_rocket_okapi::gen::OpenApiGenerator,
+    ) -> std::result::Result<openapi3::Responses, revolt_rocket_okapi::OpenApiError> {
+        let mut responses = schemars::Map::new();
+        let mut content = schemars::Map::new();
+
+        content.insert(
+            "text/csv".to_owned(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    instance_type: Some(SingleOrVec::Single(Box::new(InstanceType::String))),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+
+        responses.insert(
+            "200".to_string(),
+            RefOr::Object(openapi3::Response {
+                description: "CSV Document".to_string(),
+                content,
+                ..Default::default()
+            }),
+        );
+
+        Ok(openapi3::Responses {
+            responses,
+            ..Default::default()
+        })
+    }
+}
+
+/// Outcome of importing a single row
+#[derive(Serialize, JsonSchema, Debug, PartialEq, Eq)]
+pub enum GuestImportResult {
+    /// A new guest was added
+    Created,
+    /// An existing guest with the same email was updated
+    Updated,
+    /// The row was not imported
+    Rejected,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GuestImportRow {
+    /// Row number in the file, the header being row 1
+    pub row: usize,
+    /// What happened to the row
+    pub result: GuestImportResult,
+    /// Guest created or updated from the row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_id: Option<String>,
+    /// Why the row was rejected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema, Default)]
+pub struct GuestImportReport {
+    /// Number of guests created
+    pub created: usize,
+    /// Number of guests updated
+    pub updated: usize,
+    /// Number of rows rejected
+    pub rejected: usize,
+    /// Outcome of every row
+    pub rows: Vec<GuestImportRow>,
+}
+
+impl GuestImportReport {
+    fn push(
+        &mut self,
+        row: usize,
+        result: std::result::Result<(GuestImportResult, String), Vec<String>>,
+    ) {
+        let row = match result {
+            Ok((result, guest_id)) => {
+                if result == GuestImportResult::Created {
+                    self.created += 1;
+                } else {
+                    self.updated += 1;
+                }
+
+                GuestImportRow {
+                    row,
+                    result,
+                    guest_id: Some(guest_id),
+                    errors: vec![],
+                }
+            }
+            Err(errors) => {
+                self.rejected += 1;
+                GuestImportRow {
+                    row,
+                    result: GuestImportResult::Rejected,
+                    guest_id: None,
+                    errors,
+                }
+            }
+        };
+
+        self.rows.push(row);
+    }
+}
+
+/// Export an event's guest list as CSV
+///
+/// Plus-ones reference their main guest by email in `plus_one_of`. Answers
+/// to the event's RSVP questions follow in a column per question.
+#[openapi(tag = "Events")]
+#[get("/<event_id>/guests/export.csv")]
+pub async fn export_guests(db: &State<Database>, user: User, event_id: String) -> Result<CsvFile> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    let mut guests = db.get_event_guests(&event_id).await?;
+    guests.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let header: Vec<String> = COLUMNS
+        .iter()
+        .map(|column| column.to_string())
+        .chain(
+            event
+                .questions
+                .iter()
+                .map(|question| escape_formula(&question.label)),
+        )
+        .collect();
+
+    writer
+        .write_record(&header)
+        .map_err(|_| Error::InternalError)?;
+
+    for guest in &guests {
+        let plus_one_of = guest
+            .plus_one_of
+            .as_ref()
+            .and_then(|id| guests.iter().find(|main| &main.id == id))
+            .map(|main| main.email.as_str())
+            .unwrap_or_default();
+
+        let answers = event.questions.iter().map(|question| {
+            guest
+                .answers
+                .get(&question.id)
+                .map(|answer| escape_formula(&answer.to_string()))
+                .unwrap_or_default()
+        });
+
+        writer
+            .write_record(
+                [
+                    escape_formula(&guest.name),
+                    escape_formula(&guest.email),
+                    escape_formula(&guest.phone),
+                    String::from(guest.status.clone()),
+                    escape_formula(plus_one_of),
+                    guest
+                        .checked_in_at
+                        .as_ref()
+                        .map(|timestamp| timestamp.to_string())
+                        .unwrap_or_default(),
+                ]
+                .into_iter()
+                .chain(answers),
+            )
+            .map_err(|_| Error::InternalError)?;
+    }
+
+    let data = writer.into_inner().map_err(|_| Error::InternalError)?;
+    String::from_utf8(data)
+        .map(CsvFile)
+        .map_err(|_| Error::InternalError)
+}
+
+/// Import guests into an event from CSV
+///
+/// Takes the columns of an export, only `name` and `email` are required.
+/// Rows are matched to existing guests by email: matches have their name
+/// and phone updated, other rows add a new guest. Each row is validated on
+/// its own and rejected rows do not stop the rest of the import.
+#[openapi(tag = "Events")]
+#[post("/<event_id>/guests/import", data = "<data>")]
+pub async fn import_guests(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: String,
+) -> Result<Json<GuestImportReport>> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let headers = reader.headers().map_err(invalid_csv)?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+
+    let (name, email) = match (column("name"), column("email")) {
+        (Some(name), Some(email)) => (name, email),
+        _ => {
+            return Err(Error::InvalidRequest {
+                code: "invalid_csv".to_string(),
+                errors: vec!["header: name and email columns are required".to_string()],
+            })
+        }
+    };
+
+    let phone = column("phone");
+    let plus_one_of = column("plus_one_of");
+
+    let mut guests = db.get_event_guests(&event_id).await?;
+    let mut report = GuestImportReport::default();
+
+    for (index, record) in reader.records().enumerate() {
+        let result = match record {
+            Ok(record) => {
+                let cell = |column: Option<usize>| {
+                    column
+                        .and_then(|column| record.get(column))
+                        .map(unescape_formula)
+                        .unwrap_or_default()
+                };
+
+                let row = DataCreateGuest {
+                    name: cell(Some(name)),
+                    email: cell(Some(email)),
+                    phone: cell(phone),
+                    associated_user_id: None,
+                    plus_one_of: None,
+                    parent_guest_id: None,
+                    answers: Default::default(),
+                };
+
+                import_row(db, &event, &mut guests, row, cell(plus_one_of)).await
+            }
+            Err(error) => Err(vec![error.to_string()]),
+        };
+
+        report.push(index + 2, result);
+    }
+
+    Ok(Json(report))
+}
+
+// Helper function to create or update the guest described by a single row
+async fn import_row(
+    db: &Database,
+    event: &Event,
+    guests: &mut Vec<EventGuest>,
+    mut row: DataCreateGuest,
+    plus_one_of: String,
+) -> std::result::Result<(GuestImportResult, String), Vec<String>> {
+    row.validate()
+        .map_err(|errors| validation_errors_to_strings(&errors))?;
+
+    if !plus_one_of.is_empty() {
+        let main = guests
+            .iter()
+            .find(|guest| {
+                guest.plus_one_of.is_none() && guest.email.eq_ignore_ascii_case(&plus_one_of)
+            })
+            .ok_or_else(|| vec!["plus_one_of: no guest with this email".to_string()])?;
+
+        row.plus_one_of = Some(main.id.clone());
+    }
+
+    if let Some(guest) = guests
+        .iter_mut()
+        .find(|guest| guest.email.eq_ignore_ascii_case(&row.email))
+    {
+        if guest.name != row.name || guest.phone != row.phone {
+            guest.name = row.name;
+            guest.phone = row.phone;
+
+            db.update_guest_details(guest)
+                .await
+                .map_err(|_| vec!["Failed to update guest.".to_string()])?;
+
+            publish_guest_update(guest, guest.status == GuestStatus::Approved).await;
+        }
+
+        return Ok((GuestImportResult::Updated, guest.id.clone()));
+    }
+
+    // Once every seat is taken new guests join the waitlist
+    let status = if event.has_room(1) {
+        GuestStatus::Pending
+    } else {
+        GuestStatus::Waitlisted
+    };
+
+    let mut guest = EventGuest {
+        id: Ulid::new().to_string(),
+        event_id: event.id.clone(),
+        name: row.name,
+        email: row.email,
+        phone: row.phone,
+        answers: row.answers,
+        status,
+        user_id: None,
+        is_plus_one: Some(row.plus_one_of.is_some()),
+        plus_one_of: row.plus_one_of,
+        parent_guest_id: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        checked_in_at: None,
+        checked_in_by: None,
+        checkin_pass: None,
+    };
+
+    // Plus-ones of approved guests take a seat straight away
+    event
+        .add_guests(db, guests, std::slice::from_mut(&mut guest))
+        .await
+        .map_err(|_| vec!["Failed to add guest.".to_string()])?;
+
+    publish_guest_update(&guest, false).await;
+    Ok((GuestImportResult::Created, guest.id))
+}
+
+// Helper function to reject a file which cannot be read as CSV
+fn invalid_csv(error: csv::Error) -> Error {
+    Error::InvalidRequest {
+        code: "invalid_csv".to_string(),
+        errors: vec![error.to_string()],
+    }
+}
+
+// Helper function to stop spreadsheet apps from evaluating a cell as a formula
+//
+// Phone numbers such as `+254 700 000000` are left alone.
+fn escape_formula(value: &str) -> String {
+    let is_formula = match value.chars().next() {
+        Some('=') | Some('@') | Some('\t') | Some('\r') => true,
+        Some('+') | Some('-') => !value[1..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ' ' || c == '-'),
+        _ => false,
+    };
+
+    if is_formula {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Helper function to undo `escape_formula` on imported cells
+fn unescape_formula(value: &str) -> String {
+    match value.strip_prefix('\'') {
+        Some(rest) if escape_formula(rest) == value => rest.to_string(),
+        _ => value.to_string(),
+    }
+}
@@ -1,17 +1,27 @@
+use super::event_channel::sync_event_channel;
+use super::event_rsvp::check_plus_ones;
 use revolt_quark::authifier::config::{EmailVerificationConfig, Template};
 use revolt_quark::authifier::Authifier;
+use revolt_quark::util::templates::{EVENT_HTML, EVENT_TEXT};
 use revolt_quark::{
-    models::channels::channel::{Channel, PartialChannel},
+    events::client::EventV1,
+    models::channels::channel::Channel,
     models::channels::message::Message,
-    models::events::event::PartialEvent,
-    models::events::guest::{EventGuest, GuestStatus},
+    models::events::event::{Event, EventViewer},
+    models::events::guest::{count_seats, EventGuest, GuestStatus, GuestStatusChange},
+    models::events::message_job::{
+        DeliveryStatus, MessageJobKind, MessageJobStatus, MessageRecipient,
+    },
+    models::events::questionnaire::{RsvpAnswer, RsvpQuestion},
     models::user::User,
+    tasks::event_messages,
     types::push::MessageAuthor,
     variables::delta::APP_URL,
-    Database, Error, Result,
+    Database, Error, EventPermission, Result,
 };
 use rocket::{serde::json::Json, State};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use ulid::Ulid;
 use validator::Validate;
 
@@ -31,6 +41,9 @@ pub struct DataCreateGuest {
     pub plus_one_of: Option<String>,
     /// If this guest is a child guest (for pricing)
     pub parent_guest_id: Option<String>,
+    /// Answers to the event's RSVP questions, by question id
+    #[serde(default)]
+    pub answers: HashMap<String, RsvpAnswer>,
 }
 
 #[derive(Validate, Deserialize, JsonSchema)]
@@ -68,13 +81,14 @@ pub struct GuestStatusUpdate {
 
 #[derive(Validate, Deserialize, JsonSchema)]
 pub struct BulkStatusUpdate {
-    /// List of guest status updates
+    /// List of guest status updates, at most one per guest
     pub updates: Vec<GuestStatusUpdate>,
 }
 
 #[derive(Validate, Deserialize, JsonSchema)]
 pub struct BulkMessageData {
     /// List of messages to send
+    #[validate]
     pub messages: Vec<UserMessage>,
 }
 
@@ -87,6 +101,30 @@ pub struct UserMessage {
     pub content: Option<String>,
 }
 
+#[derive(Serialize, JsonSchema)]
+pub struct MessageJobResponse {
+    /// Id of the job sending the messages
+    job_id: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct MessageJobProgress {
+    /// Job Id
+    id: String,
+    /// Job status
+    status: MessageJobStatus,
+    /// Number of recipients
+    total: usize,
+    /// Recipients sent to
+    sent: usize,
+    /// Recipients given up on
+    failed: usize,
+    /// Recipients still to be sent to, including retries
+    pending: usize,
+    /// Recipients whose last attempt failed
+    failures: Vec<MessageRecipient>,
+}
+
 #[derive(Validate, Deserialize, JsonSchema)]
 pub struct GuestMessageData {
     /// Message content to send
@@ -100,6 +138,7 @@ pub struct GuestMessageData {
 #[openapi(tag = "Events")]
 #[post("/<event_id>/guests", data = "<data>")]
 pub async fn add_guest(
+    authifier: &State<Authifier>,
     db: &State<Database>,
     user: User,
     event_id: String,
@@ -114,36 +153,102 @@ pub async fn add_guest(
             errors: validation_errors_to_strings(&validation_errors),
         });
     }
-    let guest = EventGuest {
+
+    // Members RSVP through the RSVP endpoints, only staff managing guests add them here
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    // Once every seat is taken new guests join the waitlist
+    let status = if event.has_room(1) {
+        GuestStatus::Pending
+    } else {
+        GuestStatus::Waitlisted
+    };
+
+    // Staff may leave the answers for the guest to give when accepting their invitation
+    let answers = if data.answers.is_empty() {
+        HashMap::new()
+    } else {
+        RsvpQuestion::check_answers(&event.questions, data.answers)?
+    };
+
+    let mut guest = EventGuest {
         id: Ulid::new().to_string(),
         event_id: event_id.clone(),
         name: data.name,
         email: data.email,
         phone: data.phone,
-        status: GuestStatus::Pending,
-        user_id: Some(user.id.clone()),
+        answers,
+        status,
+        user_id: data.associated_user_id,
         plus_one_of: data.plus_one_of.clone(),
         parent_guest_id: data.parent_guest_id,
         is_plus_one: Some(data.plus_one_of.is_some()),
         created_at: chrono::Utc::now().to_rfc3339(),
+        checked_in_at: None,
+        checked_in_by: None,
+        checkin_pass: None,
     };
 
-    db.add_guest(&guest).await?;
+    // Plus-ones of approved guests take a seat straight away
+    let mut guests = db.get_event_guests(&event_id).await?;
+    event
+        .add_guests(db, &mut guests, std::slice::from_mut(&mut guest))
+        .await?;
+    publish_guest_update(&guest, false).await;
+
+    // Guests without an account answer through the link in their invitation
+    if guest.user_id.is_none() {
+        notify_guest(
+            authifier,
+            db,
+            &user,
+            &event,
+            &guest,
+            format!("You're invited to {}!", event.title),
+            guest_url(&guest),
+            format!(
+                "{} has invited you to {}.\n\nUse the link below to accept or decline, no account needed.",
+                user.username, event.title
+            ),
+        )
+        .await?;
+    }
+
     Ok(Json(GuestResponse { guest }))
 }
 
 /// Update single guest status
 #[openapi(tag = "Events")]
-#[patch("/<event_id>/guests/<guest_id>/status", data = "<status>")]
+#[patch("/<event_id>/guests/<guest_id>/status", data = "<status>", rank = 2)]
 pub async fn update_guest_status(
+    authifier: &State<Authifier>,
     db: &State<Database>,
-    _user: User,
+    user: User,
     event_id: String,
     guest_id: String,
     status: Json<GuestStatus>,
 ) -> Result<Json<()>> {
-    db.update_guest_status(&event_id, &guest_id, status.into_inner())
-        .await?;
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    let mut guests = db.get_event_guests(&event_id).await?;
+    let seats_before = count_seats(&guests);
+
+    let status = status.into_inner();
+    let guest = guests
+        .iter()
+        .find(|guest| guest.id == guest_id)
+        .ok_or(Error::NotFound)?;
+    check_guest_status_transition(&guest.status, &status)?;
+
+    // Approvals past the event's capacity land on the waitlist instead
+    set_guest_status(db, &user.id, &event, &mut guests, &guest_id, status).await?;
+
+    if count_seats(&guests) < seats_before {
+        promote_waitlisted_guests(authifier, db, &user, &event_id).await?;
+    }
+
     Ok(Json(()))
 }
 
@@ -158,19 +263,56 @@ pub async fn update_bulk_guest_status(
     data: Json<BulkStatusUpdate>,
 ) -> Result<Json<()>> {
     let data = data.into_inner();
+
+    // Verify sender may manage the event's guests
+    let event = db.fetch_event(EventViewer::User(&_user.id), &event_id).await?;
+    event.throw_permission(&_user.id, EventPermission::ManageGuests)?;
+
+    let mut guests = db.get_event_guests(&event_id).await?;
+
+    // Reject the whole batch if any of the changes is not allowed,
+    // each guest may only appear once so every change is checked
+    let mut seen = HashSet::new();
+    for update in &data.updates {
+        if !seen.insert(&update.guest_id) {
+            return Err(Error::InvalidOperation);
+        }
+
+        let guest = guests
+            .iter()
+            .find(|guest| guest.id == update.guest_id)
+            .ok_or(Error::NotFound)?;
+        check_guest_status_transition(&guest.status, &update.status)?;
+    }
+
     let mut approved_guests = Vec::new();
     let mut rejected_guests = Vec::new();
+    let mut waitlisted_count = 0;
+    let mut seats_released = false;
 
     // First update all guest statuses
     for update in &data.updates {
-        db.update_guest_status(&event_id, &update.guest_id, update.status.clone())
-            .await?;
+        let seats_before = count_seats(&guests);
+
+        // Approvals past the event's capacity land on the waitlist instead
+        let status = set_guest_status(
+            db,
+            &_user.id,
+            &event,
+            &mut guests,
+            &update.guest_id,
+            update.status.clone(),
+        )
+        .await?;
+
+        seats_released |= count_seats(&guests) < seats_before;
 
         // If the status is Approved or Rejected, add to respective lists for notifications
         if let Ok(guest) = db.get_guest(&event_id, &update.guest_id).await {
-            match update.status {
+            match status {
                 GuestStatus::Approved => approved_guests.push(guest),
                 GuestStatus::Rejected => rejected_guests.push(guest),
+                GuestStatus::Waitlisted => waitlisted_count += 1,
                 _ => {}
             }
         }
@@ -180,7 +322,7 @@ pub async fn update_bulk_guest_status(
     if !approved_guests.is_empty() {
         if let EmailVerificationConfig::Enabled { smtp, .. } = &authifier.config.email_verification
         {
-            let event = db.fetch_event(None, &event_id).await?;
+            let event = db.fetch_event(EventViewer::System, &event_id).await?;
             let approval_message = format!(
                 "This is an automatic system message \n\n
                 Your request to attend {}/events/view/{}\n
@@ -189,29 +331,26 @@ pub async fn update_bulk_guest_status(
             );
 
             for guest in &approved_guests {
+                // Link to the guest's check-in pass, rendered as a QR code by the app
+                let pass = guest.create_checkin_pass();
+                let pass_url = format!("{}/events/pass/{}", *APP_URL, pass);
+
                 smtp.send_email(
                     guest.email.clone(),
                     &Template {
                         title: format!("Approved for {}!", event.title),
-                        text: include_str!(concat!(
-                            env!("CARGO_MANIFEST_DIR"),
-                            "/assets/templates/event.txt"
-                        ))
-                        .into(),
-                        url: format!("{}/events/view/{}", *APP_URL, event_id),
-                        html: Some(
-                            include_str!(concat!(
-                                env!("CARGO_MANIFEST_DIR"),
-                                "/assets/templates/event.html"
-                            ))
-                            .into(),
-                        ),
+                        text: EVENT_TEXT.into(),
+                        url: pass_url.clone(),
+                        html: Some(EVENT_HTML.into()),
                     },
                     json!({
                         "email": guest.email.clone(),
-                        "url": format!("{}/events/view/{}", *APP_URL, event_id),
-                        "content": format!("{}",
-                            approval_message.clone()),
+                        "url": pass_url,
+                        "content": format!(
+                            "{}\n\nShow the QR code on your check-in pass at the door.\nPass code: {}",
+                            approval_message.clone(),
+                            pass
+                        ),
                     }),
                 );
             }
@@ -222,7 +361,7 @@ pub async fn update_bulk_guest_status(
     if !rejected_guests.is_empty() {
         if let EmailVerificationConfig::Enabled { smtp, .. } = &authifier.config.email_verification
         {
-            let event = db.fetch_event(None, &event_id).await?;
+            let event = db.fetch_event(EventViewer::System, &event_id).await?;
             let rejection_message = format!(
                 "This is an automatic system message \n\n
                 We regret to inform you that your request to attend {} has been declined. \n
@@ -235,19 +374,9 @@ pub async fn update_bulk_guest_status(
                     guest.email.clone(),
                     &Template {
                         title: format!("Update on {} Request", event.title),
-                        text: include_str!(concat!(
-                            env!("CARGO_MANIFEST_DIR"),
-                            "/assets/templates/event.txt"
-                        ))
-                        .into(),
+                        text: EVENT_TEXT.into(),
                         url: format!("{}/events/view/{}", *APP_URL, event_id),
-                        html: Some(
-                            include_str!(concat!(
-                                env!("CARGO_MANIFEST_DIR"),
-                                "/assets/templates/event.html"
-                            ))
-                            .into(),
-                        ),
+                        html: Some(EVENT_HTML.into()),
                     },
                     json!({
                         "email": guest.email.clone(),
@@ -261,19 +390,25 @@ pub async fn update_bulk_guest_status(
         }
     }
 
+    // Hand any released seats to the waitlist
+    if seats_released {
+        promote_waitlisted_guests(authifier, db, &_user, &event_id).await?;
+    }
+
     // Send notification to hosts and event creator
-    if !approved_guests.is_empty() || !rejected_guests.is_empty() {
-        let event = db.fetch_event(None, &event_id).await?;
+    if !approved_guests.is_empty() || !rejected_guests.is_empty() || waitlisted_count > 0 {
+        let event = db.fetch_event(EventViewer::System, &event_id).await?;
         let mut recipients = event.hosts.clone();
         if let Some(creator) = event.created_by {
             recipients.push(creator);
         }
 
         let status_message = format!(
-            "Guest status updates for {}:\n\nApproved: {}\nRejected: {}",
+            "Guest status updates for {}:\n\nApproved: {}\nRejected: {}\nWaitlisted: {}",
             event.title,
             approved_guests.len(),
-            rejected_guests.len()
+            rejected_guests.len(),
+            waitlisted_count
         );
 
         for recipient_id in recipients {
@@ -313,28 +448,60 @@ pub async fn update_bulk_guest_status(
     Ok(Json(()))
 }
 
+/// Get the status history of a guest
+#[openapi(tag = "Events")]
+#[get("/<event_id>/guests/<guest_id>/history")]
+pub async fn get_guest_status_history(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    guest_id: String,
+) -> Result<Json<Vec<GuestStatusChange>>> {
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    let history = db.fetch_guest_status_history(&event_id, &guest_id).await?;
+    Ok(Json(history))
+}
+
 /// Get all guests for an event
 #[openapi(tag = "Events")]
 #[get("/<event_id>/guests")]
 pub async fn get_event_guests(
     db: &State<Database>,
-    _user: User,
+    user: User,
     event_id: String,
 ) -> Result<Json<Vec<EventGuest>>> {
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
     let guests = db.get_event_guests(&event_id).await?;
     Ok(Json(guests))
 }
 
 /// Get a specific guest
 #[openapi(tag = "Events")]
-#[get("/<event_id>/guests/<guest_id>")]
+#[get("/<event_id>/guests/<guest_id>", rank = 2)]
 pub async fn get_guest(
     db: &State<Database>,
-    _user: User,
+    user: User,
     event_id: String,
     guest_id: String,
 ) -> Result<Json<EventGuest>> {
-    let guest = db.get_guest(&event_id, &guest_id).await?;
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    let mut guest = db.get_guest(&event_id, &guest_id).await?;
+
+    // Guests may look themselves up, anyone else must manage the event's guests
+    let is_self = guest.user_id.as_deref() == Some(&user.id);
+    if !is_self {
+        event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+    }
+
+    // Approved guests can see their own check-in pass
+    if is_self && guest.status == GuestStatus::Approved {
+        guest.checkin_pass = Some(guest.create_checkin_pass());
+    }
+
     Ok(Json(guest))
 }
 
@@ -344,14 +511,13 @@ pub async fn get_guest(
 pub async fn add_bulk_guests(
     authifier: &State<Authifier>,
     db: &State<Database>,
-    user: Option<User>,
+    user: User,
     event_id: String,
     data: Json<DataCreateBulkGuests>,
 ) -> Result<Json<BulkGuestResponse>> {
     let data = data.into_inner();
-    let mut created_guests = Vec::new();
 
-    // First create the main contact
+    // First the main contact
     if let Err(validation_errors) = data.main_contact.validate() {
         return Err(Error::InvalidRequest {
             code: "validation_error".to_string(),
@@ -359,24 +525,33 @@ pub async fn add_bulk_guests(
         });
     }
 
+    // Verify sender may manage the event's guests
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+    event.check_open()?;
+    let answers = RsvpQuestion::check_answers(&event.questions, data.main_contact.answers)?;
+
     let main_guest = EventGuest {
         id: Ulid::new().to_string(),
         event_id: event_id.clone(),
         name: data.main_contact.name,
         email: data.main_contact.email,
         phone: data.main_contact.phone,
+        answers,
         status: GuestStatus::Pending,
-        user_id: user.map(|u| u.id),
+        user_id: data.main_contact.associated_user_id,
         plus_one_of: None,
         parent_guest_id: None,
         is_plus_one: None,
         created_at: chrono::Utc::now().to_rfc3339(),
+        checked_in_at: None,
+        checked_in_by: None,
+        checkin_pass: None,
     };
 
-    db.add_guest(&main_guest).await?;
-    created_guests.push(main_guest.clone());
+    let mut party = vec![main_guest.clone()];
 
-    // Then create additional guests
+    // Then the additional guests
     for guest_data in data.additional_guests {
         if let Err(validation_errors) = guest_data.validate() {
             return Err(Error::InvalidRequest {
@@ -385,57 +560,73 @@ pub async fn add_bulk_guests(
             });
         }
 
-        let guest = EventGuest {
+        party.push(EventGuest {
             id: Ulid::new().to_string(),
             event_id: event_id.clone(),
             name: guest_data.name,
             email: guest_data.email,
             phone: guest_data.phone,
+            answers: HashMap::new(),
             status: GuestStatus::Pending,
             user_id: None,
             plus_one_of: Some(main_guest.id.clone()),
             parent_guest_id: guest_data.parent_guest_id,
             is_plus_one: Some(true),
             created_at: chrono::Utc::now().to_rfc3339(),
-        };
+            checked_in_at: None,
+            checked_in_by: None,
+            checkin_pass: None,
+        });
+    }
 
-        db.add_guest(&guest).await?;
-        created_guests.push(guest);
+    check_plus_ones(&event, &party[1..])?;
+
+    // Guests past the last free seat join the waitlist
+    for (index, guest) in party.iter_mut().enumerate() {
+        if !event.has_room(index as i32 + 1) {
+            guest.status = GuestStatus::Waitlisted;
+        }
     }
 
+    let mut guests = db.get_event_guests(&event_id).await?;
+    event.add_guests(db, &mut guests, &mut party).await?;
+    for guest in &party {
+        publish_guest_update(guest, false).await;
+    }
+
+    let created_guests = party;
+
     // Send welcome emails to all guests
     if let EmailVerificationConfig::Enabled { smtp, .. } = &authifier.config.email_verification {
-        let event = db.fetch_event(None, &event_id).await?;
-        let welcome_message = format!(
-            "Welcome to {}/events/view/{}\n
-            You have been added as a guest to this event. \n
-            Your approval is pending, we will notify you when it is approved.",
-            *APP_URL, event_id
-        );
-
         for guest in &created_guests {
+            let welcome_message = if guest.status == GuestStatus::Waitlisted {
+                format!(
+                    "Welcome to {}/events/view/{}\n
+                    This event is currently full, so you have been added to the waitlist. \n
+                    We will notify you as soon as a spot opens up.",
+                    *APP_URL, event_id
+                )
+            } else {
+                format!(
+                    "Welcome to {}/events/view/{}\n
+                    You have been added as a guest to this event. \n
+                    Your approval is pending, we will notify you when it is approved.",
+                    *APP_URL, event_id
+                )
+            };
+
             smtp.send_email(
                 guest.email.clone(),
                 &Template {
                     title: format!("Welcome to {}!", event.title),
-                    text: include_str!(concat!(
-                        env!("CARGO_MANIFEST_DIR"),
-                        "/assets/templates/event.txt"
-                    ))
-                    .into(),
-                    url: format!("{}/events/view/{}", *APP_URL, event_id),
-                    html: Some(
-                        include_str!(concat!(
-                            env!("CARGO_MANIFEST_DIR"),
-                            "/assets/templates/event.html"
-                        ))
-                        .into(),
-                    ),
+                    text: EVENT_TEXT.into(),
+                    url: guest_url(guest),
+                    html: Some(EVENT_HTML.into()),
                 },
                 json!({
                     "email": guest.email.clone(),
-                    "url": format!("{}/events/view/{}", *APP_URL, event_id),
-                    "content": welcome_message.clone(),
+                    "url": guest_url(guest),
+                    "content": welcome_message,
                 }),
             );
         }
@@ -483,19 +674,9 @@ pub async fn add_bulk_guests(
                     email.clone(),
                     &Template {
                         title: format!("New Guest Registration - {}", event.title),
-                        text: include_str!(concat!(
-                            env!("CARGO_MANIFEST_DIR"),
-                            "/assets/templates/event.txt"
-                        ))
-                        .into(),
+                        text: EVENT_TEXT.into(),
                         url: format!("{}/events/view/{}", *APP_URL, event_id),
-                        html: Some(
-                            include_str!(concat!(
-                                env!("CARGO_MANIFEST_DIR"),
-                                "/assets/templates/event.html"
-                            ))
-                            .into(),
-                        ),
+                        html: Some(EVENT_HTML.into()),
                     },
                     json!({
                         "email": email.clone(),
@@ -513,236 +694,342 @@ pub async fn add_bulk_guests(
 }
 
 /// Send DM messages to multiple users
+///
+/// Messages are queued up and sent in the background, use the returned job id to follow their progress.
 #[openapi(tag = "Events")]
 #[post("/<event_id>/guests/message", data = "<data>")]
 pub async fn send_bulk_messages(
-    authifier: &State<Authifier>,
     db: &State<Database>,
     user: User,
     event_id: String,
     data: Json<BulkMessageData>,
-) -> Result<Json<()>> {
+) -> Result<Json<MessageJobResponse>> {
     let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    // Verify sender may message the event's guests
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::MessageGuests)?;
+
+    let url = format!("{}/events/view/{}", *APP_URL, event_id);
+    let recipients = data
+        .messages
+        .into_iter()
+        .map(|message| {
+            MessageRecipient::new(
+                Some(message.user_id),
+                None,
+                None,
+                message.content,
+                url.clone(),
+            )
+        })
+        .collect();
 
-    // Verify sender is event owner or host
-    let event = db.fetch_event(Some(&user.id), &event_id).await?;
-    if event.created_by.as_deref() != Some(&user.id) && !event.hosts.contains(&user.id) {
-        return Err(Error::NotFound);
-    }
-
-    // Get current invited_count or initialize to 0
-    let mut invited_count = event.invited_count.unwrap_or(0);
-
-    // Send messages
-    for message in data.messages {
-        // Find or create DM channel
-        let channel = if let Ok(channel) = db
-            .find_direct_message_channel(&user.id, &message.user_id)
-            .await
-        {
-            channel
-        } else {
-            let new_channel = Channel::DirectMessage {
-                id: Ulid::new().to_string(),
-                active: true,
-                recipients: vec![user.id.clone(), message.user_id.clone()],
-                last_message_id: None,
-            };
-
-            new_channel.create(db).await?;
-            new_channel
-        };
-
-        // Send the message
-        let mut msg = Message {
-            id: Ulid::new().to_string(),
-            channel: channel.id().to_string(),
-            author: user.id.clone(),
-            content: Some(format!(
-                "{} is inviting you to {} event:\n\n{}/events/view/{}{}",
-                user.username,
-                event.title,
-                *APP_URL,
-                event_id,
-                message.content.as_ref().map(|c| format!("\n\n{}", c)).unwrap_or_default()
-            )),
-            ..Default::default()
-        };
-
-        // Create the message with proper notification handling
-        msg.create(db, &channel, Some(MessageAuthor::User(&user)))
-            .await?;
-
-        // Update channel as active if it wasn't already
-        if let Channel::DirectMessage { active, .. } = &channel {
-            if !active {
-                db.update_channel(
-                    &channel.id(),
-                    &PartialChannel {
-                        active: Some(true),
-                        ..Default::default()
-                    },
-                    vec![],
-                )
-                .await?;
-            }
-        }
-
-        // Send email notification
-        if let EmailVerificationConfig::Enabled { smtp, .. } = &authifier.config.email_verification
-        {
-            if let Ok(recipient) = db.inner().fetch_user(&message.user_id).await {
-                if let Ok(account) = authifier.database.find_account(&recipient.id).await {
-                    let email = account.email.clone();
-                    smtp.send_email(
-                        email,
-                        &Template {
-                            title: format!("New message from {} - {}", user.username, event.title),
-                            text: include_str!(concat!(
-                                env!("CARGO_MANIFEST_DIR"),
-                                "/assets/templates/event.txt"
-                            ))
-                            .into(),
-                            url: format!("{}/events/view/{}", *APP_URL, event_id),
-                            html: Some(
-                                include_str!(concat!(
-                                    env!("CARGO_MANIFEST_DIR"),
-                                    "/assets/templates/event.html"
-                                ))
-                                .into(),
-                            ),
-                        },
-                        json!({
-                            "email": account.email.clone(),
-                            "url": format!("{}/events/view/{}", *APP_URL, event_id),
-                            "content": format!(
-                                "{} is inviting you to {} event:\n\n{}/events/view/{}{}\n\n", 
-                                user.username, 
-                                event.title, 
-                                *APP_URL, 
-                                event_id,
-                                message.content.as_ref().map(|c| format!("\n\n{}", c)).unwrap_or_default()
-                            ),
-                        }),
-                    );
-                }
-            }
-        }
-
-        // Increment the counter for each guest invited
-        invited_count += 1;
-    }
-
-    // Update the event with the new invited_count
-    db.update_event(
-        &event_id,
-        &PartialEvent {
-            invited_count: Some(invited_count),
-            ..Default::default()
-        },
+    let job = event_messages::queue(
+        db,
+        &event,
+        &user,
+        MessageJobKind::Invitation,
+        format!("New message from {} - {}", user.username, event.title),
+        format!("{} is inviting you to {} event.", user.username, event.title),
+        recipients,
     )
     .await?;
 
-    Ok(Json(()))
+    Ok(Json(MessageJobResponse { job_id: job.id }))
 }
 
 /// Send messages to guests filtered by status
+///
+/// Messages are queued up and sent in the background, use the returned job id to follow their progress.
 #[openapi(tag = "Events")]
 #[post("/<event_id>/guests/notify", data = "<data>")]
 pub async fn notify_guests(
-    authifier: &State<Authifier>,
     db: &State<Database>,
     user: User,
     event_id: String,
     data: Json<GuestMessageData>,
-) -> Result<Json<()>> {
+) -> Result<Json<MessageJobResponse>> {
     let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
 
-    // Verify sender is event owner or host
-    let event = db.fetch_event(Some(&user.id), &event_id).await?;
-    if event.created_by.as_deref() != Some(&user.id) && !event.hosts.contains(&user.id) {
-        return Err(Error::NotFound);
-    }
+    // Verify sender may message the event's guests
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::MessageGuests)?;
 
     // Get all guests for this event with matching statuses
-    let guests = db.get_event_guests(&event_id).await?;
-    let filtered_guests: Vec<&EventGuest> = guests
+    let recipients = db
+        .get_event_guests(&event_id)
+        .await?
+        .into_iter()
+        .filter(|g| data.statuses.contains(&g.status))
+        .map(|guest| {
+            MessageRecipient::new(
+                guest.user_id.clone(),
+                Some(guest.id.clone()),
+                Some(guest.email.clone()),
+                None,
+                guest_url(&guest),
+            )
+        })
+        .collect();
+
+    let job = event_messages::queue(
+        db,
+        &event,
+        &user,
+        MessageJobKind::Notification,
+        format!("Notification from event - {}", event.title),
+        data.content,
+        recipients,
+    )
+    .await?;
+
+    Ok(Json(MessageJobResponse { job_id: job.id }))
+}
+
+/// Fetch the progress of a message job
+///
+/// Lists every recipient which failed, or is waiting to be retried, along with the reason.
+#[openapi(tag = "Events")]
+#[get("/<event_id>/guests/message/<job_id>", rank = 2)]
+pub async fn get_message_job(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    job_id: String,
+) -> Result<Json<MessageJobProgress>> {
+    let event = db.fetch_event(EventViewer::User(&user.id), &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::MessageGuests)?;
+
+    let job = db.fetch_event_message_job(&event_id, &job_id).await?;
+    Ok(Json(MessageJobProgress {
+        total: job.recipients.len(),
+        sent: job.count(&DeliveryStatus::Sent),
+        failed: job.count(&DeliveryStatus::Failed),
+        pending: job.count(&DeliveryStatus::Pending),
+        failures: job
+            .recipients
+            .into_iter()
+            .filter(|recipient| recipient.error.is_some())
+            .collect(),
+        id: job.id,
+        status: job.status,
+    }))
+}
+
+// Helper function to promote waitlisted guests into released seats, first come first served
+pub(crate) async fn promote_waitlisted_guests(
+    authifier: &Authifier,
+    db: &Database,
+    user: &User,
+    event_id: &str,
+) -> Result<()> {
+    let event = db.fetch_event(EventViewer::System, event_id).await?;
+    for guest in event.promote_waitlisted_guests(db, &user.id).await? {
+        publish_guest_update(&guest, false).await;
+        sync_event_channel(db, &user.id, &guest).await?;
+
+        let pass = guest.create_checkin_pass();
+        notify_guest(
+            authifier,
+            db,
+            user,
+            &event,
+            &guest,
+            format!("A spot opened up at {}!", event.title),
+            format!("{}/events/pass/{}", *APP_URL, pass),
+            format!(
+                "A spot opened up at {} and you have been moved off the waitlist.\n\nShow the QR code on your check-in pass at the door.\nPass code: {}",
+                event.title, pass
+            ),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Helper function to ensure a guest may be moved between two statuses
+pub(crate) fn check_guest_status_transition(from: &GuestStatus, to: &GuestStatus) -> Result<()> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(Error::InvalidGuestStatusTransition {
+            from: from.clone().into(),
+            to: to.clone().into(),
+        })
+    }
+}
+
+// Helper function to change a guest's status and record who changed it
+//
+// Returns the status the guest was moved to, which is the waitlist for
+// approvals the event has no seats left for.
+pub(crate) async fn set_guest_status(
+    db: &Database,
+    changed_by: &str,
+    event: &Event,
+    guests: &mut [EventGuest],
+    guest_id: &str,
+    status: GuestStatus,
+) -> Result<GuestStatus> {
+    set_guests_status(
+        db,
+        changed_by,
+        event,
+        guests,
+        &[guest_id.to_string()],
+        status,
+    )
+    .await
+}
+
+// Helper function to change the status of several guests at once
+//
+// The guests either all fit within the event's capacity or are all waitlisted.
+// Fails if a guest's status was changed by someone else in the meantime.
+pub(crate) async fn set_guests_status(
+    db: &Database,
+    changed_by: &str,
+    event: &Event,
+    guests: &mut [EventGuest],
+    ids: &[String],
+    status: GuestStatus,
+) -> Result<GuestStatus> {
+    let before: Vec<EventGuest> = guests
         .iter()
-        .filter(|g| data.statuses.contains(&g.status.clone()))
+        .filter(|guest| ids.contains(&guest.id))
+        .cloned()
         .collect();
 
-    // Process each guest
-    for guest in filtered_guests {
-        if let Some(user_id) = &guest.user_id {
-            // Find or create DM channel and send message
-            let channel =
-                if let Ok(channel) = db.find_direct_message_channel(&user.id, user_id).await {
-                    channel
-                } else {
-                    let new_channel = Channel::DirectMessage {
-                        id: Ulid::new().to_string(),
-                        active: false,
-                        recipients: vec![user.id.clone(), user_id.clone()],
-                        last_message_id: None,
-                    };
-
-                    new_channel.create(db).await?;
-                    new_channel
-                };
+    let status = event
+        .set_guests_status(db, changed_by, guests, ids, status)
+        .await?;
+
+    let mut stale = false;
+    for previous in before {
+        let guest = guests
+            .iter()
+            .find(|guest| guest.id == previous.id)
+            .ok_or(Error::NotFound)?;
+
+        if guest.status == previous.status {
+            stale |= guest.status != status;
+            continue;
+        }
+
+        publish_guest_update(guest, previous.status == GuestStatus::Approved).await;
+        sync_event_channel(db, changed_by, guest).await?;
+    }
 
-            // Send DM
-            let msg = Message {
+    if stale {
+        return Err(Error::InvalidOperation);
+    }
+
+    Ok(status)
+}
+
+// Helper function to publish a guest change to the event topic
+//
+// Guests who are not subscribed to the event yet also receive it directly.
+pub(crate) async fn publish_guest_update(guest: &EventGuest, subscribed: bool) {
+    let event = EventV1::EventGuestUpdate {
+        id: guest.event_id.clone(),
+        guest: guest.clone(),
+    };
+
+    if let Some(user_id) = guest.user_id.as_ref().filter(|_| !subscribed) {
+        event.clone().private(user_id.clone()).await;
+    }
+
+    event.p(guest.event_id.clone()).await;
+}
+
+// Helper function to link a guest to the event
+//
+// Guests without an account get their RSVP link instead, as they cannot open the event page.
+pub(crate) fn guest_url(guest: &EventGuest) -> String {
+    if guest.user_id.is_some() {
+        format!("{}/events/view/{}", *APP_URL, guest.event_id)
+    } else {
+        format!(
+            "{}/events/invitation/{}",
+            *APP_URL,
+            guest.create_rsvp_token()
+        )
+    }
+}
+
+// Helper function to notify a guest by DM (if they have an account) and email
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn notify_guest(
+    authifier: &Authifier,
+    db: &Database,
+    user: &User,
+    event: &Event,
+    guest: &EventGuest,
+    title: String,
+    url: String,
+    content: String,
+) -> Result<()> {
+    if let Some(user_id) = guest.user_id.as_ref().filter(|id| *id != &user.id) {
+        // Find or create DM channel and send message
+        let channel = if let Ok(channel) = db.find_direct_message_channel(&user.id, user_id).await {
+            channel
+        } else {
+            let new_channel = Channel::DirectMessage {
                 id: Ulid::new().to_string(),
-                channel: channel.id().to_string(),
-                author: user.id.clone(),
-                content: Some(format!(
-                    "{} is inviting you to this event: {}/events/view/{}",
-                    user.username, *APP_URL, event_id
-                )),
-                ..Default::default()
+                active: false,
+                recipients: vec![user.id.clone(), user_id.clone()],
+                last_message_id: None,
             };
 
-            db.insert_message(&msg).await?;
-        }
-        if let EmailVerificationConfig::Enabled {
-            templates,
-            expiry,
-            smtp,
-        } = &authifier.config.email_verification
-        {
-            smtp.send_email(
-                guest.email.clone(),
-                &Template {
-                    title: format!("Notification from event - {}", event.title),
-                    text: include_str!(concat!(
-                        env!("CARGO_MANIFEST_DIR"),
-                        "/assets/templates/event.txt"
-                    ))
-                    .into(),
-                    url: format!("{}/events/view/{}", *APP_URL, event_id),
-                    html: Some(
-                        include_str!(concat!(
-                            env!("CARGO_MANIFEST_DIR"),
-                            "/assets/templates/event.html"
-                        ))
-                        .into(),
-                    ),
-                },
-                json!({
-                    "email": guest.email.clone(),
-                    "url": format!("{}/events/view/{}", *APP_URL, event_id),
-                    "title": event.title.clone(),
-                    "content": data.content.clone(),
-                }),
-            );
-        }
+            new_channel.create(db).await?;
+            new_channel
+        };
+
+        let msg = Message {
+            id: Ulid::new().to_string(),
+            channel: channel.id().to_string(),
+            author: user.id.clone(),
+            content: Some(format!("{}\n\n{}", content, url)),
+            ..Default::default()
+        };
+
+        db.insert_message(&msg).await?;
     }
 
-    Ok(Json(()))
+    if guest.email.is_empty() {
+        return Ok(());
+    }
+
+    if let EmailVerificationConfig::Enabled { smtp, .. } = &authifier.config.email_verification {
+        smtp.send_email(
+            guest.email.clone(),
+            &Template {
+                title,
+                text: EVENT_TEXT.into(),
+                url: url.clone(),
+                html: Some(EVENT_HTML.into()),
+            },
+            json!({
+                "email": guest.email.clone(),
+                "url": url,
+                "title": event.title.clone(),
+                "content": content,
+            }),
+        );
+    }
+
+    Ok(())
 }
 
 // Helper function to convert validation errors to strings
-fn validation_errors_to_strings(errors: &validator::ValidationErrors) -> Vec<String> {
+pub(crate) fn validation_errors_to_strings(errors: &validator::ValidationErrors) -> Vec<String> {
     errors
         .field_errors()
         .iter()
@@ -0,0 +1,371 @@
+use super::event_guests::{
+    check_guest_status_transition, promote_waitlisted_guests, publish_guest_update,
+    set_guest_status, set_guests_status,
+};
+use super::event_rsvp::{
+    active_plus_ones, replace_guest, update_plus_ones, DataEditRsvp, DataPlusOne,
+};
+use chrono::{DateTime, Utc};
+use revolt_quark::authifier::Authifier;
+use revolt_quark::models::events::event::{Event, EventType, EventViewer, GeoPoint};
+use revolt_quark::models::events::guest::{
+    count_seats, verify_rsvp_token, EventGuest, GuestStatus,
+};
+use revolt_quark::models::events::questionnaire::{RsvpAnswer, RsvpQuestion};
+use revolt_quark::{Database, Error, Result};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataAcceptInvitation {
+    /// Plus-ones coming along, replacing any added before
+    pub plus_ones: Option<Vec<DataPlusOne>>,
+    /// Answers to the event's RSVP questions, replacing those given before
+    pub answers: Option<HashMap<String, RsvpAnswer>>,
+}
+
+/// Public details of the event a guest is invited to
+#[derive(Serialize, JsonSchema)]
+pub struct InvitationEvent {
+    /// Event Id
+    pub id: String,
+    /// Event title
+    pub title: String,
+    /// Event type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<EventType>,
+    /// Start date and time
+    #[serde(serialize_with = "revolt_database::util::iso_bson_chrono::serialize")]
+    pub start_date: DateTime<Utc>,
+    /// End date and time
+    #[serde(serialize_with = "revolt_database::util::iso_bson_chrono::serialize")]
+    pub end_date: DateTime<Utc>,
+    /// IANA timezone the event takes place in
+    pub timezone: String,
+    /// City where event is held
+    pub city: String,
+    /// Country where event is held
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Area/neighborhood
+    pub area: String,
+    /// Full address, hidden until the guest is approved if the event hides it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Coordinates of the venue, rounded until the guest is approved if the event hides its address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    /// Event description, only shown if the event is shown to non-members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Thumbnail image ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// Gallery image URLs, only shown if the event is shown to non-members
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gallery: Vec<String>,
+    /// Allow +1 guests
+    pub allow_plus_one: bool,
+    /// Maximum number of +1 guests allowed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_plus_one_amount: Option<i32>,
+    /// Require full information for +1 guests
+    pub requires_plus_one_info: bool,
+    /// Require RSVP approval by host
+    pub requires_rsvp_approval: bool,
+    /// Questions the guest is asked when accepting
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub questions: Vec<RsvpQuestion>,
+}
+
+impl InvitationEvent {
+    fn new(event: Event, guest: &EventGuest) -> InvitationEvent {
+        let show_address = !event.hide_address || guest.status == GuestStatus::Approved;
+        let show_details = event.show_to_non_members;
+
+        InvitationEvent {
+            id: event.id,
+            title: event.title,
+            event_type: event.event_type,
+            start_date: event.start_date,
+            end_date: event.end_date,
+            timezone: event.timezone,
+            city: event.city,
+            country: event.country,
+            area: event.area,
+            address: Some(event.address).filter(|_| show_address),
+            location: if show_address {
+                event.location
+            } else {
+                event.location.as_ref().map(GeoPoint::rounded)
+            },
+            description: event.description.filter(|_| show_details),
+            thumbnail: event.thumbnail,
+            gallery: if show_details { event.gallery } else { vec![] },
+            allow_plus_one: event.allow_plus_one,
+            allow_plus_one_amount: event.allow_plus_one_amount,
+            requires_plus_one_info: event.requires_plus_one_info,
+            requires_rsvp_approval: event.requires_rsvp_approval,
+            questions: event.questions,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct InvitationResponse {
+    /// Event the guest is invited to
+    event: InvitationEvent,
+    /// The invited guest
+    guest: EventGuest,
+    /// Plus-ones attached to the guest
+    plus_ones: Vec<EventGuest>,
+}
+
+/// Fetch an invitation
+///
+/// Takes the RSVP token emailed to the guest, no account is needed.
+#[openapi(tag = "Events")]
+#[get("/invitations/<token>", rank = 2)]
+pub async fn get_invitation(
+    db: &State<Database>,
+    token: String,
+) -> Result<Json<InvitationResponse>> {
+    let (event, guests, guest) = resolve_invitation(db, &token).await?;
+    Ok(Json(invitation_response(event, &guests, guest)))
+}
+
+/// Accept an invitation
+///
+/// The guest is approved straight away unless the event requires approval
+/// by a host, and lands on the waitlist if the party does not fit.
+#[openapi(tag = "Events")]
+#[post("/invitations/<token>/accept", data = "<data>")]
+pub async fn accept_invitation(
+    db: &State<Database>,
+    token: String,
+    data: Json<DataAcceptInvitation>,
+) -> Result<Json<InvitationResponse>> {
+    let data = data.into_inner();
+    for plus_one in data.plus_ones.iter().flatten() {
+        plus_one
+            .validate()
+            .map_err(|error| Error::FailedValidation { error })?;
+    }
+
+    let (event, mut guests, mut guest) = resolve_invitation(db, &token).await?;
+    event.check_open()?;
+    if guest.status != GuestStatus::Approved {
+        check_guest_status_transition(&guest.status, &GuestStatus::Approved)?;
+    }
+
+    // Plus-ones are not asked the event's questions
+    if guest.plus_one_of.is_none() {
+        let answers = data.answers.unwrap_or_else(|| guest.answers.clone());
+        let answers = RsvpQuestion::check_answers(&event.questions, answers)?;
+        if answers != guest.answers {
+            guest.answers = answers;
+            db.update_guest_details(&guest).await?;
+            replace_guest(&mut guests, &guest);
+        }
+    }
+
+    let changed_by = acting_id(&guest);
+    if let Some(plus_ones) = data.plus_ones {
+        update_plus_ones(db, &changed_by, &event, &mut guests, &guest, plus_ones).await?;
+    }
+
+    // Waitlisted guests keep their place in the queue
+    if guest.status == GuestStatus::Pending {
+        let mut party = vec![guest.id.clone()];
+        party.extend(
+            active_plus_ones(&guests, &guest.id)
+                .into_iter()
+                .map(|plus_one| plus_one.id),
+        );
+
+        let status = event.admission_status(party.len() as i32);
+        set_guests_status(db, &changed_by, &event, &mut guests, &party, status).await?;
+    }
+
+    let guest = find_guest(&guests, &guest.id)?;
+    Ok(Json(invitation_response(event, &guests, guest)))
+}
+
+/// Decline an invitation
+///
+/// Withdraws the guest and their plus-ones, this cannot be undone.
+#[openapi(tag = "Events")]
+#[post("/invitations/<token>/decline")]
+pub async fn decline_invitation(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    token: String,
+) -> Result<Json<InvitationResponse>> {
+    let (event, mut guests, guest) = resolve_invitation(db, &token).await?;
+    check_guest_status_transition(&guest.status, &GuestStatus::Cancelled)?;
+
+    let seats_before = count_seats(&guests);
+    let changed_by = acting_id(&guest);
+
+    for plus_one in active_plus_ones(&guests, &guest.id) {
+        set_guest_status(
+            db,
+            &changed_by,
+            &event,
+            &mut guests,
+            &plus_one.id,
+            GuestStatus::Cancelled,
+        )
+        .await?;
+    }
+
+    set_guest_status(
+        db,
+        &changed_by,
+        &event,
+        &mut guests,
+        &guest.id,
+        GuestStatus::Cancelled,
+    )
+    .await?;
+
+    if count_seats(&guests) < seats_before {
+        promote_as_owner(authifier, db, &event).await?;
+    }
+
+    let guest = find_guest(&guests, &guest.id)?;
+    Ok(Json(invitation_response(event, &guests, guest)))
+}
+
+/// Edit an invitation's details and plus-ones
+#[openapi(tag = "Events")]
+#[patch("/invitations/<token>", data = "<data>", rank = 2)]
+pub async fn edit_invitation(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    token: String,
+    data: Json<DataEditRsvp>,
+) -> Result<Json<InvitationResponse>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+    for plus_one in data.plus_ones.iter().flatten() {
+        plus_one
+            .validate()
+            .map_err(|error| Error::FailedValidation { error })?;
+    }
+
+    let (event, mut guests, mut guest) = resolve_invitation(db, &token).await?;
+    if matches!(guest.status, GuestStatus::Rejected | GuestStatus::Cancelled) {
+        return Err(Error::InvalidGuestStatusTransition {
+            from: guest.status.into(),
+            to: GuestStatus::Pending.into(),
+        });
+    }
+
+    let seats_before = count_seats(&guests);
+
+    if let Some(name) = data.name {
+        guest.name = name;
+    }
+
+    if let Some(phone) = data.phone {
+        guest.phone = phone;
+    }
+
+    if let Some(answers) = data.answers {
+        guest.answers = RsvpQuestion::check_answers(&event.questions, answers)?;
+    }
+
+    db.update_guest_details(&guest).await?;
+    publish_guest_update(&guest, guest.status == GuestStatus::Approved).await;
+    replace_guest(&mut guests, &guest);
+
+    if let Some(plus_ones) = data.plus_ones {
+        if guest.plus_one_of.is_some() {
+            return Err(Error::PlusOnesNotAllowed);
+        }
+
+        let changed_by = acting_id(&guest);
+        update_plus_ones(db, &changed_by, &event, &mut guests, &guest, plus_ones).await?;
+    }
+
+    if count_seats(&guests) < seats_before {
+        promote_as_owner(authifier, db, &event).await?;
+    }
+
+    Ok(Json(invitation_response(event, &guests, guest)))
+}
+
+// Helper function to look up the event and guest an RSVP token was issued for
+async fn resolve_invitation(
+    db: &Database,
+    token: &str,
+) -> Result<(Event, Vec<EventGuest>, EventGuest)> {
+    let (event_id, guest_id) = verify_rsvp_token(token).ok_or(Error::InvalidInvitation)?;
+
+    let event = match db.fetch_event(EventViewer::System, &event_id).await {
+        Ok(event) => event,
+        Err(Error::NotFound) => return Err(Error::InvalidInvitation),
+        Err(error) => return Err(error),
+    };
+
+    // Tokens stop working once the event is over
+    if event.end_date <= Utc::now() {
+        return Err(Error::InvitationExpired);
+    }
+
+    let guests = db.get_event_guests(&event_id).await?;
+    let guest = guests
+        .iter()
+        .find(|guest| guest.id == guest_id)
+        .cloned()
+        .ok_or(Error::InvalidInvitation)?;
+
+    Ok((event, guests, guest))
+}
+
+// Helper function to build the response for an invitation
+fn invitation_response(
+    event: Event,
+    guests: &[EventGuest],
+    mut guest: EventGuest,
+) -> InvitationResponse {
+    if guest.status == GuestStatus::Approved {
+        guest.checkin_pass = Some(guest.create_checkin_pass());
+    }
+
+    InvitationResponse {
+        event: InvitationEvent::new(event, &guest),
+        plus_ones: active_plus_ones(guests, &guest.id),
+        guest,
+    }
+}
+
+// Helper function to find a guest in the guest list
+fn find_guest(guests: &[EventGuest], guest_id: &str) -> Result<EventGuest> {
+    guests
+        .iter()
+        .find(|guest| guest.id == guest_id)
+        .cloned()
+        .ok_or(Error::NotFound)
+}
+
+// Helper function to pick who status changes made through a token are recorded against
+//
+// Guests without an account are recorded by their guest id.
+fn acting_id(guest: &EventGuest) -> String {
+    guest.user_id.clone().unwrap_or_else(|| guest.id.clone())
+}
+
+// Helper function to fill released seats on behalf of the event owner
+async fn promote_as_owner(authifier: &Authifier, db: &Database, event: &Event) -> Result<()> {
+    if let Some(owner_id) = event.created_by.as_ref().or_else(|| event.hosts.first()) {
+        let owner = db.fetch_user(owner_id).await?;
+        promote_waitlisted_guests(authifier, db, &owner, &event.id).await?;
+    }
+
+    Ok(())
+}
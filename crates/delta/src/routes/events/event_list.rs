@@ -1,27 +1,125 @@
-use revolt_quark::models::event::{Event, EventType};
+use chrono_tz::Tz;
+use revolt_quark::models::event::{
+    parse_event_date, Event, EventListResponse, EventQuery, EventStatus, EventType, GeoPoint,
+    GeoRadius,
+};
 use revolt_quark::models::user::User;
-use revolt_quark::{Database, Result};
+use revolt_quark::{Database, Error, Result};
 use rocket::{serde::json::Json, State};
 use serde::Deserialize;
+use std::convert::TryFrom;
+use validator::Validate;
 
-#[derive(Deserialize, JsonSchema)]
-pub struct EventQuery {
+/// Radius searched around a point if none is given, in metres
+const DEFAULT_SEARCH_RADIUS: f64 = 10_000.0;
+
+/// # Query Parameters
+#[derive(Validate, Deserialize, JsonSchema, FromForm)]
+pub struct OptionsQueryEvents {
+    /// Event type
     pub event_type: Option<EventType>,
+    /// Only include events starting at or after this date
     pub start_date_from: Option<String>,
+    /// Only include events starting at or before this date
     pub start_date_to: Option<String>,
+    /// City where event is held
     pub city: Option<String>,
+    /// Event status, defaults to every status but drafts
+    ///
+    /// Drafts are only listed for their own staff.
+    pub status: Option<EventStatus>,
+    /// Latitude of the point to search around, requires `longitude`
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+    /// Longitude of the point to search around, requires `latitude`
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    /// Search radius in metres, defaults to 10 km
+    #[validate(range(min = 1.0, max = 500000.0))]
+    pub radius: Option<f64>,
+    /// Page number, starting at 1
+    #[validate(range(min = 1))]
     pub page: Option<i64>,
+    /// Number of events per page
+    #[validate(range(min = 1, max = 100))]
     pub per_page: Option<i64>,
 }
 
+impl TryFrom<OptionsQueryEvents> for EventQuery {
+    type Error = Error;
+
+    fn try_from(options: OptionsQueryEvents) -> Result<Self> {
+        let near = match (options.latitude, options.longitude) {
+            (Some(latitude), Some(longitude)) => Some(GeoRadius {
+                center: GeoPoint::new(latitude, longitude),
+                radius: options.radius.unwrap_or(DEFAULT_SEARCH_RADIUS),
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(Error::InvalidRequest {
+                    code: "invalid_location".to_string(),
+                    errors: vec!["latitude: must be given along with longitude".to_string()],
+                })
+            }
+        };
+
+        Ok(Self {
+            event_type: options.event_type,
+            start_date_from: options
+                .start_date_from
+                .as_deref()
+                .map(|date| parse_event_date("start_date_from", date, &Tz::UTC))
+                .transpose()?,
+            start_date_to: options
+                .start_date_to
+                .as_deref()
+                .map(|date| parse_event_date("start_date_to", date, &Tz::UTC))
+                .transpose()?,
+            city: options.city,
+            status: options.status,
+            near,
+            page: options.page,
+            per_page: options.per_page,
+        })
+    }
+}
+
 /// List all events with optional filtering
+///
+/// Pass `latitude` and `longitude` to only list events within `radius`
+/// metres of that point, nearest first.
 #[openapi(tag = "Events")]
-#[get("/")]
-pub async fn list_events(db: &State<Database>, user: Option<User>) -> Result<Json<Vec<Event>>> {
-    let events = db
-        .fetch_events(user.as_ref().map(|u| u.id.as_str()), &[])
+#[get("/?<options..>")]
+pub async fn list_events(
+    db: &State<Database>,
+    user: Option<User>,
+    options: OptionsQueryEvents,
+) -> Result<Json<EventListResponse>> {
+    options
+        .validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let query = EventQuery::try_from(options)?;
+    let (mut events, total) = db
+        .query_events(user.as_ref().map(|u| u.id.as_str()).into(), &query)
         .await?;
-    Ok(Json(events))
+
+    // Measured from the coordinates the viewer gets so distances do not give hidden venues away
+    if let Some(near) = &query.near {
+        for event in &mut events {
+            event.distance = event
+                .location
+                .as_ref()
+                .map(|location| location.distance_to(&near.center));
+        }
+    }
+
+    Ok(Json(EventListResponse {
+        events,
+        total,
+        page: query.page(),
+        per_page: query.per_page(),
+    }))
 }
 
 /// Get all events created by the current user
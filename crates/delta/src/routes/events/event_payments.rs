@@ -0,0 +1,165 @@
+use super::event_tickets::release_ticket;
+use revolt_quark::models::events::ticket::{PaymentStatus, TicketStatus};
+use revolt_quark::payments::{self, FakeProvider, PaymentNotification};
+use revolt_quark::{Database, Error, Result};
+use revolt_rocket_okapi::{
+    gen::OpenApiGenerator,
+    request::{OpenApiFromRequest, RequestHeaderInput},
+    revolt_okapi::openapi3::{MediaType, Parameter, ParameterValue},
+};
+use rocket::{http::Status, request::FromRequest, Request, State};
+use schemars::schema::SchemaObject;
+
+/// Signature the payment provider sends its webhooks with
+pub struct PaymentSignature<'r>(pub &'r str);
+
+impl<'r> std::ops::Deref for PaymentSignature<'r> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for PaymentSignature<'r> {
+    type Error = Error;
+
+    async fn from_request(request: &'r Request<'_>) -> rocket::request::Outcome<Self, Self::Error> {
+        match request.headers().get_one("X-Payment-Signature") {
+            Some(signature) => rocket::request::Outcome::Success(Self(signature)),
+            None => rocket::request::Outcome::Failure((
+                Status::BadRequest,
+                Error::InvalidPaymentWebhook,
+            )),
+        }
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for PaymentSignature<'r> {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> revolt_rocket_okapi::Result<RequestHeaderInput> {
+        let mut content = schemars::Map::new();
+        content.insert(
+            "X-Payment-Signature".to_string(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    string: Some(Box::default()),
+                    ..Default::default()
+                }),
+                example: None,
+                examples: None,
+                encoding: schemars::Map::new(),
+                extensions: schemars::Map::new(),
+            },
+        );
+
+        Ok(RequestHeaderInput::Parameter(Parameter {
+            name: "X-Payment-Signature".to_string(),
+            location: "header".to_string(),
+            required: true,
+            description: Some("Signature of the webhook body".to_string()),
+            deprecated: false,
+            allow_empty_value: false,
+            value: ParameterValue::Content { content },
+            extensions: schemars::Map::new(),
+        }))
+    }
+}
+
+/// Receive a webhook from the payment provider
+///
+/// Reserved tickets are only confirmed once the provider tells us they were paid for.
+#[openapi(tag = "Events")]
+#[post("/payments/webhook", data = "<data>")]
+pub async fn payment_webhook(
+    db: &State<Database>,
+    signature: PaymentSignature<'_>,
+    data: String,
+) -> Result<()> {
+    let notification = payments::provider()?.verify_webhook(&signature, &data)?;
+    apply_payment_notification(db, notification).await
+}
+
+/// Complete a checkout with the fake payment provider
+///
+/// Stands in for the provider's checkout page and sends the webhook it
+/// would, failing the checkout instead if `failed` is set. Only mounted
+/// when the fake provider is in use.
+#[post("/payments/fake/<session_id>?<failed>")]
+pub async fn complete_fake_checkout(
+    db: &State<Database>,
+    session_id: String,
+    failed: Option<bool>,
+) -> Result<()> {
+    let provider = payments::provider()?;
+    if provider.name() != "fake" {
+        return Err(Error::NotFound);
+    }
+
+    let notification = if failed.unwrap_or(false) {
+        PaymentNotification::Failed { session_id }
+    } else {
+        PaymentNotification::Completed { session_id }
+    };
+
+    let (signature, body) = FakeProvider::webhook(&notification);
+    let notification = provider.verify_webhook(&signature, &body)?;
+    apply_payment_notification(db, notification).await
+}
+
+// Helper function to update a ticket with what the payment provider told us
+//
+// Providers may deliver a webhook more than once, so anything already handled is ignored.
+async fn apply_payment_notification(
+    db: &Database,
+    notification: PaymentNotification,
+) -> Result<()> {
+    let ticket = db
+        .fetch_ticket_by_payment(notification.session_id())
+        .await?;
+    let mut payment = ticket.payment.ok_or(Error::InvalidPaymentWebhook)?;
+
+    match notification {
+        PaymentNotification::Completed { .. } => {
+            if payment.status != PaymentStatus::Pending {
+                return Ok(());
+            }
+
+            payment.status = PaymentStatus::Paid;
+            if ticket.status == TicketStatus::Reserved {
+                db.confirm_ticket(&ticket.event_id, &ticket.id).await?;
+            } else {
+                // The ticket was cancelled before the buyer paid, so hand the money back
+                payment.refund_id = Some(payments::provider()?.refund(&payment).await?);
+                payment.status = PaymentStatus::Refunded;
+            }
+        }
+        PaymentNotification::Failed { .. } => {
+            if payment.status != PaymentStatus::Pending {
+                return Ok(());
+            }
+
+            payment.status = PaymentStatus::Failed;
+            if ticket.status == TicketStatus::Reserved {
+                release_ticket(db, &ticket.event_id, &ticket.id).await?;
+            }
+        }
+        PaymentNotification::Refunded { .. } => {
+            if payment.status == PaymentStatus::Refunded {
+                return Ok(());
+            }
+
+            payment.status = PaymentStatus::Refunded;
+            if ticket.status != TicketStatus::Cancelled {
+                release_ticket(db, &ticket.event_id, &ticket.id).await?;
+            }
+        }
+    }
+
+    db.set_ticket_payment(&ticket.event_id, &ticket.id, &payment)
+        .await
+}
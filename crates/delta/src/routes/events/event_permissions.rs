@@ -0,0 +1,113 @@
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::{Event, EventViewer, PartialEvent};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, Result, ALL_EVENT_PERMISSIONS};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DataSetEventPermissions {
+    /// Event permission bit flags
+    pub permissions: u32,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct EventPermissionsResponse {
+    /// Event permission bit flags
+    permissions: u32,
+}
+
+/// Fetch your permissions on an event
+#[openapi(tag = "Events")]
+#[get("/<event_id>/permissions/@me")]
+pub async fn get_event_permissions(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<EventPermissionsResponse>> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    Ok(Json(EventPermissionsResponse {
+        permissions: event.permissions_for(&user.id),
+    }))
+}
+
+/// Assign permissions to a host or manager
+///
+/// Only the event's creator can assign permissions.
+#[openapi(tag = "Events")]
+#[put("/<event_id>/permissions/<user_id>", data = "<data>")]
+pub async fn set_event_permissions(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    user_id: String,
+    data: Json<DataSetEventPermissions>,
+) -> Result<Json<Event>> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    let mut staff_permissions = staff_permissions(&event, &user, &user_id)?;
+
+    staff_permissions.insert(user_id, data.permissions & *ALL_EVENT_PERMISSIONS);
+    update_staff_permissions(db, &user, &event_id, staff_permissions).await
+}
+
+/// Reset a host or manager to the default permissions of their role
+///
+/// Only the event's creator can reset permissions.
+#[openapi(tag = "Events")]
+#[delete("/<event_id>/permissions/<user_id>")]
+pub async fn reset_event_permissions(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    user_id: String,
+) -> Result<Json<Event>> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    let mut staff_permissions = staff_permissions(&event, &user, &user_id)?;
+
+    if staff_permissions.remove(&user_id).is_none() {
+        return Err(Error::NoEffect);
+    }
+
+    update_staff_permissions(db, &user, &event_id, staff_permissions).await
+}
+
+// Helper function to check a user may change the permissions of a staff member
+fn staff_permissions(event: &Event, user: &User, user_id: &str) -> Result<HashMap<String, u32>> {
+    event.throw_owner(&user.id)?;
+
+    // The creator always holds every permission
+    if event.created_by.as_deref() == Some(user_id) || !event.is_staff(user_id) {
+        return Err(Error::InvalidOperation);
+    }
+
+    Ok(event.staff_permissions.clone())
+}
+
+// Helper function to save an event's staff permissions
+async fn update_staff_permissions(
+    db: &Database,
+    user: &User,
+    event_id: &str,
+    staff_permissions: HashMap<String, u32>,
+) -> Result<Json<Event>> {
+    let partial = PartialEvent {
+        staff_permissions: Some(staff_permissions),
+        ..Default::default()
+    };
+
+    db.update_event(event_id, &partial).await?;
+
+    EventV1::EventUpdate {
+        id: event_id.to_string(),
+        data: partial,
+    }
+    .p(event_id.to_string())
+    .await;
+
+    db.fetch_event(EventViewer::User(&user.id), event_id)
+        .await
+        .map(Json)
+}
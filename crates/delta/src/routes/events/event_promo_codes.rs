@@ -0,0 +1,126 @@
+use crate::util::regex::RE_PROMO_CODE;
+use chrono::Utc;
+use revolt_quark::models::event::{parse_event_date, EventViewer};
+use revolt_quark::models::events::promo_code::{PromoCode, PromoDiscount, PromoRedemption};
+use revolt_quark::models::events::ticket::TicketTier;
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use ulid::Ulid;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCreatePromoCode {
+    /// Code buyers enter, matched regardless of case
+    #[validate(length(min = 3, max = 32), regex = "RE_PROMO_CODE")]
+    pub code: String,
+    /// Discount given
+    pub discount: PromoDiscount,
+    /// Restrict the code to a tier
+    pub tier: Option<TicketTier>,
+    /// Partner the code is handed out to
+    #[validate(length(min = 1, max = 100))]
+    pub partner: Option<String>,
+    /// Maximum number of times the code can be redeemed
+    #[validate(range(min = 1))]
+    pub max_uses: Option<i32>,
+    /// When the code stops working (ISO 8601, local to the event's timezone if no offset is given)
+    pub expires_at: Option<String>,
+}
+
+/// Create a promo code
+#[openapi(tag = "Events")]
+#[post("/<event_id>/promo_codes", data = "<data>")]
+pub async fn create_promo_code(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataCreatePromoCode>,
+) -> Result<Json<PromoCode>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+    data.discount.validate()?;
+
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    let code = PromoCode::normalise(&data.code);
+    match db.fetch_promo_code(&event_id, &code).await {
+        Ok(_) => return Err(Error::PromoCodeExists),
+        Err(Error::NotFound) => {}
+        Err(error) => return Err(error),
+    }
+
+    let promo_code = PromoCode {
+        id: Ulid::new().to_string(),
+        event_id,
+        code,
+        discount: data.discount,
+        tier: data.tier,
+        partner: data.partner,
+        max_uses: data.max_uses,
+        uses: 0,
+        expires_at: data
+            .expires_at
+            .as_deref()
+            .map(|date| parse_event_date("expires_at", date, &event.tz()))
+            .transpose()?,
+        created_by: user.id,
+        created_at: Utc::now(),
+    };
+
+    db.insert_promo_code(&promo_code).await?;
+    Ok(Json(promo_code))
+}
+
+/// List an event's promo codes
+#[openapi(tag = "Events")]
+#[get("/<event_id>/promo_codes")]
+pub async fn get_promo_codes(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<Vec<PromoCode>>> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    let promo_codes = db.fetch_promo_codes(&event_id).await?;
+    Ok(Json(promo_codes))
+}
+
+/// Delete a promo code
+///
+/// Tickets already bought with the code keep their discount.
+#[openapi(tag = "Events")]
+#[delete("/<event_id>/promo_codes/<promo_code_id>")]
+pub async fn delete_promo_code(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    promo_code_id: String,
+) -> Result<()> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    db.delete_promo_code(&event_id, &promo_code_id).await
+}
+
+/// List tickets bought with an event's promo codes
+///
+/// Each redemption names the partner the code was handed out to, so hosts
+/// can see which partners drove sales.
+#[openapi(tag = "Events")]
+#[get("/<event_id>/promo_codes/redemptions")]
+pub async fn get_promo_redemptions(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<Vec<PromoRedemption>>> {
+    let event = db.fetch_event(EventViewer::System, &event_id).await?;
+    event.throw_permission(&user.id, EventPermission::ViewAnalytics)?;
+
+    let redemptions = db.fetch_promo_redemptions(&event_id).await?;
+    Ok(Json(redemptions))
+}
@@ -0,0 +1,470 @@
+use super::event_channel::sync_event_channel;
+use super::event_guests::{
+    check_guest_status_transition, promote_waitlisted_guests, publish_guest_update,
+    set_guest_status,
+};
+use revolt_quark::authifier::Authifier;
+use revolt_quark::models::event::{Event, EventViewer};
+use revolt_quark::models::events::guest::{count_seats, EventGuest, GuestStatus};
+use revolt_quark::models::events::questionnaire::{RsvpAnswer, RsvpQuestion};
+use revolt_quark::models::user::User;
+use revolt_quark::{Database, Error, Result};
+use rocket::{serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ulid::Ulid;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataPlusOne {
+    /// Id of an existing plus-one, when editing an RSVP
+    pub id: Option<String>,
+    /// Plus-one's full name
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    /// Plus-one's email
+    #[validate(email)]
+    pub email: Option<String>,
+    /// Plus-one's phone number
+    pub phone: Option<String>,
+}
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataRsvp {
+    /// Name to RSVP under, defaults to your display name
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    /// Phone number
+    pub phone: Option<String>,
+    /// Plus-ones coming along
+    #[serde(default)]
+    pub plus_ones: Vec<DataPlusOne>,
+    /// Answers to the event's RSVP questions, by question id
+    #[serde(default)]
+    pub answers: HashMap<String, RsvpAnswer>,
+}
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataEditRsvp {
+    /// Name to RSVP under
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    /// Phone number
+    pub phone: Option<String>,
+    /// Full list of plus-ones, existing plus-ones left out are withdrawn
+    pub plus_ones: Option<Vec<DataPlusOne>>,
+    /// Answers to the event's RSVP questions, replacing those given before
+    pub answers: Option<HashMap<String, RsvpAnswer>>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct RsvpResponse {
+    /// The RSVP itself
+    guest: EventGuest,
+    /// Plus-ones attached to the RSVP
+    plus_ones: Vec<EventGuest>,
+}
+
+/// RSVP to an event
+///
+/// The RSVP is approved straight away unless the event requires approval
+/// by a host, and lands on the waitlist if the party does not fit.
+#[openapi(tag = "Events")]
+#[post("/<event_id>/rsvp", data = "<data>")]
+pub async fn create_rsvp(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataRsvp>,
+) -> Result<Json<RsvpResponse>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+    for plus_one in &data.plus_ones {
+        plus_one
+            .validate()
+            .map_err(|error| Error::FailedValidation { error })?;
+    }
+
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.check_open()?;
+    let answers = RsvpQuestion::check_answers(&event.questions, data.answers)?;
+
+    let mut guests = db.get_event_guests(&event_id).await?;
+    if find_rsvp(&guests, &user.id).is_some() {
+        return Err(Error::AlreadyRsvped);
+    }
+
+    let account = authifier
+        .database
+        .find_account(&user.id)
+        .await
+        .map_err(|_| Error::InternalError)?;
+
+    let mut guest = new_guest(
+        &event_id,
+        data.name.unwrap_or_else(|| {
+            user.display_name
+                .clone()
+                .unwrap_or_else(|| user.username.clone())
+        }),
+        account.email,
+        data.phone.unwrap_or_default(),
+        None,
+    );
+    guest.user_id = Some(user.id.clone());
+    guest.answers = answers;
+
+    let mut plus_ones: Vec<EventGuest> = data
+        .plus_ones
+        .into_iter()
+        .map(|plus_one| {
+            new_guest(
+                &event_id,
+                plus_one.name.unwrap_or_default(),
+                plus_one.email.unwrap_or_default(),
+                plus_one.phone.unwrap_or_default(),
+                Some(&guest),
+            )
+        })
+        .collect();
+
+    check_plus_ones(&event, &plus_ones)?;
+    name_plus_ones(&mut plus_ones, &guest);
+
+    let mut party = vec![guest.clone()];
+    party.append(&mut plus_ones);
+
+    let status = event.admission_status(party.len() as i32);
+    for member in party.iter_mut() {
+        member.status = status.clone();
+    }
+
+    event.add_guests(db, &mut guests, &mut party).await?;
+    for member in &party {
+        publish_guest_update(member, false).await;
+    }
+
+    sync_event_channel(db, &user.id, &party[0]).await?;
+
+    let guest = party.remove(0);
+    Ok(Json(RsvpResponse {
+        guest,
+        plus_ones: party,
+    }))
+}
+
+/// Get your RSVP for an event
+#[openapi(tag = "Events")]
+#[get("/<event_id>/rsvp")]
+pub async fn get_rsvp(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<RsvpResponse>> {
+    // Hidden and draft events have no RSVPs to show
+    db.fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    let guests = db.get_event_guests(&event_id).await?;
+    let guest = find_rsvp(&guests, &user.id).ok_or(Error::NotFound)?;
+
+    Ok(Json(RsvpResponse {
+        guest: guest.clone(),
+        plus_ones: active_plus_ones(&guests, &guest.id),
+    }))
+}
+
+/// Edit your RSVP and plus-ones for an event
+#[openapi(tag = "Events")]
+#[patch("/<event_id>/rsvp", data = "<data>")]
+pub async fn edit_rsvp(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataEditRsvp>,
+) -> Result<Json<RsvpResponse>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+    for plus_one in data.plus_ones.iter().flatten() {
+        plus_one
+            .validate()
+            .map_err(|error| Error::FailedValidation { error })?;
+    }
+
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    let mut guests = db.get_event_guests(&event_id).await?;
+    let seats_before = count_seats(&guests);
+
+    let mut guest = find_rsvp(&guests, &user.id)
+        .cloned()
+        .ok_or(Error::NotFound)?;
+
+    if let Some(name) = data.name {
+        guest.name = name;
+    }
+
+    if let Some(phone) = data.phone {
+        guest.phone = phone;
+    }
+
+    if let Some(answers) = data.answers {
+        guest.answers = RsvpQuestion::check_answers(&event.questions, answers)?;
+    }
+
+    db.update_guest_details(&guest).await?;
+    publish_guest_update(&guest, guest.status == GuestStatus::Approved).await;
+    replace_guest(&mut guests, &guest);
+
+    if let Some(plus_ones) = data.plus_ones {
+        update_plus_ones(db, &user.id, &event, &mut guests, &guest, plus_ones).await?;
+    }
+
+    if count_seats(&guests) < seats_before {
+        promote_waitlisted_guests(authifier, db, &user, &event_id).await?;
+    }
+
+    Ok(Json(RsvpResponse {
+        plus_ones: active_plus_ones(&guests, &guest.id),
+        guest,
+    }))
+}
+
+/// Withdraw your RSVP and plus-ones from an event
+#[openapi(tag = "Events")]
+#[delete("/<event_id>/rsvp")]
+pub async fn withdraw_rsvp(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<()> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    let mut guests = db.get_event_guests(&event_id).await?;
+    let seats_before = count_seats(&guests);
+
+    let guest = find_rsvp(&guests, &user.id)
+        .cloned()
+        .ok_or(Error::NotFound)?;
+    check_guest_status_transition(&guest.status, &GuestStatus::Cancelled)?;
+
+    for plus_one in active_plus_ones(&guests, &guest.id) {
+        set_guest_status(
+            db,
+            &user.id,
+            &event,
+            &mut guests,
+            &plus_one.id,
+            GuestStatus::Cancelled,
+        )
+        .await?;
+    }
+
+    set_guest_status(
+        db,
+        &user.id,
+        &event,
+        &mut guests,
+        &guest.id,
+        GuestStatus::Cancelled,
+    )
+    .await?;
+
+    if count_seats(&guests) < seats_before {
+        promote_waitlisted_guests(authifier, db, &user, &event_id).await?;
+    }
+
+    Ok(())
+}
+
+// Helper function to replace the plus-ones of an RSVP with a new list
+//
+// Existing plus-ones left out of the list are withdrawn.
+pub(crate) async fn update_plus_ones(
+    db: &Database,
+    changed_by: &str,
+    event: &Event,
+    guests: &mut Vec<EventGuest>,
+    guest: &EventGuest,
+    plus_ones: Vec<DataPlusOne>,
+) -> Result<()> {
+    let existing = active_plus_ones(guests, &guest.id);
+    let mut kept = Vec::new();
+    let mut added = Vec::new();
+
+    for plus_one in plus_ones {
+        match plus_one.id {
+            Some(id) => {
+                let mut current = existing
+                    .iter()
+                    .find(|guest| guest.id == id)
+                    .cloned()
+                    .ok_or(Error::NotFound)?;
+
+                if let Some(name) = plus_one.name {
+                    current.name = name;
+                }
+
+                if let Some(email) = plus_one.email {
+                    current.email = email;
+                }
+
+                if let Some(phone) = plus_one.phone {
+                    current.phone = phone;
+                }
+
+                kept.push(current);
+            }
+            None => added.push(new_guest(
+                &event.id,
+                plus_one.name.unwrap_or_default(),
+                plus_one.email.unwrap_or_default(),
+                plus_one.phone.unwrap_or_default(),
+                Some(guest),
+            )),
+        }
+    }
+
+    check_plus_ones(event, &[kept.clone(), added.clone()].concat())?;
+    name_plus_ones(&mut added, guest);
+
+    // Withdraw plus-ones left out of the new list
+    for plus_one in existing
+        .iter()
+        .filter(|plus_one| !kept.iter().any(|kept| kept.id == plus_one.id))
+    {
+        set_guest_status(
+            db,
+            changed_by,
+            event,
+            guests,
+            &plus_one.id,
+            GuestStatus::Cancelled,
+        )
+        .await?;
+    }
+
+    for plus_one in &kept {
+        db.update_guest_details(plus_one).await?;
+        publish_guest_update(plus_one, plus_one.status == GuestStatus::Approved).await;
+        replace_guest(guests, plus_one);
+    }
+
+    // New plus-ones follow their RSVP, taking a fresh seat if it was approved
+    let status = if guest.status == GuestStatus::Approved {
+        event.admission_status(added.len() as i32)
+    } else {
+        guest.status.clone()
+    };
+
+    for plus_one in added.iter_mut() {
+        plus_one.status = status.clone();
+    }
+
+    event.add_guests(db, guests, &mut added).await?;
+    for plus_one in &added {
+        publish_guest_update(plus_one, false).await;
+    }
+
+    Ok(())
+}
+
+// Helper function to find a user's RSVP, ignoring withdrawn ones
+fn find_rsvp<'a>(guests: &'a [EventGuest], user_id: &str) -> Option<&'a EventGuest> {
+    guests.iter().find(|guest| {
+        guest.user_id.as_deref() == Some(user_id)
+            && guest.plus_one_of.is_none()
+            && guest.status != GuestStatus::Cancelled
+    })
+}
+
+// Helper function to list the plus-ones of an RSVP which have not been withdrawn or rejected
+pub(crate) fn active_plus_ones(guests: &[EventGuest], guest_id: &str) -> Vec<EventGuest> {
+    guests
+        .iter()
+        .filter(|guest| guest.plus_one_of.as_deref() == Some(guest_id))
+        .filter(|guest| {
+            matches!(
+                guest.status,
+                GuestStatus::Pending | GuestStatus::Approved | GuestStatus::Waitlisted
+            )
+        })
+        .cloned()
+        .collect()
+}
+
+// Helper function to swap an updated guest into the guest list
+pub(crate) fn replace_guest(guests: &mut [EventGuest], updated: &EventGuest) {
+    if let Some(guest) = guests.iter_mut().find(|guest| guest.id == updated.id) {
+        *guest = updated.clone();
+    }
+}
+
+// Helper function to build a new guest, optionally as a plus-one of another guest
+pub(crate) fn new_guest(
+    event_id: &str,
+    name: String,
+    email: String,
+    phone: String,
+    plus_one_of: Option<&EventGuest>,
+) -> EventGuest {
+    EventGuest {
+        id: Ulid::new().to_string(),
+        event_id: event_id.to_string(),
+        name,
+        email,
+        phone,
+        answers: HashMap::new(),
+        status: GuestStatus::Pending,
+        user_id: None,
+        plus_one_of: plus_one_of.map(|main| main.id.clone()),
+        parent_guest_id: None,
+        is_plus_one: Some(plus_one_of.is_some()),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        checked_in_at: None,
+        checked_in_by: None,
+        checkin_pass: None,
+    }
+}
+
+// Helper function to give unnamed plus-ones a name based on their RSVP
+pub(crate) fn name_plus_ones(plus_ones: &mut [EventGuest], main: &EventGuest) {
+    for plus_one in plus_ones.iter_mut().filter(|guest| guest.name.is_empty()) {
+        plus_one.name = format!("Guest of {}", main.name);
+    }
+}
+
+// Helper function to enforce the event's plus-one rules
+pub(crate) fn check_plus_ones(event: &Event, plus_ones: &[EventGuest]) -> Result<()> {
+    if plus_ones.is_empty() {
+        return Ok(());
+    }
+
+    if !event.allow_plus_one {
+        return Err(Error::PlusOnesNotAllowed);
+    }
+
+    if let Some(max) = event.allow_plus_one_amount {
+        if plus_ones.len() as i32 > max {
+            return Err(Error::TooManyPlusOnes { max: max as usize });
+        }
+    }
+
+    if event.requires_plus_one_info
+        && plus_ones.iter().any(|plus_one| {
+            plus_one.name.is_empty() || plus_one.email.is_empty() || plus_one.phone.is_empty()
+        })
+    {
+        return Err(Error::MissingPlusOneInfo);
+    }
+
+    Ok(())
+}
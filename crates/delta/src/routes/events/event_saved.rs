@@ -1,4 +1,7 @@
-use revolt_quark::models::event::Event;
+use super::event_analytics::record_activity;
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::{Event, EventStatus};
+use revolt_quark::models::events::analytics::EventActivityKind;
 use revolt_quark::models::user::User;
 use revolt_quark::{Database, Result};
 use rocket::{serde::json::Json, State};
@@ -20,6 +23,19 @@ pub async fn toggle_saved_event(
     event_id: String,
 ) -> Result<Json<EventSaveResponse>> {
     let (event, is_saved) = db.toggle_saved_event(&user.id, &event_id).await?;
+    if is_saved {
+        record_activity(db, &event, EventActivityKind::Save).await;
+    }
+
+    // Saves are personal, so only the user's own sessions are told
+    EventV1::EventSaved {
+        id: event_id,
+        user: user.id.clone(),
+        saved: is_saved,
+    }
+    .private(user.id)
+    .await;
+
     Ok(Json(EventSaveResponse {
         event,
         saved: is_saved,
@@ -27,9 +43,15 @@ pub async fn toggle_saved_event(
 }
 
 /// Get user's saved events
+///
+/// Lists every status but drafts unless a status is given.
 #[openapi(tag = "Events")]
-#[get("/saved")]
-pub async fn get_saved_events(db: &State<Database>, user: User) -> Result<Json<Vec<Event>>> {
-    let events = db.get_saved_events(&user.id).await?;
+#[get("/saved?<status>")]
+pub async fn get_saved_events(
+    db: &State<Database>,
+    user: User,
+    status: Option<EventStatus>,
+) -> Result<Json<Vec<Event>>> {
+    let events = db.get_saved_events(&user.id, status.as_ref()).await?;
     Ok(Json(events))
 }
@@ -0,0 +1,168 @@
+use super::event_guests::{guest_url, notify_guest};
+use chrono::Utc;
+use revolt_quark::authifier::Authifier;
+use revolt_quark::events::client::EventV1;
+use revolt_quark::models::event::{
+    parse_event_date, Event, EventStatus, EventViewer, PartialEvent,
+};
+use revolt_quark::models::events::guest::GuestStatus;
+use revolt_quark::models::user::User;
+use revolt_quark::tasks::{event_lifecycle, web_push};
+use revolt_quark::types::push::PushNotification;
+use revolt_quark::variables::delta::{APP_URL, AUTUMN_URL};
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use std::time::SystemTime;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataPublishEvent {
+    /// Publish at this date and time instead of now (ISO 8601, local to the event's timezone if no offset is given)
+    pub publish_at: Option<String>,
+}
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCancelEvent {
+    /// Reason for cancelling, passed on to guests
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: Option<String>,
+}
+
+/// Publish a draft event
+///
+/// Pass `publish_at` to schedule publication for later instead.
+#[openapi(tag = "Events")]
+#[post("/<id>/publish", data = "<data>")]
+pub async fn publish_event(
+    db: &State<Database>,
+    user: User,
+    id: String,
+    data: Json<DataPublishEvent>,
+) -> Result<Json<Event>> {
+    let data = data.into_inner();
+
+    let event = db.fetch_event(EventViewer::System, &id).await?;
+    event.throw_permission(&user.id, EventPermission::EditEvent)?;
+    check_event_status_transition(&event.status, &EventStatus::Published)?;
+
+    let publish_at = data
+        .publish_at
+        .as_deref()
+        .map(|date| parse_event_date("publish_at", date, &event.tz()))
+        .transpose()?;
+
+    match publish_at {
+        Some(publish_at) if publish_at > Utc::now() => {
+            let partial = PartialEvent {
+                publish_at: Some(publish_at),
+                ..Default::default()
+            };
+
+            db.update_event(&event.id, &partial).await?;
+
+            EventV1::EventUpdate {
+                id: event.id.clone(),
+                data: partial,
+            }
+            .p(event.id.clone())
+            .await;
+        }
+        _ => event_lifecycle::set_status(db, &event, EventStatus::Published).await?,
+    }
+
+    let event = db.fetch_event(EventViewer::User(&user.id), &id).await?;
+    Ok(Json(event))
+}
+
+/// Cancel an event
+///
+/// The event is kept so guests can still see it. Approved and pending
+/// guests are told by DM, email and push notification. Only the event's
+/// creator can cancel it.
+#[openapi(tag = "Events")]
+#[post("/<id>/cancel", data = "<data>")]
+pub async fn cancel_event(
+    authifier: &State<Authifier>,
+    db: &State<Database>,
+    user: User,
+    id: String,
+    data: Json<DataCancelEvent>,
+) -> Result<Json<Event>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let event = db.fetch_event(EventViewer::System, &id).await?;
+    event.throw_owner(&user.id)?;
+    check_event_status_transition(&event.status, &EventStatus::Cancelled)?;
+
+    event_lifecycle::set_status(db, &event, EventStatus::Cancelled).await?;
+    db.replace_event_reminders(&event.id, &[]).await?;
+
+    let guests: Vec<_> = db
+        .get_event_guests(&event.id)
+        .await?
+        .into_iter()
+        .filter(|guest| matches!(guest.status, GuestStatus::Approved | GuestStatus::Pending))
+        .collect();
+
+    let content = match &data.reason {
+        Some(reason) => format!("{} has been cancelled.\n\n{}", event.title, reason),
+        None => format!("{} has been cancelled.", event.title),
+    };
+
+    for guest in &guests {
+        notify_guest(
+            authifier,
+            db,
+            &user,
+            &event,
+            guest,
+            format!("{} has been cancelled", event.title),
+            guest_url(guest),
+            content.clone(),
+        )
+        .await?;
+    }
+
+    web_push::queue(
+        guests
+            .iter()
+            .filter_map(|guest| guest.user_id.clone())
+            .filter(|id| id != &user.id)
+            .collect(),
+        json!(PushNotification {
+            author: event.title.clone(),
+            icon: format!("{}/assets/logo.png", *APP_URL),
+            image: event
+                .thumbnail
+                .as_ref()
+                .map(|id| format!("{}/attachments/{}", *AUTUMN_URL, id)),
+            body: content,
+            tag: format!("event-cancelled-{}", event.id),
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs(),
+            url: format!("{}/events/view/{}", *APP_URL, event.id),
+        })
+        .to_string(),
+    )
+    .await;
+
+    let event = db.fetch_event(EventViewer::User(&user.id), &id).await?;
+    Ok(Json(event))
+}
+
+// Helper function to ensure an event may be moved between two statuses
+fn check_event_status_transition(from: &EventStatus, to: &EventStatus) -> Result<()> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(Error::InvalidEventStatusTransition {
+            from: format!("{:?}", from),
+            to: format!("{:?}", to),
+        })
+    }
+}
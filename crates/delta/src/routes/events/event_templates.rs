@@ -0,0 +1,110 @@
+use super::event_create::publish_event_create;
+use revolt_quark::models::event::{
+    parse_event_date, parse_timezone, validate_event_dates, Event, EventViewer,
+};
+use revolt_quark::models::events::template::EventTemplate;
+use revolt_quark::models::user::User;
+use revolt_quark::tasks::event_reminders;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCreateTemplate {
+    /// Name to save the template under
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataCreateFromTemplate {
+    /// Title of the new event, defaults to the template's
+    #[validate(length(min = 1, max = 100))]
+    pub title: Option<String>,
+    /// Start date and time (ISO 8601, local to the template's timezone if no offset is given)
+    pub start_date: String,
+    /// End date and time (ISO 8601, local to the template's timezone if no offset is given)
+    pub end_date: String,
+}
+
+/// Save an event as a template
+///
+/// The template belongs to the user saving it and keeps the event's
+/// details, ticket configuration, gallery and thumbnail.
+#[openapi(tag = "Events")]
+#[post("/<id>/template", data = "<data>")]
+pub async fn create_event_template(
+    db: &State<Database>,
+    user: User,
+    id: String,
+    data: Json<DataCreateTemplate>,
+) -> Result<Json<EventTemplate>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let event = db.fetch_event(EventViewer::System, &id).await?;
+    event.throw_permission(&user.id, EventPermission::EditEvent)?;
+
+    let template = EventTemplate::from_event(&event, data.name, user.id);
+    db.insert_event_template(&template).await?;
+
+    Ok(Json(template))
+}
+
+/// List your event templates
+#[openapi(tag = "Events")]
+#[get("/templates")]
+pub async fn get_event_templates(
+    db: &State<Database>,
+    user: User,
+) -> Result<Json<Vec<EventTemplate>>> {
+    let templates = db.fetch_event_templates(&user.id).await?;
+    Ok(Json(templates))
+}
+
+/// Create a draft event from a template
+///
+/// The draft is owned by you and stays hidden from guests until published.
+#[openapi(tag = "Events")]
+#[post("/templates/<template_id>/events", data = "<data>")]
+pub async fn create_event_from_template(
+    db: &State<Database>,
+    user: User,
+    template_id: String,
+    data: Json<DataCreateFromTemplate>,
+) -> Result<Json<Event>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let template = db.fetch_event_template(&user.id, &template_id).await?;
+
+    let tz = parse_timezone(&template.timezone)?;
+    let start_date = parse_event_date("start_date", &data.start_date, &tz)?;
+    let end_date = parse_event_date("end_date", &data.end_date, &tz)?;
+    validate_event_dates(&start_date, &end_date)?;
+
+    let mut event = template.to_draft(start_date, end_date);
+    if let Some(title) = data.title {
+        event.title = title;
+    }
+
+    db.insert_event(&event).await?;
+    event_reminders::schedule(db, &event).await?;
+    publish_event_create(&event).await;
+
+    Ok(Json(event))
+}
+
+/// Delete an event template
+#[openapi(tag = "Events")]
+#[delete("/templates/<template_id>", rank = 2)]
+pub async fn delete_event_template(
+    db: &State<Database>,
+    user: User,
+    template_id: String,
+) -> Result<()> {
+    db.delete_event_template(&user.id, &template_id).await
+}
@@ -0,0 +1,269 @@
+use chrono::Utc;
+use revolt_quark::models::event::EventViewer;
+use revolt_quark::models::events::promo_code::{PromoCode, PromoRedemption};
+use revolt_quark::models::events::ticket::{
+    PaymentStatus, Ticket, TicketPayment, TicketStatus, TicketSummary, TicketTier,
+};
+use revolt_quark::models::user::User;
+use revolt_quark::payments::{self, CheckoutRequest};
+use revolt_quark::variables::delta::DEFAULT_SERVER;
+use revolt_quark::{Database, Error, EventPermission, Result};
+use rocket::{serde::json::Json, State};
+use serde::Deserialize;
+use ulid::Ulid;
+use validator::Validate;
+
+#[derive(Validate, Deserialize, JsonSchema)]
+pub struct DataReserveTicket {
+    /// Number of seats to reserve
+    #[validate(range(min = 1, max = 100))]
+    pub quantity: Option<i32>,
+    /// Promo code to redeem
+    #[validate(length(min = 1, max = 32))]
+    pub promo_code: Option<String>,
+}
+
+/// Reserve tickets for an event
+///
+/// The member or non-member tier is picked from the buyer's membership
+/// of the community server. Free tickets are confirmed immediately, paid
+/// tickets once the payment provider tells us the checkout was completed.
+/// Paid tickets which are not paid for within half an hour are released.
+/// A promo code may take some or all of the price off.
+#[openapi(tag = "Events")]
+#[post("/<event_id>/tickets", data = "<data>")]
+pub async fn reserve_ticket(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    data: Json<DataReserveTicket>,
+) -> Result<Json<Ticket>> {
+    let data = data.into_inner();
+    data.validate()
+        .map_err(|error| Error::FailedValidation { error })?;
+
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.check_open()?;
+
+    let config = &event.ticket_config;
+    let quantity = data.quantity.unwrap_or(1);
+
+    // Tickets already held are checked when the ticket is reserved
+    if !config.allow_multiple_tickets && quantity > 1 {
+        return Err(Error::TooManyTickets { max: 1 });
+    }
+
+    let tier = if db.fetch_member(&DEFAULT_SERVER, &user.id).await.is_ok() {
+        TicketTier::Member
+    } else {
+        TicketTier::NonMember
+    };
+
+    let promo_code = match data.promo_code.as_deref() {
+        Some(code) => {
+            let promo_code = match db
+                .fetch_promo_code(&event_id, &PromoCode::normalise(code))
+                .await
+            {
+                Ok(promo_code) => promo_code,
+                Err(Error::NotFound) => return Err(Error::InvalidPromoCode),
+                Err(error) => return Err(error),
+            };
+
+            promo_code.check_redeemable(tier)?;
+            Some(promo_code)
+        }
+        None => None,
+    };
+
+    let totals = tier.totals(
+        config,
+        quantity,
+        promo_code.as_ref().map(|promo_code| &promo_code.discount),
+    )?;
+
+    // Codes only ever take money off
+    if promo_code.is_some() && totals.is_none() {
+        return Err(Error::InvalidPromoCode);
+    }
+
+    let to_pay = totals.clone().filter(|totals| !totals.total.is_zero());
+    let currency = tier.currency(config).or_else(|| event.currency.clone());
+    if to_pay.is_some() && currency.is_none() {
+        return Err(Error::InvalidTicketPrice);
+    }
+
+    // Paid tickets can only be taken with a provider to pay through
+    let provider = to_pay.as_ref().map(|_| payments::provider()).transpose()?;
+
+    let mut ticket = Ticket {
+        id: Ulid::new().to_string(),
+        event_id: event_id.clone(),
+        user_id: user.id.clone(),
+        tier,
+        quantity,
+        price: tier.price(config),
+        currency,
+        promo_code: promo_code
+            .as_ref()
+            .map(|promo_code| promo_code.code.clone()),
+        status: if to_pay.is_some() {
+            TicketStatus::Reserved
+        } else {
+            TicketStatus::Confirmed
+        },
+        payment: None,
+        created_at: Utc::now(),
+    };
+
+    db.reserve_ticket(
+        &ticket,
+        tier.max_tickets(config),
+        !config.allow_multiple_tickets,
+    )
+    .await?;
+
+    if let (Some(promo_code), Some(totals)) = (&promo_code, &totals) {
+        if let Err(error) = db.redeem_promo_code(promo_code).await {
+            db.cancel_ticket(&event_id, &ticket.id).await?;
+            return Err(error);
+        }
+
+        let guest_id = db
+            .get_event_guests(&event_id)
+            .await?
+            .into_iter()
+            .find(|guest| guest.user_id.as_deref() == Some(user.id.as_str()))
+            .map(|guest| guest.id);
+
+        db.insert_promo_redemption(&PromoRedemption {
+            id: Ulid::new().to_string(),
+            event_id: event_id.clone(),
+            promo_code_id: promo_code.id.clone(),
+            code: promo_code.code.clone(),
+            partner: promo_code.partner.clone(),
+            ticket_id: ticket.id.clone(),
+            user_id: user.id.clone(),
+            guest_id,
+            quantity,
+            discount: totals.discount.to_string(),
+            created_at: Utc::now(),
+        })
+        .await?;
+    }
+
+    if let (Some(totals), Some(provider)) = (to_pay, provider) {
+        let currency = ticket.currency.clone().unwrap_or_default();
+        let session = match provider
+            .create_checkout(&CheckoutRequest {
+                reference: ticket.id.clone(),
+                description: format!("{} x {}", quantity, event.title),
+                amount: totals.total,
+                currency: currency.clone(),
+            })
+            .await
+        {
+            Ok(session) => session,
+            Err(error) => {
+                // Release the seats, there is no way to pay for them
+                release_ticket(db, &event_id, &ticket.id).await?;
+                return Err(error);
+            }
+        };
+
+        let payment = TicketPayment {
+            provider: provider.name().to_string(),
+            session_id: session.id,
+            checkout_url: session.url,
+            subtotal: totals.subtotal.to_string(),
+            discount: Some(totals.discount.to_string()).filter(|_| !totals.discount.is_zero()),
+            processing_fee: totals.processing_fee.to_string(),
+            total: totals.total.to_string(),
+            currency,
+            status: PaymentStatus::Pending,
+            refund_id: None,
+        };
+
+        db.set_ticket_payment(&event_id, &ticket.id, &payment)
+            .await?;
+        ticket.payment = Some(payment);
+    }
+
+    Ok(Json(ticket))
+}
+
+/// Get ticket summary for an event
+#[openapi(tag = "Events")]
+#[get("/<event_id>/tickets")]
+pub async fn get_ticket_summary(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<TicketSummary>> {
+    let event = db
+        .fetch_event(EventViewer::User(&user.id), &event_id)
+        .await?;
+    event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+
+    let tickets = db.fetch_tickets(&event_id).await?;
+    Ok(Json(TicketSummary::from_tickets(
+        &event.ticket_config,
+        tickets,
+    )))
+}
+
+/// Get the current user's tickets for an event
+#[openapi(tag = "Events")]
+#[get("/<event_id>/tickets/@me")]
+pub async fn get_my_tickets(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+) -> Result<Json<Vec<Ticket>>> {
+    let tickets = db.fetch_user_tickets(&event_id, &user.id).await?;
+    Ok(Json(tickets))
+}
+
+/// Cancel a ticket and release its seats
+///
+/// Tickets which were paid for are refunded in full.
+#[openapi(tag = "Events")]
+#[delete("/<event_id>/tickets/<ticket_id>")]
+pub async fn cancel_ticket(
+    db: &State<Database>,
+    user: User,
+    event_id: String,
+    ticket_id: String,
+) -> Result<()> {
+    let ticket = db.fetch_ticket(&event_id, &ticket_id).await?;
+    if ticket.user_id != user.id {
+        let event = db
+            .fetch_event(EventViewer::User(&user.id), &event_id)
+            .await?;
+        event.throw_permission(&user.id, EventPermission::ManageGuests)?;
+    }
+
+    if ticket.status == TicketStatus::Cancelled {
+        return Err(Error::InvalidOperation);
+    }
+
+    if let Some(mut payment) = ticket
+        .payment
+        .filter(|payment| payment.status == PaymentStatus::Paid)
+    {
+        payment.refund_id = Some(payments::provider()?.refund(&payment).await?);
+        payment.status = PaymentStatus::Refunded;
+        db.set_ticket_payment(&event_id, &ticket_id, &payment)
+            .await?;
+    }
+
+    release_ticket(db, &event_id, &ticket_id).await
+}
+
+// Helper function to cancel a ticket, giving back any promo code redeemed for it
+pub(crate) async fn release_ticket(db: &Database, event_id: &str, ticket_id: &str) -> Result<()> {
+    db.cancel_ticket(event_id, ticket_id).await?;
+    db.release_promo_redemption(event_id, ticket_id).await
+}
@@ -1,20 +1,45 @@
+use revolt_quark::payments;
 use revolt_rocket_okapi::revolt_okapi::openapi3::OpenApi;
 use rocket::Route;
 
+mod event_analytics;
+mod event_calendar;
+mod event_channel;
+mod event_checkin;
+mod event_clone;
 mod event_create;
 mod event_delete;
 mod event_edit;
+mod event_feedback;
 mod event_fetch;
+mod event_guest_csv;
 mod event_guests;
+mod event_invitation;
 mod event_list;
+mod event_payments;
+mod event_permissions;
+mod event_promo_codes;
+mod event_rsvp;
 mod event_saved;
+mod event_status;
+mod event_templates;
+mod event_tickets;
 
 pub fn routes() -> (Vec<Route>, OpenApi) {
-    openapi_get_routes_spec![
+    let (mut routes, spec) = openapi_get_routes_spec![
         event_create::create_event,
+        event_clone::clone_event,
+        event_templates::create_event_template,
+        event_templates::get_event_templates,
+        event_templates::create_event_from_template,
+        event_templates::delete_event_template,
         event_delete::delete_event,
         event_edit::update_event,
+        event_status::publish_event,
+        event_status::cancel_event,
         event_fetch::get_event,
+        event_fetch::get_event_series,
+        event_analytics::get_event_analytics,
         event_list::list_events,
         event_saved::toggle_saved_event,
         event_saved::get_saved_events,
@@ -22,9 +47,52 @@ pub fn routes() -> (Vec<Route>, OpenApi) {
         event_guests::add_guest,
         event_guests::get_event_guests,
         event_guests::get_guest,
+        event_guests::update_guest_status,
+        event_guests::get_guest_status_history,
         event_guests::add_bulk_guests,
         event_guests::update_bulk_guest_status,
         event_guests::send_bulk_messages,
         event_guests::notify_guests,
-    ]
+        event_guests::get_message_job,
+        event_guest_csv::export_guests,
+        event_guest_csv::import_guests,
+        event_channel::create_event_channel,
+        event_permissions::get_event_permissions,
+        event_permissions::set_event_permissions,
+        event_permissions::reset_event_permissions,
+        event_rsvp::create_rsvp,
+        event_rsvp::get_rsvp,
+        event_rsvp::edit_rsvp,
+        event_rsvp::withdraw_rsvp,
+        event_invitation::get_invitation,
+        event_invitation::accept_invitation,
+        event_invitation::decline_invitation,
+        event_invitation::edit_invitation,
+        event_tickets::reserve_ticket,
+        event_tickets::get_ticket_summary,
+        event_tickets::get_my_tickets,
+        event_tickets::cancel_ticket,
+        event_payments::payment_webhook,
+        event_promo_codes::create_promo_code,
+        event_promo_codes::get_promo_codes,
+        event_promo_codes::delete_promo_code,
+        event_promo_codes::get_promo_redemptions,
+        event_checkin::check_in_guest,
+        event_feedback::submit_feedback,
+        event_feedback::get_my_feedback,
+        event_feedback::get_event_feedback,
+        event_feedback::get_feedback_summary,
+        event_calendar::export_event_calendar,
+        event_calendar::get_calendar_feed,
+        event_calendar::reset_calendar_feed,
+        event_calendar::revoke_calendar_feed,
+        event_calendar::subscribe_calendar_feed,
+    ];
+
+    // Checkouts can only be completed through the API when no money is involved
+    if payments::is_fake() {
+        routes.append(&mut routes![event_payments::complete_fake_checkout]);
+    }
+
+    (routes, spec)
 }
@@ -17,3 +17,8 @@ pub static RE_USERNAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\p{L}|[\d_.-])
 ///
 /// Alphanumeric and underscores
 pub static RE_EMOJI: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9_]+$").unwrap());
+
+/// Regex for valid promo codes
+///
+/// Letters, numbers, dashes and underscores
+pub static RE_PROMO_CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_-]+$").unwrap());
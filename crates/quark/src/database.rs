@@ -38,7 +38,7 @@ impl DatabaseInfo {
 
                 DatabaseInfo::Dummy.connect().await?
             }
-            DatabaseInfo::Dummy => Database::Dummy(DummyDb),
+            DatabaseInfo::Dummy => Database::Dummy(DummyDb::default()),
             DatabaseInfo::MongoDb(uri) => {
                 let client = mongodb::Client::with_uri_str(uri)
                     .await
@@ -3,6 +3,8 @@ use revolt_models::v0::{FieldsWebhook, PartialWebhook, Webhook};
 use serde::{Deserialize, Serialize};
 
 use crate::models::channel::{FieldsChannel, PartialChannel};
+use crate::models::event::{Event, PartialEvent};
+use crate::models::events::guest::EventGuest;
 use crate::models::message::{AppendMessage, PartialMessage};
 use crate::models::server::{FieldsRole, FieldsServer, PartialRole, PartialServer};
 use crate::models::server_member::{FieldsMember, MemberCompositeKey, PartialMember};
@@ -231,6 +233,25 @@ pub enum EventV1 {
     /// New report
     ReportCreate(Report),
 
+    /// New event
+    EventCreate(Event),
+
+    /// Update existing event
+    EventUpdate { id: String, data: PartialEvent },
+
+    /// Delete event
+    EventDelete { id: String },
+
+    /// Guest added to an event or their details or status changed
+    EventGuestUpdate { id: String, guest: EventGuest },
+
+    /// User saved or unsaved an event
+    EventSaved {
+        id: String,
+        user: String,
+        saved: bool,
+    },
+
     /// Auth events
     Auth(AuthifierEvent),
 }
@@ -3,6 +3,8 @@ use std::collections::HashSet;
 use crate::{
     get_relationship,
     models::{
+        event::EventViewer,
+        events::guest::GuestStatus,
         server_member::FieldsMember,
         user::{PartialUser, Presence, RelationshipStatus},
         Channel, Member, User,
@@ -164,6 +166,10 @@ impl State {
             .await?,
         );
 
+        // Fetch events we help run or are attending.
+        let hosted_events = db.fetch_staffed_event_ids(&user.id).await?;
+        let attending_events = db.fetch_attending_event_ids(&user.id).await?;
+
         // Copy data into local state cache.
         self.cache.users = users.iter().cloned().map(|x| (x.id.clone(), x)).collect();
         self.cache
@@ -174,6 +180,7 @@ impl State {
             .cloned()
             .map(|x| (x.id().to_string(), x))
             .collect();
+        self.cache.hosted_events = hosted_events.iter().cloned().collect();
 
         // Make all users appear from our perspective.
         let mut users: Vec<User> = users
@@ -204,6 +211,10 @@ impl State {
             self.insert_subscription(channel.id().to_string());
         }
 
+        for event in hosted_events.into_iter().chain(attending_events) {
+            self.insert_subscription(event);
+        }
+
         Ok(EventV1::Ready {
             users,
             servers,
@@ -509,6 +520,52 @@ impl State {
                 }
             }
 
+            EventV1::EventCreate(event) => {
+                if event.is_staff(&self.cache.user_id) {
+                    self.cache.hosted_events.insert(event.id.clone());
+                    self.insert_subscription(event.id.clone());
+                }
+            }
+            EventV1::EventUpdate { id, data } => {
+                // Staff changes may have added or removed us
+                if data.hosts.is_some() || data.managers.is_some() {
+                    if let Ok(updated) = db.fetch_event(EventViewer::System, id).await {
+                        if updated.is_staff(&self.cache.user_id) {
+                            self.cache.hosted_events.insert(id.clone());
+                            self.insert_subscription(id.clone());
+                        } else if self.cache.hosted_events.remove(id) {
+                            // Former staff still follow the event if they attend it
+                            let attending = db
+                                .fetch_attending_event_ids(&self.cache.user_id)
+                                .await
+                                .unwrap_or_default();
+
+                            if !attending.contains(id) {
+                                self.remove_subscription(id);
+                            }
+                        }
+                    }
+                }
+            }
+            EventV1::EventDelete { id } => {
+                self.cache.hosted_events.remove(id);
+                self.remove_subscription(id);
+            }
+            EventV1::EventGuestUpdate { id, guest } => {
+                if guest.user_id.as_ref() == Some(&self.cache.user_id) {
+                    if guest.status == GuestStatus::Approved {
+                        self.insert_subscription(id.clone());
+                    } else if !self.cache.hosted_events.contains(id) {
+                        self.remove_subscription(id);
+                    }
+                } else if !self.cache.hosted_events.contains(id) {
+                    // Only staff get to see other guests' contact details and answers
+                    guest.email.clear();
+                    guest.phone.clear();
+                    guest.answers.clear();
+                }
+            }
+
             EventV1::UserUpdate { event_id, .. } => {
                 if let Some(id) = event_id {
                     if self.cache.seen_events.contains(id) {
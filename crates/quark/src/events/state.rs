@@ -36,6 +36,7 @@ pub struct Cache {
     pub channels: HashMap<String, Channel>,
     pub members: HashMap<String, Member>,
     pub servers: HashMap<String, Server>,
+    pub hosted_events: HashSet<String>,
 
     pub seen_events: LruCache<String, ()>,
 }
@@ -49,6 +50,7 @@ impl Default for Cache {
             channels: Default::default(),
             members: Default::default(),
             servers: Default::default(),
+            hosted_events: Default::default(),
 
             seen_events: LruCache::new(20),
         }
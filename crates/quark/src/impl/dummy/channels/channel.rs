@@ -25,6 +25,9 @@ impl AbstractChannel for DummyDb {
             nsfw: false,
             hide_title: false,
             password: None,
+
+            event: None,
+            archived: false,
         })
     }
 
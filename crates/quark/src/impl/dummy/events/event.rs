@@ -1,40 +1,130 @@
-use crate::models::event::{Event, PartialEvent};
-use crate::models::events::guest::{EventGuest, GuestStatus};
+use crate::models::channel::Channel;
+use crate::models::event::{
+    Event, EventAccess, EventQuery, EventStatus, EventViewer, PartialEvent,
+};
+use crate::models::events::analytics::{EventActivityDay, EventActivityKind};
+use crate::models::events::calendar::CalendarFeed;
+use crate::models::events::feedback::EventFeedback;
+use crate::models::events::guest::{EventGuest, GuestStatus, GuestStatusChange};
+use crate::models::events::message_job::EventMessageJob;
+use crate::models::events::promo_code::{PromoCode, PromoRedemption};
+use crate::models::events::reminder::EventReminder;
+use crate::models::events::series::EventSeries;
+use crate::models::events::template::EventTemplate;
+use crate::models::events::ticket::{Ticket, TicketPayment, TicketStatus, TicketTier};
 use crate::models::user::User;
 use crate::{AbstractEvents, Error, Result};
+use chrono::{DateTime, Utc};
 
 use super::super::DummyDb;
 
 #[async_trait]
 impl AbstractEvents for DummyDb {
-    async fn insert_event(&self, _: &Event) -> Result<()> {
+    async fn insert_event(&self, event: &Event) -> Result<()> {
+        self.events
+            .lock()
+            .await
+            .insert(event.id.clone(), event.clone());
         Ok(())
     }
 
-    async fn fetch_event(&self, _: Option<&str>, _: &str) -> Result<Event> {
-        Err(Error::NotFound)
+    async fn fetch_event(&self, viewer: EventViewer<'_>, id: &str) -> Result<Event> {
+        let event = self
+            .events
+            .lock()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or(Error::NotFound)?;
+
+        restrict_events(self, viewer, vec![event])
+            .await?
+            .pop()
+            .ok_or(Error::NotFound)
     }
-    async fn fetch_events<'a>(&self, _: Option<&str>, _: &'a [String]) -> Result<Vec<Event>> {
-        Ok(vec![])
+
+    async fn fetch_events<'a>(
+        &self,
+        viewer: EventViewer<'_>,
+        ids: &'a [String],
+    ) -> Result<Vec<Event>> {
+        let events = self
+            .events
+            .lock()
+            .await
+            .values()
+            .filter(|event| ids.is_empty() || ids.contains(&event.id))
+            .cloned()
+            .collect();
+
+        restrict_events(self, viewer, events).await
     }
 
-    async fn update_event(&self, _: &str, _: &PartialEvent) -> Result<()> {
+    async fn query_events(
+        &self,
+        viewer: EventViewer<'_>,
+        query: &EventQuery,
+    ) -> Result<(Vec<Event>, u64)> {
+        let mut events: Vec<Event> = self
+            .events
+            .lock()
+            .await
+            .values()
+            .filter(|event| matches_query(event, query))
+            .cloned()
+            .collect();
+
+        match &query.near {
+            Some(near) => {
+                let distance = |event: &Event| {
+                    event
+                        .search_location()
+                        .map(|location| location.distance_to(&near.center))
+                };
+
+                events.retain(|event| distance(event).map_or(false, |d| d <= near.radius));
+                events.sort_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap());
+            }
+            None => events.sort_by(|a, b| (a.start_date, &a.id).cmp(&(b.start_date, &b.id))),
+        }
+
+        // Addresses are only hidden once distances were measured, like MongoDB does
+        let events = restrict_events(self, viewer, events).await?;
+        let total = events.len() as u64;
+        let events = events
+            .into_iter()
+            .skip(query.skip() as usize)
+            .take(query.per_page() as usize)
+            .collect();
+
+        Ok((events, total))
+    }
+
+    async fn update_event(&self, id: &str, partial: &PartialEvent) -> Result<()> {
+        let mut events = self.events.lock().await;
+        let event = events.get_mut(id).ok_or(Error::NotFound)?;
+        event.apply_options(partial.clone());
         Ok(())
     }
 
-    async fn delete_event(&self, _: &str) -> Result<()> {
+    async fn delete_event(&self, id: &str) -> Result<()> {
+        self.events.lock().await.remove(id);
         Ok(())
     }
 
-    async fn toggle_saved_event(&self, _: &str, _: &str) -> Result<(Event, bool)> {
-        Ok((Event::default(), true))
+    async fn toggle_saved_event(&self, user_id: &str, event_id: &str) -> Result<(Event, bool)> {
+        let event = self
+            .fetch_event(EventViewer::User(user_id), event_id)
+            .await?;
+
+        Ok((event, true))
     }
 
     async fn is_event_saved(&self, _: &str, _: &str) -> Result<bool> {
         Ok(false)
     }
 
-    async fn get_saved_events(&self, _: &str) -> Result<Vec<Event>> {
+    async fn get_saved_events(&self, _: &str, _: Option<&EventStatus>) -> Result<Vec<Event>> {
         Ok(vec![])
     }
 
@@ -42,19 +132,946 @@ impl AbstractEvents for DummyDb {
         Ok(vec![])
     }
 
-    async fn add_guest(&self, _: &EventGuest) -> Result<()> {
+    async fn insert_event_series(&self, _: &EventSeries) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_event_series(&self, _: &str) -> Result<EventSeries> {
+        Err(Error::NotFound)
+    }
+
+    async fn fetch_series_events(
+        &self,
+        viewer: EventViewer<'_>,
+        series_id: &str,
+    ) -> Result<Vec<Event>> {
+        let mut events: Vec<Event> = self
+            .events
+            .lock()
+            .await
+            .values()
+            .filter(|event| event.series_id.as_deref() == Some(series_id))
+            .cloned()
+            .collect();
+
+        events.sort_by_key(|event| event.start_date);
+        restrict_events(self, viewer, events).await
+    }
+
+    async fn fetch_attending_event_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .event_guests
+            .lock()
+            .await
+            .iter()
+            .filter(|guest| {
+                guest.user_id.as_deref() == Some(user_id) && guest.status == GuestStatus::Approved
+            })
+            .map(|guest| guest.event_id.clone())
+            .collect())
+    }
+
+    async fn fetch_staffed_event_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .events
+            .lock()
+            .await
+            .values()
+            .filter(|event| event.is_staff(user_id))
+            .map(|event| event.id.clone())
+            .collect())
+    }
+
+    async fn fetch_calendar_events(&self, _: &str) -> Result<Vec<Event>> {
+        Ok(vec![])
+    }
+
+    async fn add_guest(&self, guest: &EventGuest) -> Result<()> {
+        let mut guests = self.event_guests.lock().await;
+        if guest.user_id.is_some()
+            && guests.iter().any(|existing| {
+                existing.event_id == guest.event_id
+                    && existing.user_id == guest.user_id
+                    && existing.status != GuestStatus::Cancelled
+            })
+        {
+            return Err(Error::AlreadyRsvped);
+        }
+
+        guests.push(guest.clone());
+        Ok(())
+    }
+
+    async fn update_guest_status(
+        &self,
+        event_id: &str,
+        guest_id: &str,
+        from: GuestStatus,
+        to: GuestStatus,
+    ) -> Result<bool> {
+        let mut guests = self.event_guests.lock().await;
+        let guest = guests
+            .iter_mut()
+            .find(|guest| guest.event_id == event_id && guest.id == guest_id)
+            .ok_or(Error::NotFound)?;
+
+        if guest.status != from {
+            return Ok(false);
+        }
+
+        guest.status = to;
+        Ok(true)
+    }
+
+    async fn take_event_seats(&self, id: &str, seats: i32, capacity: Option<i32>) -> Result<bool> {
+        let mut events = self.events.lock().await;
+        let event = events.get_mut(id).ok_or(Error::NotFound)?;
+        if capacity.map_or(false, |capacity| event.seats_taken + seats > capacity) {
+            return Ok(false);
+        }
+
+        event.seats_taken += seats;
+        Ok(true)
+    }
+
+    async fn release_event_seats(&self, id: &str, seats: i32) -> Result<()> {
+        let mut events = self.events.lock().await;
+        let event = events.get_mut(id).ok_or(Error::NotFound)?;
+        event.seats_taken -= seats;
+        Ok(())
+    }
+
+    async fn get_event_guests(&self, event_id: &str) -> Result<Vec<EventGuest>> {
+        Ok(self
+            .event_guests
+            .lock()
+            .await
+            .iter()
+            .filter(|guest| guest.event_id == event_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_guest(&self, event_id: &str, guest_id: &str) -> Result<EventGuest> {
+        self.event_guests
+            .lock()
+            .await
+            .iter()
+            .find(|guest| guest.event_id == event_id && guest.id == guest_id)
+            .cloned()
+            .ok_or(Error::NotFound)
+    }
+
+    async fn update_guest_details(&self, _: &EventGuest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn insert_guest_status_change(&self, _: &GuestStatusChange) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_guest_status_history(&self, _: &str, _: &str) -> Result<Vec<GuestStatusChange>> {
+        Ok(vec![])
+    }
+
+    async fn fetch_event_status_history(&self, _: &str) -> Result<Vec<GuestStatusChange>> {
+        Ok(vec![])
+    }
+
+    async fn check_in_guest(&self, _: &str, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn reserve_ticket(
+        &self,
+        ticket: &Ticket,
+        max_tickets: Option<i32>,
+        single: bool,
+    ) -> Result<()> {
+        // Both locks are held throughout, like the checks MongoDB makes atomically
+        let mut events = self.events.lock().await;
+        let mut tickets = self.event_tickets.lock().await;
+        let event = events.get_mut(&ticket.event_id).ok_or(Error::NotFound)?;
+
+        if single
+            && tickets.iter().any(|held| {
+                held.event_id == ticket.event_id
+                    && held.user_id == ticket.user_id
+                    && held.status != TicketStatus::Cancelled
+            })
+        {
+            return Err(Error::TooManyTickets { max: 1 });
+        }
+
+        let count = match ticket.tier {
+            TicketTier::Member => &mut event.ticket_counts.member,
+            TicketTier::NonMember => &mut event.ticket_counts.non_member,
+        };
+
+        if max_tickets.map_or(false, |max| *count + ticket.quantity > max) {
+            return Err(Error::TicketsSoldOut);
+        }
+
+        *count += ticket.quantity;
+        tickets.push(ticket.clone());
+        Ok(())
+    }
+
+    async fn fetch_ticket(&self, event_id: &str, ticket_id: &str) -> Result<Ticket> {
+        self.event_tickets
+            .lock()
+            .await
+            .iter()
+            .find(|ticket| ticket.event_id == event_id && ticket.id == ticket_id)
+            .cloned()
+            .ok_or(Error::NotFound)
+    }
+
+    async fn fetch_tickets(&self, event_id: &str) -> Result<Vec<Ticket>> {
+        Ok(self
+            .event_tickets
+            .lock()
+            .await
+            .iter()
+            .filter(|ticket| ticket.event_id == event_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_user_tickets(&self, event_id: &str, user_id: &str) -> Result<Vec<Ticket>> {
+        Ok(self
+            .fetch_tickets(event_id)
+            .await?
+            .into_iter()
+            .filter(|ticket| ticket.user_id == user_id)
+            .collect())
+    }
+
+    async fn confirm_ticket(&self, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cancel_ticket(&self, event_id: &str, ticket_id: &str) -> Result<()> {
+        let mut events = self.events.lock().await;
+        let mut tickets = self.event_tickets.lock().await;
+        let ticket = tickets
+            .iter_mut()
+            .find(|ticket| {
+                ticket.event_id == event_id
+                    && ticket.id == ticket_id
+                    && ticket.status != TicketStatus::Cancelled
+            })
+            .ok_or(Error::InvalidOperation)?;
+
+        ticket.status = TicketStatus::Cancelled;
+        if let Some(event) = events.get_mut(event_id) {
+            match ticket.tier {
+                TicketTier::Member => event.ticket_counts.member -= ticket.quantity,
+                TicketTier::NonMember => event.ticket_counts.non_member -= ticket.quantity,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_expired_reservations(&self, _: DateTime<Utc>) -> Result<Vec<Ticket>> {
+        Ok(vec![])
+    }
+
+    async fn expire_reservation(&self, _: &str, _: &str) -> Result<()> {
         Ok(())
     }
 
-    async fn update_guest_status(&self, _: &str, _: &str, _: GuestStatus) -> Result<()> {
+    async fn set_ticket_payment(&self, _: &str, _: &str, _: &TicketPayment) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_ticket_by_payment(&self, _: &str) -> Result<Ticket> {
+        Err(Error::NotFound)
+    }
+
+    async fn insert_promo_code(&self, _: &PromoCode) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_promo_code(&self, _: &str, _: &str) -> Result<PromoCode> {
+        Err(Error::NotFound)
+    }
+
+    async fn fetch_promo_codes(&self, _: &str) -> Result<Vec<PromoCode>> {
+        Ok(vec![])
+    }
+
+    async fn delete_promo_code(&self, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn redeem_promo_code(&self, _: &PromoCode) -> Result<()> {
+        Ok(())
+    }
+
+    async fn insert_promo_redemption(&self, _: &PromoRedemption) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_promo_redemptions(&self, _: &str) -> Result<Vec<PromoRedemption>> {
+        Ok(vec![])
+    }
+
+    async fn release_promo_redemption(&self, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn insert_event_template(&self, _: &EventTemplate) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_event_template(&self, _: &str, _: &str) -> Result<EventTemplate> {
+        Err(Error::NotFound)
+    }
+
+    async fn fetch_event_templates(&self, _: &str) -> Result<Vec<EventTemplate>> {
+        Ok(vec![])
+    }
+
+    async fn delete_event_template(&self, _: &str, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn record_event_activity(&self, _: &str, _: &str, _: EventActivityKind) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_event_activity(&self, _: &str) -> Result<Vec<EventActivityDay>> {
+        Ok(vec![])
+    }
+
+    async fn count_event_saves(&self, _: &str) -> Result<i32> {
+        Ok(0)
+    }
+
+    async fn save_event_feedback(&self, _: &EventFeedback) -> Result<()> {
         Ok(())
     }
 
-    async fn get_event_guests(&self, _: &str) -> Result<Vec<EventGuest>> {
+    async fn fetch_event_feedback(&self, _: &str, _: &str) -> Result<EventFeedback> {
+        Err(Error::NotFound)
+    }
+
+    async fn fetch_event_feedbacks(&self, _: &str) -> Result<Vec<EventFeedback>> {
         Ok(vec![])
     }
 
-    async fn get_guest(&self, _: &str, _: &str) -> Result<EventGuest> {
+    async fn fetch_calendar_feed(&self, _: &str) -> Result<CalendarFeed> {
+        Err(Error::NotFound)
+    }
+
+    async fn fetch_calendar_feed_by_token(&self, _: &str) -> Result<CalendarFeed> {
+        Err(Error::NotFound)
+    }
+
+    async fn save_calendar_feed(&self, _: &CalendarFeed) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_calendar_feed(&self, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn replace_event_reminders(&self, _: &str, _: &[EventReminder]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn claim_due_event_reminder(
+        &self,
+        _: DateTime<Utc>,
+        _: DateTime<Utc>,
+    ) -> Result<Option<EventReminder>> {
+        Ok(None)
+    }
+
+    async fn update_event_reminder(&self, _: &EventReminder) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_event_reminder(&self, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn insert_event_message_job(&self, _: &EventMessageJob) -> Result<()> {
+        Ok(())
+    }
+
+    async fn fetch_event_message_job(&self, _: &str, _: &str) -> Result<EventMessageJob> {
         Err(Error::NotFound)
     }
+
+    async fn claim_due_event_message_job(
+        &self,
+        _: DateTime<Utc>,
+        _: DateTime<Utc>,
+    ) -> Result<Option<EventMessageJob>> {
+        Ok(None)
+    }
+
+    async fn update_event_message_job(&self, _: &EventMessageJob) -> Result<()> {
+        Ok(())
+    }
+
+    async fn increment_event_invited_count(&self, id: &str, count: i32) -> Result<()> {
+        let mut events = self.events.lock().await;
+        let event = events.get_mut(id).ok_or(Error::NotFound)?;
+        event.invited_count = Some(event.invited_count.unwrap_or(0) + count);
+        Ok(())
+    }
+
+    async fn fetch_unarchived_event_groups(&self) -> Result<Vec<Channel>> {
+        Ok(vec![])
+    }
+
+    async fn fetch_scheduled_events(&self, _: DateTime<Utc>) -> Result<Vec<Event>> {
+        Ok(vec![])
+    }
+
+    async fn fetch_ended_events(&self, _: DateTime<Utc>) -> Result<Vec<Event>> {
+        Ok(vec![])
+    }
+}
+
+// Helper function to restrict a list of events to what the viewer may see
+async fn restrict_events(
+    db: &DummyDb,
+    viewer: EventViewer<'_>,
+    events: Vec<Event>,
+) -> Result<Vec<Event>> {
+    Ok(match EventAccess::resolve(db, viewer).await? {
+        Some(access) => events
+            .into_iter()
+            .filter_map(|event| access.restrict(event))
+            .collect(),
+        None => events,
+    })
+}
+
+// Helper function to check an event against the filters of a query
+fn matches_query(event: &Event, query: &EventQuery) -> bool {
+    let status = match &query.status {
+        Some(status) => &event.status == status,
+        None => event.status != EventStatus::Draft,
+    };
+
+    status
+        && (query.event_type.is_none() || event.event_type == query.event_type)
+        && query
+            .start_date_from
+            .map_or(true, |from| event.start_date >= from)
+        && query
+            .start_date_to
+            .map_or(true, |to| event.start_date <= to)
+        && query
+            .city
+            .as_ref()
+            .map_or(true, |city| event.city.eq_ignore_ascii_case(city))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::models::event::{
+        Event, EventQuery, EventStatus, EventType, EventViewer, GeoPoint, GeoRadius, TicketConfig,
+    };
+    use crate::models::events::guest::{EventGuest, GuestStatus};
+    use crate::models::events::ticket::{Ticket, TicketStatus, TicketTier};
+    use crate::models::server_member::Member;
+    use crate::r#impl::DummyDb;
+    use crate::variables::delta::DEFAULT_SERVER;
+    use crate::{Database, Error};
+
+    const HOST: &str = "host";
+    const MEMBER: &str = "member";
+    const GUEST: &str = "guest";
+    const OUTSIDER: &str = "outsider";
+
+    fn event(id: &str, event_type: EventType, show_to_non_members: bool) -> Event {
+        let start_date = Utc::now() + Duration::days(7);
+
+        Event {
+            id: id.to_string(),
+            created_by: Some(HOST.to_string()),
+            title: id.to_string(),
+            event_type: Some(event_type),
+            start_date,
+            end_date: start_date + Duration::hours(3),
+            timezone: "UTC".to_string(),
+            city: "Nairobi".to_string(),
+            country: None,
+            hide_address: false,
+            area: "Westlands".to_string(),
+            address: "1 Example Road".to_string(),
+            location: Some(GeoPoint::new(-1.26789, 36.80789)),
+            description: None,
+            allow_plus_one: false,
+            allow_plus_one_amount: None,
+            requires_plus_one_info: false,
+            requires_rsvp_approval: false,
+            show_to_non_members,
+            disable_reminders: false,
+            public_ratings: false,
+            channel_id: None,
+            capacity: None,
+            hosts: vec![],
+            host_details: None,
+            managers: vec![],
+            staff_permissions: Default::default(),
+            sponsors: vec![],
+            sponsor_details: None,
+            questions: vec![],
+            ticket_config: TicketConfig::default(),
+            ticket_counts: Default::default(),
+            seats_taken: 0,
+            currency: None,
+            payment_type: None,
+            attachments: vec![],
+            gallery: vec![],
+            is_saved: None,
+            created_at: Utc::now(),
+            series_id: None,
+            sequence: 0,
+            status: EventStatus::Published,
+            publish_at: None,
+            guests: None,
+            guest_stats: None,
+            distance: None,
+            thumbnail: None,
+            invited_count: None,
+        }
+    }
+
+    fn guest(event_id: &str, user_id: &str, status: GuestStatus) -> EventGuest {
+        EventGuest {
+            id: format!("{event_id}:{user_id}"),
+            event_id: event_id.to_string(),
+            parent_guest_id: None,
+            plus_one_of: None,
+            user_id: Some(user_id.to_string()),
+            name: user_id.to_string(),
+            email: format!("{user_id}@example.com"),
+            phone: String::new(),
+            answers: Default::default(),
+            status,
+            is_plus_one: None,
+            created_at: Utc::now().to_rfc3339(),
+            checked_in_at: None,
+            checked_in_by: None,
+            checkin_pass: None,
+        }
+    }
+
+    fn ticket(id: &str, user_id: &str, quantity: i32) -> Ticket {
+        Ticket {
+            id: id.to_string(),
+            event_id: "event".to_string(),
+            user_id: user_id.to_string(),
+            tier: TicketTier::Member,
+            quantity,
+            price: None,
+            currency: None,
+            promo_code: None,
+            status: TicketStatus::Confirmed,
+            payment: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    async fn database(events: Vec<Event>) -> Database {
+        let db = Database::Dummy(DummyDb::default());
+        db.insert_member(&Member::new(DEFAULT_SERVER.to_string(), MEMBER.to_string()))
+            .await
+            .unwrap();
+
+        for event in &events {
+            db.insert_event(event).await.unwrap();
+        }
+
+        db
+    }
+
+    async fn can_see(db: &Database, viewer: EventViewer<'_>, id: &str) -> bool {
+        match db.fetch_event(viewer, id).await {
+            Ok(_) => true,
+            Err(Error::NotFound) => false,
+            Err(error) => panic!("unexpected error {error:?}"),
+        }
+    }
+
+    #[async_std::test]
+    async fn members_only_events_are_hidden_from_non_members() {
+        let db = database(vec![event("private", EventType::Other, false)]).await;
+        db.add_guest(&guest("private", GUEST, GuestStatus::Approved))
+            .await
+            .unwrap();
+
+        assert!(can_see(&db, EventViewer::System, "private").await);
+        assert!(can_see(&db, EventViewer::User(HOST), "private").await);
+        assert!(can_see(&db, EventViewer::User(MEMBER), "private").await);
+        assert!(can_see(&db, EventViewer::User(GUEST), "private").await);
+        assert!(!can_see(&db, EventViewer::User(OUTSIDER), "private").await);
+        assert!(!can_see(&db, EventViewer::Anonymous, "private").await);
+    }
+
+    #[async_std::test]
+    async fn visible_events_can_be_saved() {
+        let db = database(vec![
+            event("public", EventType::Other, true),
+            event("private", EventType::Other, false),
+        ])
+        .await;
+
+        let (event, saved) = db.toggle_saved_event(OUTSIDER, "public").await.unwrap();
+        assert_eq!(event.id, "public");
+        assert!(saved);
+
+        assert!(matches!(
+            db.toggle_saved_event(OUTSIDER, "private").await,
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[async_std::test]
+    async fn events_shown_to_non_members_are_public() {
+        let db = database(vec![event("public", EventType::Other, true)]).await;
+
+        assert!(can_see(&db, EventViewer::User(OUTSIDER), "public").await);
+        assert!(can_see(&db, EventViewer::Anonymous, "public").await);
+    }
+
+    #[async_std::test]
+    async fn members_events_are_hidden_from_non_members() {
+        let db = database(vec![event("members", EventType::MembersEvent, true)]).await;
+        db.add_guest(&guest("members", GUEST, GuestStatus::Approved))
+            .await
+            .unwrap();
+
+        assert!(can_see(&db, EventViewer::User(MEMBER), "members").await);
+        assert!(can_see(&db, EventViewer::User(GUEST), "members").await);
+        assert!(!can_see(&db, EventViewer::User(OUTSIDER), "members").await);
+        assert!(!can_see(&db, EventViewer::Anonymous, "members").await);
+    }
+
+    #[async_std::test]
+    async fn drafts_are_only_shown_to_staff() {
+        let mut draft = event("draft", EventType::Other, true);
+        draft.status = EventStatus::Draft;
+        draft.managers = vec![GUEST.to_string()];
+        let db = database(vec![draft]).await;
+
+        assert!(can_see(&db, EventViewer::User(HOST), "draft").await);
+        assert!(can_see(&db, EventViewer::User(GUEST), "draft").await);
+        assert!(!can_see(&db, EventViewer::User(MEMBER), "draft").await);
+        assert!(!can_see(&db, EventViewer::Anonymous, "draft").await);
+    }
+
+    #[async_std::test]
+    async fn hidden_addresses_are_redacted() {
+        let mut hidden = event("hidden", EventType::Other, true);
+        hidden.hide_address = true;
+        let db = database(vec![hidden]).await;
+        db.add_guest(&guest("hidden", GUEST, GuestStatus::Approved))
+            .await
+            .unwrap();
+        db.add_guest(&guest("hidden", MEMBER, GuestStatus::Pending))
+            .await
+            .unwrap();
+
+        for viewer in [
+            EventViewer::System,
+            EventViewer::User(HOST),
+            EventViewer::User(GUEST),
+        ] {
+            let event = db.fetch_event(viewer, "hidden").await.unwrap();
+            assert_eq!(event.address, "1 Example Road");
+            assert_eq!(event.location, Some(GeoPoint::new(-1.26789, 36.80789)));
+        }
+
+        for viewer in [
+            EventViewer::User(MEMBER),
+            EventViewer::User(OUTSIDER),
+            EventViewer::Anonymous,
+        ] {
+            let event = db.fetch_event(viewer, "hidden").await.unwrap();
+            assert_eq!(event.address, "");
+            assert_eq!(event.location, Some(GeoPoint::new(-1.27, 36.81)));
+        }
+    }
+
+    #[async_std::test]
+    async fn hidden_addresses_are_searched_by_rounded_coordinates() {
+        let mut hidden = event("hidden", EventType::Other, true);
+        hidden.hide_address = true;
+        let db = database(vec![hidden]).await;
+
+        let mut query = EventQuery {
+            near: Some(GeoRadius {
+                center: GeoPoint::new(-1.26789, 36.80789),
+                radius: 1.0,
+            }),
+            ..Default::default()
+        };
+
+        let (_, total) = db
+            .query_events(EventViewer::Anonymous, &query)
+            .await
+            .unwrap();
+        assert_eq!(total, 0);
+
+        query.near = Some(GeoRadius {
+            center: GeoPoint::new(-1.27, 36.81),
+            radius: 1.0,
+        });
+
+        let (_, total) = db
+            .query_events(EventViewer::Anonymous, &query)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+    }
+
+    #[async_std::test]
+    async fn addresses_are_shown_unless_hidden() {
+        let db = database(vec![event("open", EventType::Other, true)]).await;
+
+        let event = db
+            .fetch_event(EventViewer::Anonymous, "open")
+            .await
+            .unwrap();
+        assert_eq!(event.address, "1 Example Road");
+    }
+
+    #[async_std::test]
+    async fn queries_only_list_visible_events() {
+        let db = database(vec![
+            event("public", EventType::Other, true),
+            event("private", EventType::Other, false),
+            event("members", EventType::MembersEvent, true),
+        ])
+        .await;
+
+        let query = EventQuery::default();
+        let ids = |events: Vec<Event>| {
+            let mut ids: Vec<String> = events.into_iter().map(|event| event.id).collect();
+            ids.sort();
+            ids
+        };
+
+        let (events, total) = db
+            .query_events(EventViewer::Anonymous, &query)
+            .await
+            .unwrap();
+        assert_eq!(ids(events), vec!["public"]);
+        assert_eq!(total, 1);
+
+        let (events, total) = db
+            .query_events(EventViewer::User(MEMBER), &query)
+            .await
+            .unwrap();
+        assert_eq!(ids(events), vec!["members", "private", "public"]);
+        assert_eq!(total, 3);
+    }
+
+    #[async_std::test]
+    async fn single_tickets_are_held_once_until_cancelled() {
+        let db = database(vec![event("event", EventType::Other, true)]).await;
+
+        db.reserve_ticket(&ticket("first", GUEST, 1), None, true)
+            .await
+            .unwrap();
+        assert!(matches!(
+            db.reserve_ticket(&ticket("second", GUEST, 1), None, true)
+                .await,
+            Err(Error::TooManyTickets { max: 1 })
+        ));
+
+        db.cancel_ticket("event", "first").await.unwrap();
+        db.reserve_ticket(&ticket("second", GUEST, 1), None, true)
+            .await
+            .unwrap();
+
+        // Other buyers are not affected
+        db.reserve_ticket(&ticket("third", MEMBER, 1), None, true)
+            .await
+            .unwrap();
+    }
+
+    #[async_std::test]
+    async fn tickets_are_held_against_the_tier_limit() {
+        let db = database(vec![event("event", EventType::Other, true)]).await;
+
+        db.reserve_ticket(&ticket("first", GUEST, 3), Some(4), false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            db.reserve_ticket(&ticket("second", MEMBER, 2), Some(4), false)
+                .await,
+            Err(Error::TicketsSoldOut)
+        ));
+
+        db.reserve_ticket(&ticket("second", MEMBER, 1), Some(4), false)
+            .await
+            .unwrap();
+        let event = db.fetch_event(EventViewer::System, "event").await.unwrap();
+        assert_eq!(event.ticket_counts.member, 4);
+
+        db.cancel_ticket("event", "first").await.unwrap();
+        let event = db.fetch_event(EventViewer::System, "event").await.unwrap();
+        assert_eq!(event.ticket_counts.member, 1);
+        assert_eq!(
+            db.fetch_user_tickets("event", GUEST).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[async_std::test]
+    async fn users_are_added_once_until_they_cancel() {
+        let db = database(vec![event("event", EventType::Other, true)]).await;
+        db.add_guest(&guest("event", GUEST, GuestStatus::Pending))
+            .await
+            .unwrap();
+
+        let mut second = guest("event", GUEST, GuestStatus::Pending);
+        second.id = "second".into();
+        assert!(matches!(
+            db.add_guest(&second).await,
+            Err(Error::AlreadyRsvped)
+        ));
+
+        let id = format!("event:{GUEST}");
+        db.update_guest_status("event", &id, GuestStatus::Pending, GuestStatus::Cancelled)
+            .await
+            .unwrap();
+        db.add_guest(&second).await.unwrap();
+    }
+
+    async fn status_of(db: &Database, id: &str) -> GuestStatus {
+        db.get_guest("event", id).await.unwrap().status
+    }
+
+    async fn seats_taken(db: &Database) -> i32 {
+        db.fetch_event(EventViewer::System, "event")
+            .await
+            .unwrap()
+            .seats_taken
+    }
+
+    #[async_std::test]
+    async fn approvals_past_capacity_are_waitlisted() {
+        let mut event = event("event", EventType::Other, true);
+        event.capacity = Some(1);
+        let db = database(vec![event.clone()]).await;
+        for user_id in ["a", "b"] {
+            db.add_guest(&guest("event", user_id, GuestStatus::Pending))
+                .await
+                .unwrap();
+        }
+
+        let mut guests = db.get_event_guests("event").await.unwrap();
+        let status = event
+            .set_guests_status(
+                &db,
+                HOST,
+                &mut guests,
+                &["event:a".into()],
+                GuestStatus::Approved,
+            )
+            .await
+            .unwrap();
+        assert_eq!(status, GuestStatus::Approved);
+
+        let status = event
+            .set_guests_status(
+                &db,
+                HOST,
+                &mut guests,
+                &["event:b".into()],
+                GuestStatus::Approved,
+            )
+            .await
+            .unwrap();
+        assert_eq!(status, GuestStatus::Waitlisted);
+        assert_eq!(status_of(&db, "event:b").await, GuestStatus::Waitlisted);
+        assert_eq!(seats_taken(&db).await, 1);
+    }
+
+    #[async_std::test]
+    async fn released_seats_go_to_the_waitlist_in_order() {
+        let mut event = event("event", EventType::Other, true);
+        event.capacity = Some(1);
+        event.seats_taken = 1;
+        let db = database(vec![event.clone()]).await;
+        db.add_guest(&guest("event", "a", GuestStatus::Approved))
+            .await
+            .unwrap();
+        for user_id in ["c", "b"] {
+            db.add_guest(&guest("event", user_id, GuestStatus::Waitlisted))
+                .await
+                .unwrap();
+        }
+
+        let mut guests = db.get_event_guests("event").await.unwrap();
+        event
+            .set_guests_status(
+                &db,
+                HOST,
+                &mut guests,
+                &["event:a".into()],
+                GuestStatus::Cancelled,
+            )
+            .await
+            .unwrap();
+        assert_eq!(seats_taken(&db).await, 0);
+
+        let promoted = event.promote_waitlisted_guests(&db, HOST).await.unwrap();
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].id, "event:b");
+        assert_eq!(status_of(&db, "event:b").await, GuestStatus::Approved);
+        assert_eq!(status_of(&db, "event:c").await, GuestStatus::Waitlisted);
+        assert_eq!(seats_taken(&db).await, 1);
+
+        // Nothing is left to hand out
+        assert!(event
+            .promote_waitlisted_guests(&db, HOST)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[async_std::test]
+    async fn waitlisted_guests_are_only_promoted_once() {
+        let mut event = event("event", EventType::Other, true);
+        event.capacity = Some(2);
+        let db = database(vec![event.clone()]).await;
+        db.add_guest(&guest("event", "a", GuestStatus::Waitlisted))
+            .await
+            .unwrap();
+
+        let mut stale = db.get_event_guests("event").await.unwrap();
+        let promoted = event.promote_waitlisted_guests(&db, HOST).await.unwrap();
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(seats_taken(&db).await, 1);
+
+        // A second promotion working from the old guest list gives its seat back
+        event
+            .set_guests_status(
+                &db,
+                HOST,
+                &mut stale,
+                &["event:a".into()],
+                GuestStatus::Approved,
+            )
+            .await
+            .unwrap();
+        assert_eq!(stale[0].status, GuestStatus::Waitlisted);
+        assert_eq!(status_of(&db, "event:a").await, GuestStatus::Approved);
+        assert_eq!(seats_taken(&db).await, 1);
+    }
 }
@@ -1,3 +1,11 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::lock::Mutex;
+
+use crate::models::event::Event;
+use crate::models::events::guest::EventGuest;
+use crate::models::events::ticket::Ticket;
+use crate::models::server_member::{Member, MemberCompositeKey};
 use crate::AbstractDatabase;
 
 pub mod admin {
@@ -37,7 +45,16 @@ pub mod events {
     pub mod event;
 }
 
-#[derive(Debug, Clone)]
-pub struct DummyDb;
+/// Mock database
+///
+/// Events, their guests and tickets and server members are kept in memory,
+/// everything else is made up on the spot.
+#[derive(Debug, Clone, Default)]
+pub struct DummyDb {
+    pub events: Arc<Mutex<HashMap<String, Event>>>,
+    pub event_guests: Arc<Mutex<Vec<EventGuest>>>,
+    pub event_tickets: Arc<Mutex<Vec<Ticket>>>,
+    pub server_members: Arc<Mutex<HashMap<MemberCompositeKey, Member>>>,
+}
 
 impl AbstractDatabase for DummyDb {}
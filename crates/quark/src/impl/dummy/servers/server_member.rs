@@ -1,16 +1,30 @@
 use crate::models::server_member::{FieldsMember, Member, MemberCompositeKey, PartialMember};
-use crate::{AbstractServerMember, Result};
+use crate::{AbstractServerMember, Error, Result};
 
 use super::super::DummyDb;
 
 #[async_trait]
 impl AbstractServerMember for DummyDb {
     async fn fetch_member(&self, server: &str, user: &str) -> Result<Member> {
-        Ok(Member::new(server.into(), user.into()))
+        let id = MemberCompositeKey {
+            server: server.into(),
+            user: user.into(),
+        };
+
+        self.server_members
+            .lock()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or(Error::NotFound)
     }
 
     async fn insert_member(&self, member: &Member) -> Result<()> {
         info!("Create {member:?}");
+        self.server_members
+            .lock()
+            .await
+            .insert(member.id.clone(), member.clone());
         Ok(())
     }
 
@@ -26,26 +40,48 @@ impl AbstractServerMember for DummyDb {
 
     async fn delete_member(&self, id: &MemberCompositeKey) -> Result<()> {
         info!("Delete {id:?}");
+        self.server_members.lock().await.remove(id);
         Ok(())
     }
 
     async fn fetch_all_members<'a>(&self, server: &str) -> Result<Vec<Member>> {
-        Ok(vec![self.fetch_member(server, "member").await.unwrap()])
+        Ok(self
+            .server_members
+            .lock()
+            .await
+            .values()
+            .filter(|member| member.id.server == server)
+            .cloned()
+            .collect())
     }
 
     async fn fetch_all_memberships<'a>(&self, user: &str) -> Result<Vec<Member>> {
-        Ok(vec![self.fetch_member("server", user).await.unwrap()])
+        Ok(self
+            .server_members
+            .lock()
+            .await
+            .values()
+            .filter(|member| member.id.user == user)
+            .cloned()
+            .collect())
     }
 
-    async fn fetch_members<'a>(&self, server: &str, _ids: &'a [String]) -> Result<Vec<Member>> {
-        Ok(vec![self.fetch_member(server, "member").await.unwrap()])
+    async fn fetch_members<'a>(&self, server: &str, ids: &'a [String]) -> Result<Vec<Member>> {
+        Ok(self
+            .server_members
+            .lock()
+            .await
+            .values()
+            .filter(|member| member.id.server == server && ids.contains(&member.id.user))
+            .cloned()
+            .collect())
     }
 
-    async fn fetch_member_count(&self, _server: &str) -> Result<usize> {
-        Ok(100)
+    async fn fetch_member_count(&self, server: &str) -> Result<usize> {
+        Ok(self.fetch_all_members(server).await?.len())
     }
 
-    async fn fetch_server_count(&self, _user: &str) -> Result<usize> {
-        Ok(5)
+    async fn fetch_server_count(&self, user: &str) -> Result<usize> {
+        Ok(self.fetch_all_memberships(user).await?.len())
     }
 }
@@ -6,6 +6,7 @@ use crate::{
     events::client::EventV1,
     models::{
         channel::{FieldsChannel, PartialChannel},
+        event::EventViewer,
         message::{DataMessageSend, Message, Reply, SystemMessage, RE_MENTION},
         Channel,
     },
@@ -163,6 +164,7 @@ impl Channel {
                 icon,
                 nsfw,
                 permissions,
+                archived,
                 ..
             } => {
                 if let Some(v) = partial.name {
@@ -188,6 +190,10 @@ impl Channel {
                 if let Some(v) = partial.permissions {
                     permissions.replace(v);
                 }
+
+                if let Some(v) = partial.archived {
+                    *archived = v;
+                }
             }
             Self::TextChannel {
                 name,
@@ -257,6 +263,36 @@ impl Channel {
         Ok(())
     }
 
+    /// Check whether a user has owner rights over a group
+    ///
+    /// Staff of the event a group was created for share the owner's rights.
+    pub async fn is_group_owner(&self, db: &Database, user: &str) -> Result<bool> {
+        match self {
+            Channel::Group {
+                owner,
+                recipients,
+                event,
+                ..
+            } => {
+                if owner == user {
+                    return Ok(true);
+                }
+
+                match event {
+                    Some(event) if recipients.iter().any(|x| x == user) => {
+                        match db.fetch_event(EventViewer::System, event).await {
+                            Ok(event) => Ok(event.is_staff(user)),
+                            Err(Error::NotFound) => Ok(false),
+                            Err(err) => Err(err),
+                        }
+                    }
+                    _ => Ok(false),
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
     /// Add user to a group
     pub async fn add_user_to_group(&mut self, db: &Database, user: &str, by: &str) -> Result<()> {
         if let Channel::Group { recipients, .. } = self {
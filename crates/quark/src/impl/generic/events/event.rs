@@ -0,0 +1,195 @@
+use ulid::Ulid;
+
+use crate::{
+    models::event::{Event, EventAccess, EventViewer},
+    models::events::guest::{count_seats, EventGuest, GuestStatus, GuestStatusChange},
+    variables::delta::DEFAULT_SERVER,
+    AbstractDatabase, Database, Error, Result,
+};
+
+impl EventAccess {
+    /// Work out what a viewer may see, `None` if they may see everything
+    pub async fn resolve(
+        db: &dyn AbstractDatabase,
+        viewer: EventViewer<'_>,
+    ) -> Result<Option<EventAccess>> {
+        Ok(match viewer {
+            EventViewer::System => None,
+            EventViewer::Anonymous => Some(EventAccess::default()),
+            EventViewer::User(user_id) => Some(EventAccess {
+                user_id: Some(user_id.to_string()),
+                is_member: db.fetch_member(&DEFAULT_SERVER, user_id).await.is_ok(),
+                attending: db.fetch_attending_event_ids(user_id).await?,
+            }),
+        })
+    }
+}
+
+impl Event {
+    /// Add guests to this event, taking the seats they need
+    ///
+    /// Guests who would take seats the event no longer has join the
+    /// waitlist instead. Added guests are appended to `guests`.
+    pub async fn add_guests(
+        &self,
+        db: &Database,
+        guests: &mut Vec<EventGuest>,
+        party: &mut [EventGuest],
+    ) -> Result<()> {
+        let seats = seats_added(guests, party);
+        let taken = seats > 0 && db.take_event_seats(&self.id, seats, self.capacity).await?;
+        if seats > 0 && !taken {
+            for guest in party.iter_mut() {
+                guest.status = GuestStatus::Waitlisted;
+            }
+        }
+
+        for (index, guest) in party.iter().enumerate() {
+            if let Err(error) = db.add_guest(guest).await {
+                // Give back the seats of everyone who was not added
+                if taken {
+                    let seats = seats_added(guests, &party[index..]);
+                    db.release_event_seats(&self.id, seats).await?;
+                }
+
+                return Err(error);
+            }
+
+            guests.push(guest.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Move guests to another status, taking or giving back the seats involved
+    ///
+    /// Guests who would take seats the event no longer has are waitlisted
+    /// instead, the status they were moved to is returned. Guests whose
+    /// status changed since `guests` was fetched are left as they are.
+    pub async fn set_guests_status(
+        &self,
+        db: &Database,
+        changed_by: &str,
+        guests: &mut [EventGuest],
+        ids: &[String],
+        status: GuestStatus,
+    ) -> Result<GuestStatus> {
+        let before = count_seats(guests);
+        let mut status = status;
+        let mut held = seats_moved(guests, ids, &status);
+        if held > 0 && !db.take_event_seats(&self.id, held, self.capacity).await? {
+            status = GuestStatus::Waitlisted;
+            held = 0;
+        } else if held < 0 {
+            held = 0;
+        }
+
+        for id in ids {
+            let guest = guests
+                .iter_mut()
+                .find(|guest| &guest.id == id)
+                .ok_or(Error::NotFound)?;
+
+            if guest.status == status
+                || !db
+                    .update_guest_status(&self.id, id, guest.status.clone(), status.clone())
+                    .await?
+            {
+                continue;
+            }
+
+            db.insert_guest_status_change(&GuestStatusChange {
+                id: Ulid::new().to_string(),
+                event_id: self.id.clone(),
+                guest_id: id.clone(),
+                from: guest.status.clone(),
+                to: status.clone(),
+                changed_by: changed_by.to_string(),
+                changed_at: chrono::Utc::now(),
+            })
+            .await?;
+
+            guest.status = status.clone();
+        }
+
+        // Seats taken for guests someone else moved first are not needed
+        let moved = count_seats(guests) - before;
+        if held > moved {
+            db.release_event_seats(&self.id, held - moved).await?;
+        }
+
+        Ok(status)
+    }
+
+    /// Move waitlisted guests into free seats, first come first served
+    ///
+    /// Plus-ones move along with their main guest. Returns the guests who
+    /// were promoted, guests promoted by someone else in the meantime are
+    /// not promoted again.
+    pub async fn promote_waitlisted_guests(
+        &self,
+        db: &Database,
+        changed_by: &str,
+    ) -> Result<Vec<EventGuest>> {
+        let mut guests = db.get_event_guests(&self.id).await?;
+        guests.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let queue: Vec<String> = guests
+            .iter()
+            .filter(|guest| guest.status == GuestStatus::Waitlisted)
+            .filter(|guest| {
+                !guests.iter().any(|main| {
+                    guest.plus_one_of.as_ref() == Some(&main.id)
+                        && main.status == GuestStatus::Waitlisted
+                })
+            })
+            .map(|guest| guest.id.clone())
+            .collect();
+
+        let mut promoted = Vec::new();
+        for guest_id in queue {
+            let party: Vec<String> = guests
+                .iter()
+                .filter(|guest| guest.status == GuestStatus::Waitlisted)
+                .filter(|guest| {
+                    guest.id == guest_id || guest.plus_one_of.as_ref() == Some(&guest_id)
+                })
+                .map(|guest| guest.id.clone())
+                .collect();
+
+            let status = self
+                .set_guests_status(db, changed_by, &mut guests, &party, GuestStatus::Approved)
+                .await?;
+
+            if status != GuestStatus::Approved {
+                break;
+            }
+
+            promoted.extend(
+                guests
+                    .iter()
+                    .filter(|guest| party.contains(&guest.id))
+                    .filter(|guest| guest.status == GuestStatus::Approved)
+                    .cloned(),
+            );
+        }
+
+        Ok(promoted)
+    }
+}
+
+// Helper function to count the seats taken by adding guests to a guest list
+fn seats_added(guests: &[EventGuest], added: &[EventGuest]) -> i32 {
+    let after: Vec<EventGuest> = guests.iter().chain(added).cloned().collect();
+    count_seats(&after) - count_seats(guests)
+}
+
+// Helper function to count the seats taken by moving guests to another status
+fn seats_moved(guests: &[EventGuest], ids: &[String], status: &GuestStatus) -> i32 {
+    let mut after = guests.to_vec();
+    for guest in after.iter_mut().filter(|guest| ids.contains(&guest.id)) {
+        guest.status = status.clone();
+    }
+
+    count_seats(&after) - count_seats(guests)
+}
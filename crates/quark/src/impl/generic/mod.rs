@@ -28,3 +28,7 @@ pub mod safety {
     pub mod report;
     pub mod snapshot;
 }
+
+pub mod events {
+    pub mod event;
+}
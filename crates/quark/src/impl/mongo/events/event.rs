@@ -1,23 +1,54 @@
+use crate::models::channel::Channel;
 use crate::models::event::EventGuestStats;
-use crate::models::event::{Event, EventHost, PartialEvent};
-use crate::models::guest::{EventGuest, GuestStatus};
+use crate::models::event::{
+    Event, EventAccess, EventHost, EventQuery, EventStatus, EventType, EventViewer, PartialEvent,
+    EARTH_RADIUS,
+};
+use crate::models::events::analytics::{EventActivityDay, EventActivityKind};
+use crate::models::events::calendar::CalendarFeed;
+use crate::models::events::feedback::EventFeedback;
+use crate::models::guest::{EventGuest, GuestStatus, GuestStatusChange};
+use crate::models::message_job::{EventMessageJob, MessageJobStatus};
+use crate::models::promo_code::{PromoCode, PromoRedemption};
+use crate::models::reminder::EventReminder;
 use crate::models::saved_event::SavedEvent;
+use crate::models::series::EventSeries;
+use crate::models::template::EventTemplate;
+use crate::models::ticket::{Ticket, TicketPayment, TicketStatus};
 use crate::models::user::User;
 use crate::{AbstractEvents, Error, Result};
-use bson::Document;
+use bson::{Bson, Document};
+use chrono::{DateTime, Utc};
 use iso8601_timestamp::Timestamp;
 use mongodb::bson::doc;
+use mongodb::error::{ErrorKind, WriteError, WriteFailure};
+use mongodb::options::{FindOptions, ReplaceOptions, UpdateOptions};
 
 use super::super::MongoDb;
 
 static COL: &str = "events";
 static SAVED_EVENTS_COL: &str = "saved_events";
 static GUESTS_COL: &str = "event_guests";
+static TICKETS_COL: &str = "event_tickets";
+static GUEST_HISTORY_COL: &str = "event_guest_history";
+static CALENDAR_FEEDS_COL: &str = "event_calendar_feeds";
+static SERIES_COL: &str = "event_series";
+static REMINDERS_COL: &str = "event_reminders";
+static MESSAGE_JOBS_COL: &str = "event_message_jobs";
+static PROMO_CODES_COL: &str = "event_promo_codes";
+static PROMO_REDEMPTIONS_COL: &str = "event_promo_redemptions";
+static TEMPLATES_COL: &str = "event_templates";
+static FEEDBACK_COL: &str = "event_feedback";
+static ACTIVITY_COL: &str = "event_activity_days";
+static CHANNELS_COL: &str = "channels";
 
 #[async_trait]
 impl AbstractEvents for MongoDb {
-    async fn fetch_event(&self, user_id: Option<&str>, id: &str) -> Result<Event> {
+    async fn fetch_event(&self, viewer: EventViewer<'_>, id: &str) -> Result<Event> {
         let mut event: Event = self.find_one_by_id(COL, id).await?;
+        if let Some(access) = EventAccess::resolve(self, viewer).await? {
+            event = access.restrict(event).ok_or(Error::NotFound)?;
+        }
 
         // Fetch host details
         let host_details = fetch_user_details(self, &event.hosts).await?;
@@ -46,23 +77,70 @@ impl AbstractEvents for MongoDb {
                 .iter()
                 .filter(|g| matches!(g.status, GuestStatus::Rejected))
                 .count() as i32,
+            total_waitlisted: guests
+                .iter()
+                .filter(|g| matches!(g.status, GuestStatus::Waitlisted))
+                .count() as i32,
         };
 
         event.guest_stats = Some(stats);
 
         // Check saved status
-        if let Some(user_id) = user_id {
+        if let Some(user_id) = viewer.user_id() {
             event.is_saved = Some(self.is_event_saved(user_id, id).await?);
         }
 
         Ok(event)
     }
     async fn insert_event(&self, event: &Event) -> Result<()> {
-        self.insert_one(COL, event).await.map(|_| ())
+        let mut document = bson::to_document(event).map_err(|_| Error::DatabaseError {
+            operation: "to_document",
+            with: COL,
+        })?;
+
+        if let Some(location) = event.search_location() {
+            document.insert("search_location", to_bson(&location, COL)?);
+        }
+
+        self.insert_one(COL, document).await.map(|_| ())
     }
 
     async fn update_event(&self, id: &str, event: &PartialEvent) -> Result<()> {
-        self.update_one_by_id(COL, id, event, vec![], None).await?;
+        let mut partial = bson::to_document(event).map_err(|_| Error::DatabaseError {
+            operation: "to_document",
+            with: COL,
+        })?;
+
+        // Dates must be stored as BSON datetimes so they sort and filter correctly
+        for (key, date) in [
+            ("start_date", event.start_date),
+            ("end_date", event.end_date),
+            ("created_at", event.created_at),
+            ("publish_at", event.publish_at),
+        ] {
+            if let Some(date) = date {
+                partial.insert(key, bson::DateTime::from_chrono(date));
+            }
+        }
+
+        // Keep the point searches run against in line with the location and its visibility
+        if event.location.is_some() || event.hide_address.is_some() {
+            let mut existing: Event = self.find_one_by_id(COL, id).await?;
+            if let Some(location) = &event.location {
+                existing.location = Some(location.clone());
+            }
+
+            if let Some(hide_address) = event.hide_address {
+                existing.hide_address = hide_address;
+            }
+
+            if let Some(location) = existing.search_location() {
+                partial.insert("search_location", to_bson(&location, COL)?);
+            }
+        }
+
+        self.update_one_by_id(COL, id, partial, vec![], None)
+            .await?;
         Ok(())
     }
 
@@ -72,10 +150,10 @@ impl AbstractEvents for MongoDb {
 
     async fn fetch_events<'a>(
         &self,
-        user_id: Option<&str>,
+        viewer: EventViewer<'_>,
         ids: &'a [String],
     ) -> Result<Vec<Event>> {
-        let mut events: Vec<Event> = if ids.is_empty() {
+        let events: Vec<Event> = if ids.is_empty() {
             // Use find with sort option to get events sorted by start_date in ascending order
             self.find_with_options(
                 "events",
@@ -89,6 +167,7 @@ impl AbstractEvents for MongoDb {
             self.find("events", doc! { "_id": { "$in": ids } }).await?
         };
 
+        let mut events = restrict_events(self, viewer, events).await?;
         for event in &mut events {
             // Fetch host details
             let host_details = fetch_user_details(self, &event.hosts).await?;
@@ -99,7 +178,7 @@ impl AbstractEvents for MongoDb {
             event.sponsor_details = Some(sponsor_details);
 
             // Set saved status
-            if let Some(user_id) = user_id {
+            if let Some(user_id) = viewer.user_id() {
                 event.is_saved = Some(self.is_event_saved(user_id, &event.id).await?);
             } else {
                 event.is_saved = None;
@@ -109,11 +188,157 @@ impl AbstractEvents for MongoDb {
         Ok(events)
     }
 
+    async fn query_events(
+        &self,
+        viewer: EventViewer<'_>,
+        query: &EventQuery,
+    ) -> Result<(Vec<Event>, u64)> {
+        let access = EventAccess::resolve(self, viewer).await?;
+        let mut filter = doc! {};
+
+        if let Some(event_type) = &query.event_type {
+            filter.insert(
+                "event_type",
+                bson::to_bson(event_type).map_err(|_| Error::DatabaseError {
+                    operation: "to_bson",
+                    with: COL,
+                })?,
+            );
+        }
+
+        let mut start_date = doc! {};
+        if let Some(from) = query.start_date_from {
+            start_date.insert("$gte", bson::DateTime::from_chrono(from));
+        }
+
+        if let Some(to) = query.start_date_to {
+            start_date.insert("$lte", bson::DateTime::from_chrono(to));
+        }
+
+        if !start_date.is_empty() {
+            filter.insert("start_date", start_date);
+        }
+
+        if let Some(city) = &query.city {
+            filter.insert(
+                "city",
+                doc! {
+                    "$regex": format!("^{}$", regex::escape(city)),
+                    "$options": "i"
+                },
+            );
+        }
+
+        filter.insert("status", status_filter(query.status.as_ref())?);
+
+        // Leave out events hidden from the viewer, mirroring `EventAccess::can_view`
+        if let Some(access) = &access {
+            let mut visible = match access.user_id.as_deref() {
+                Some(user_id) => vec![
+                    doc! { "created_by": user_id },
+                    doc! { "hosts": user_id },
+                    doc! { "managers": user_id },
+                ],
+                None => vec![],
+            };
+
+            let drafts = query.status == Some(EventStatus::Draft);
+            if !drafts && !access.is_member {
+                visible.push(doc! { "_id": { "$in": access.attending.clone() } });
+                visible.push(doc! {
+                    "show_to_non_members": true,
+                    "event_type": { "$ne": to_bson(&EventType::MembersEvent, COL)? }
+                });
+            }
+
+            // Members see every event which is not a draft
+            if drafts || !access.is_member {
+                if visible.is_empty() {
+                    return Ok((vec![], 0));
+                }
+
+                filter.insert("$or", visible);
+            }
+        }
+
+        // Counting does not support $nearSphere, so matches are counted within the same circle instead
+        let mut count_filter = filter.clone();
+        let mut options = FindOptions::builder()
+            .skip(query.skip())
+            .limit(query.per_page())
+            .build();
+
+        match &query.near {
+            Some(near) => {
+                let center = vec![near.center.longitude(), near.center.latitude()];
+                count_filter.insert(
+                    "search_location",
+                    doc! {
+                        "$geoWithin": {
+                            "$centerSphere": [center.clone(), near.radius / EARTH_RADIUS]
+                        }
+                    },
+                );
+
+                // Results of $nearSphere are already sorted nearest first
+                filter.insert(
+                    "search_location",
+                    doc! {
+                        "$nearSphere": {
+                            "$geometry": { "type": "Point", "coordinates": center },
+                            "$maxDistance": near.radius
+                        }
+                    },
+                );
+            }
+            None => options.sort = Some(doc! { "start_date": 1_i32, "_id": 1_i32 }),
+        }
+
+        let total = self
+            .col::<Document>(COL)
+            .count_documents(count_filter, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "count_documents",
+                with: COL,
+            })?;
+
+        let events: Vec<Event> = self.find_with_options(COL, filter, options).await?;
+
+        // Addresses are hidden from the viewer after the fact
+        let mut events: Vec<Event> = match &access {
+            Some(access) => events
+                .into_iter()
+                .filter_map(|event| access.restrict(event))
+                .collect(),
+            None => events,
+        };
+
+        for event in &mut events {
+            // Fetch host details
+            let host_details = fetch_user_details(self, &event.hosts).await?;
+            event.host_details = Some(host_details);
+
+            // Fetch sponsor details
+            let sponsor_details = fetch_user_details(self, &event.sponsors).await?;
+            event.sponsor_details = Some(sponsor_details);
+
+            // Set saved status
+            if let Some(user_id) = viewer.user_id() {
+                event.is_saved = Some(self.is_event_saved(user_id, &event.id).await?);
+            }
+        }
+
+        Ok((events, total))
+    }
+
     async fn toggle_saved_event(&self, user_id: &str, event_id: &str) -> Result<(Event, bool)> {
         let saved_id = format!("{}:{}", user_id, event_id);
 
         // Fetch the event first
-        let event = self.fetch_event(Some(user_id), event_id).await?;
+        let event = self
+            .fetch_event(EventViewer::User(user_id), event_id)
+            .await?;
 
         if event.is_saved.unwrap_or(false) {
             // Unsave - delete the record
@@ -148,7 +373,11 @@ impl AbstractEvents for MongoDb {
         Ok(exists.is_some())
     }
 
-    async fn get_saved_events(&self, user_id: &str) -> Result<Vec<Event>> {
+    async fn get_saved_events(
+        &self,
+        user_id: &str,
+        status: Option<&EventStatus>,
+    ) -> Result<Vec<Event>> {
         // First get all saved event IDs for this user
         let saved = self
             .find(SAVED_EVENTS_COL, doc! { "user_id": user_id })
@@ -163,8 +392,17 @@ impl AbstractEvents for MongoDb {
         if event_ids.is_empty() {
             Ok(vec![])
         } else {
-            let mut events: Vec<Event> =
-                self.find(COL, doc! { "_id": { "$in": event_ids } }).await?;
+            let events: Vec<Event> = self
+                .find(
+                    COL,
+                    doc! {
+                        "_id": { "$in": event_ids },
+                        "status": status_filter(status)?
+                    },
+                )
+                .await?;
+
+            let mut events = restrict_events(self, EventViewer::User(user_id), events).await?;
 
             // Set is_saved to true since these are saved events
             for event in &mut events {
@@ -200,21 +438,179 @@ impl AbstractEvents for MongoDb {
         Ok(events)
     }
 
+    async fn insert_event_series(&self, series: &EventSeries) -> Result<()> {
+        self.insert_one(SERIES_COL, series).await.map(|_| ())
+    }
+
+    async fn fetch_event_series(&self, id: &str) -> Result<EventSeries> {
+        self.find_one_by_id(SERIES_COL, id).await
+    }
+
+    async fn fetch_series_events(
+        &self,
+        viewer: EventViewer<'_>,
+        series_id: &str,
+    ) -> Result<Vec<Event>> {
+        let events = self
+            .find_with_options(
+                COL,
+                doc! { "series_id": series_id },
+                FindOptions::builder()
+                    .sort(doc! { "start_date": 1 })
+                    .build(),
+            )
+            .await?;
+
+        restrict_events(self, viewer, events).await
+    }
+
+    async fn fetch_attending_event_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        let guests: Vec<EventGuest> = self
+            .find(
+                GUESTS_COL,
+                doc! {
+                    "user_id": user_id,
+                    "status": String::from(GuestStatus::Approved)
+                },
+            )
+            .await?;
+
+        Ok(guests.into_iter().map(|guest| guest.event_id).collect())
+    }
+
+    async fn fetch_staffed_event_ids(&self, user_id: &str) -> Result<Vec<String>> {
+        let events: Vec<Event> = self
+            .find(
+                COL,
+                doc! {
+                    "$or": [
+                        { "created_by": user_id },
+                        { "hosts": user_id },
+                        { "managers": user_id }
+                    ]
+                },
+            )
+            .await?;
+
+        Ok(events.into_iter().map(|event| event.id).collect())
+    }
+
+    async fn fetch_calendar_events(&self, user_id: &str) -> Result<Vec<Event>> {
+        let saved: Vec<SavedEvent> = self
+            .find(SAVED_EVENTS_COL, doc! { "user_id": user_id })
+            .await?;
+
+        let mut event_ids = self.fetch_attending_event_ids(user_id).await?;
+        event_ids.extend(saved.into_iter().map(|saved| saved.event_id));
+
+        let events = self
+            .find_with_options(
+                COL,
+                doc! {
+                    "$or": [
+                        { "_id": { "$in": event_ids } },
+                        { "created_by": user_id },
+                        { "hosts": user_id }
+                    ]
+                },
+                FindOptions::builder()
+                    .sort(doc! { "start_date": 1 })
+                    .build(),
+            )
+            .await?;
+
+        // Saved events may since have been hidden or turned back into drafts
+        restrict_events(self, EventViewer::User(user_id), events).await
+    }
+
     async fn add_guest(&self, guest: &EventGuest) -> Result<()> {
-        self.insert_one(GUESTS_COL, guest).await.map(|_| ())
+        // Users have one guest entry per event until they cancel, see `event_guest_user`
+        self.col::<EventGuest>(GUESTS_COL)
+            .insert_one(guest, None)
+            .await
+            .map(|_| ())
+            .map_err(|error| match *error.kind {
+                ErrorKind::Write(WriteFailure::WriteError(WriteError { code: 11000, .. })) => {
+                    Error::AlreadyRsvped
+                }
+                _ => Error::DatabaseError {
+                    operation: "insert_one",
+                    with: GUESTS_COL,
+                },
+            })
     }
 
     async fn update_guest_status(
         &self,
         event_id: &str,
         guest_id: &str,
-        status: GuestStatus,
-    ) -> Result<()> {
-        let status_str: String = status.into();
+        from: GuestStatus,
+        to: GuestStatus,
+    ) -> Result<bool> {
+        let from: String = from.into();
+        let to: String = to.into();
+        let result = self
+            .col::<Document>(GUESTS_COL)
+            .update_one(
+                doc! { "_id": guest_id, "event_id": event_id, "status": from },
+                doc! { "$set": { "status": to } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: GUESTS_COL,
+            })?;
+
+        Ok(result.matched_count > 0)
+    }
+
+    async fn take_event_seats(&self, id: &str, seats: i32, capacity: Option<i32>) -> Result<bool> {
+        // Atomically take seats only if they remain within the capacity
+        let mut filter = doc! { "_id": id };
+        if let Some(capacity) = capacity {
+            filter.insert("seats_taken", doc! { "$not": { "$gt": capacity - seats } });
+        }
+
+        let result = self
+            .col::<Document>(COL)
+            .update_one(filter, doc! { "$inc": { "seats_taken": seats } }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: COL,
+            })?;
+
+        Ok(result.matched_count > 0)
+    }
+
+    async fn release_event_seats(&self, id: &str, seats: i32) -> Result<()> {
+        self.col::<Document>(COL)
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$inc": { "seats_taken": -seats } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: COL,
+            })?;
+        Ok(())
+    }
+
+    async fn update_guest_details(&self, guest: &EventGuest) -> Result<()> {
         self.col::<Document>(GUESTS_COL)
             .update_one(
-                doc! { "_id": guest_id, "event_id": event_id },
-                doc! { "$set": { "status": status_str } },
+                doc! { "_id": &guest.id, "event_id": &guest.event_id },
+                doc! {
+                    "$set": {
+                        "name": &guest.name,
+                        "email": &guest.email,
+                        "phone": &guest.phone,
+                        "answers": to_bson(&guest.answers, GUESTS_COL)?
+                    }
+                },
                 None,
             )
             .await
@@ -225,6 +621,36 @@ impl AbstractEvents for MongoDb {
         Ok(())
     }
 
+    async fn insert_guest_status_change(&self, change: &GuestStatusChange) -> Result<()> {
+        self.insert_one(GUEST_HISTORY_COL, change).await.map(|_| ())
+    }
+
+    async fn fetch_guest_status_history(
+        &self,
+        event_id: &str,
+        guest_id: &str,
+    ) -> Result<Vec<GuestStatusChange>> {
+        self.find_with_options(
+            GUEST_HISTORY_COL,
+            doc! { "event_id": event_id, "guest_id": guest_id },
+            FindOptions::builder()
+                .sort(doc! { "changed_at": 1, "_id": 1 })
+                .build(),
+        )
+        .await
+    }
+
+    async fn fetch_event_status_history(&self, event_id: &str) -> Result<Vec<GuestStatusChange>> {
+        self.find_with_options(
+            GUEST_HISTORY_COL,
+            doc! { "event_id": event_id },
+            FindOptions::builder()
+                .sort(doc! { "changed_at": 1, "_id": 1 })
+                .build(),
+        )
+        .await
+    }
+
     async fn get_event_guests(&self, event_id: &str) -> Result<Vec<EventGuest>> {
         self.find(GUESTS_COL, doc! { "event_id": event_id }).await
     }
@@ -233,9 +659,648 @@ impl AbstractEvents for MongoDb {
         self.find_one::<EventGuest>(GUESTS_COL, doc! { "_id": guest_id, "event_id": event_id })
             .await
     }
+
+    async fn check_in_guest(
+        &self,
+        event_id: &str,
+        guest_id: &str,
+        checked_in_by: &str,
+    ) -> Result<()> {
+        let approved: String = GuestStatus::Approved.into();
+        let result = self
+            .col::<Document>(GUESTS_COL)
+            .update_one(
+                doc! {
+                    "_id": guest_id,
+                    "event_id": event_id,
+                    "status": approved,
+                    "checked_in_at": { "$exists": false }
+                },
+                doc! {
+                    "$set": {
+                        "checked_in_at": Timestamp::now_utc().to_string(),
+                        "checked_in_by": checked_in_by
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: GUESTS_COL,
+            })?;
+
+        if result.matched_count == 0 {
+            return Err(Error::AlreadyCheckedIn);
+        }
+
+        Ok(())
+    }
+
+    async fn reserve_ticket(
+        &self,
+        ticket: &Ticket,
+        max_tickets: Option<i32>,
+        single: bool,
+    ) -> Result<()> {
+        let counter = format!("ticket_counts.{}", ticket.tier.key());
+
+        // Atomically take seats only if they remain under the tier limit
+        let mut filter = doc! { "_id": &ticket.event_id };
+        if let Some(max_tickets) = max_tickets {
+            filter.insert(
+                counter.clone(),
+                doc! { "$not": { "$gt": max_tickets - ticket.quantity } },
+            );
+        }
+
+        let result = self
+            .col::<Document>(COL)
+            .update_one(filter, doc! { "$inc": { &counter: ticket.quantity } }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: COL,
+            })?;
+
+        if result.matched_count == 0 {
+            return Err(Error::TicketsSoldOut);
+        }
+
+        let mut document = bson::to_document(ticket).map_err(|_| Error::DatabaseError {
+            operation: "to_document",
+            with: TICKETS_COL,
+        })?;
+
+        // Holders are unique until the ticket is cancelled, see `event_ticket_holder`
+        if single {
+            document.insert("holder", format!("{}:{}", ticket.event_id, ticket.user_id));
+        }
+
+        if let Err(error) = self
+            .col::<Document>(TICKETS_COL)
+            .insert_one(document, None)
+            .await
+        {
+            // Release the seats we just took
+            self.col::<Document>(COL)
+                .update_one(
+                    doc! { "_id": &ticket.event_id },
+                    doc! { "$inc": { &counter: -ticket.quantity } },
+                    None,
+                )
+                .await
+                .ok();
+
+            return Err(match *error.kind {
+                ErrorKind::Write(WriteFailure::WriteError(WriteError { code: 11000, .. })) => {
+                    Error::TooManyTickets { max: 1 }
+                }
+                _ => Error::DatabaseError {
+                    operation: "insert_one",
+                    with: TICKETS_COL,
+                },
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_ticket(&self, event_id: &str, ticket_id: &str) -> Result<Ticket> {
+        self.find_one(TICKETS_COL, doc! { "_id": ticket_id, "event_id": event_id })
+            .await
+    }
+
+    async fn fetch_tickets(&self, event_id: &str) -> Result<Vec<Ticket>> {
+        self.find(TICKETS_COL, doc! { "event_id": event_id }).await
+    }
+
+    async fn fetch_user_tickets(&self, event_id: &str, user_id: &str) -> Result<Vec<Ticket>> {
+        self.find(
+            TICKETS_COL,
+            doc! { "event_id": event_id, "user_id": user_id },
+        )
+        .await
+    }
+
+    async fn confirm_ticket(&self, event_id: &str, ticket_id: &str) -> Result<()> {
+        let result = self
+            .col::<Document>(TICKETS_COL)
+            .update_one(
+                doc! {
+                    "_id": ticket_id,
+                    "event_id": event_id,
+                    "status": to_bson(&TicketStatus::Reserved, TICKETS_COL)?
+                },
+                doc! { "$set": { "status": to_bson(&TicketStatus::Confirmed, TICKETS_COL)? } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: TICKETS_COL,
+            })?;
+
+        if result.matched_count == 0 {
+            return Err(Error::InvalidOperation);
+        }
+
+        Ok(())
+    }
+
+    async fn cancel_ticket(&self, event_id: &str, ticket_id: &str) -> Result<()> {
+        // Only the first cancellation matches, so seats are released exactly once
+        cancel_ticket_matching(
+            self,
+            doc! {
+                "_id": ticket_id,
+                "event_id": event_id,
+                "status": { "$ne": to_bson(&TicketStatus::Cancelled, TICKETS_COL)? }
+            },
+        )
+        .await
+    }
+
+    async fn fetch_expired_reservations(&self, before: DateTime<Utc>) -> Result<Vec<Ticket>> {
+        self.find(
+            TICKETS_COL,
+            doc! {
+                "status": to_bson(&TicketStatus::Reserved, TICKETS_COL)?,
+                "created_at": { "$lt": bson::DateTime::from_chrono(before) }
+            },
+        )
+        .await
+    }
+
+    async fn expire_reservation(&self, event_id: &str, ticket_id: &str) -> Result<()> {
+        // Tickets confirmed in the meantime no longer match
+        cancel_ticket_matching(
+            self,
+            doc! {
+                "_id": ticket_id,
+                "event_id": event_id,
+                "status": to_bson(&TicketStatus::Reserved, TICKETS_COL)?
+            },
+        )
+        .await
+    }
+
+    async fn set_ticket_payment(
+        &self,
+        event_id: &str,
+        ticket_id: &str,
+        payment: &TicketPayment,
+    ) -> Result<()> {
+        self.col::<Document>(TICKETS_COL)
+            .update_one(
+                doc! { "_id": ticket_id, "event_id": event_id },
+                doc! { "$set": { "payment": to_bson(payment, TICKETS_COL)? } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: TICKETS_COL,
+            })?;
+
+        Ok(())
+    }
+
+    async fn fetch_ticket_by_payment(&self, session_id: &str) -> Result<Ticket> {
+        self.find_one(TICKETS_COL, doc! { "payment.session_id": session_id })
+            .await
+    }
+
+    async fn insert_promo_code(&self, promo_code: &PromoCode) -> Result<()> {
+        self.insert_one(PROMO_CODES_COL, promo_code)
+            .await
+            .map(|_| ())
+    }
+
+    async fn fetch_promo_code(&self, event_id: &str, code: &str) -> Result<PromoCode> {
+        self.find_one(PROMO_CODES_COL, doc! { "event_id": event_id, "code": code })
+            .await
+    }
+
+    async fn fetch_promo_codes(&self, event_id: &str) -> Result<Vec<PromoCode>> {
+        self.find(PROMO_CODES_COL, doc! { "event_id": event_id })
+            .await
+    }
+
+    async fn delete_promo_code(&self, event_id: &str, id: &str) -> Result<()> {
+        let result = self
+            .col::<Document>(PROMO_CODES_COL)
+            .delete_one(doc! { "_id": id, "event_id": event_id }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "delete_one",
+                with: PROMO_CODES_COL,
+            })?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn redeem_promo_code(&self, promo_code: &PromoCode) -> Result<()> {
+        // Only count the use if the code has any left
+        let result = self
+            .col::<Document>(PROMO_CODES_COL)
+            .update_one(
+                doc! {
+                    "_id": &promo_code.id,
+                    "$or": [
+                        { "max_uses": null },
+                        { "$expr": { "$lt": [ "$uses", "$max_uses" ] } }
+                    ]
+                },
+                doc! { "$inc": { "uses": 1 } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: PROMO_CODES_COL,
+            })?;
+
+        if result.matched_count == 0 {
+            return Err(Error::PromoCodeUsedUp);
+        }
+
+        Ok(())
+    }
+
+    async fn insert_promo_redemption(&self, redemption: &PromoRedemption) -> Result<()> {
+        self.insert_one(PROMO_REDEMPTIONS_COL, redemption)
+            .await
+            .map(|_| ())
+    }
+
+    async fn fetch_promo_redemptions(&self, event_id: &str) -> Result<Vec<PromoRedemption>> {
+        self.find(PROMO_REDEMPTIONS_COL, doc! { "event_id": event_id })
+            .await
+    }
+
+    async fn release_promo_redemption(&self, event_id: &str, ticket_id: &str) -> Result<()> {
+        // Only the first release finds the redemption, so the use is given back exactly once
+        let redemption = self
+            .col::<PromoRedemption>(PROMO_REDEMPTIONS_COL)
+            .find_one_and_delete(doc! { "event_id": event_id, "ticket_id": ticket_id }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "find_one_and_delete",
+                with: PROMO_REDEMPTIONS_COL,
+            })?;
+
+        if let Some(redemption) = redemption {
+            self.col::<Document>(PROMO_CODES_COL)
+                .update_one(
+                    doc! { "_id": redemption.promo_code_id },
+                    doc! { "$inc": { "uses": -1 } },
+                    None,
+                )
+                .await
+                .map_err(|_| Error::DatabaseError {
+                    operation: "update_one",
+                    with: PROMO_CODES_COL,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_event_template(&self, template: &EventTemplate) -> Result<()> {
+        self.insert_one(TEMPLATES_COL, template).await.map(|_| ())
+    }
+
+    async fn fetch_event_template(&self, user_id: &str, id: &str) -> Result<EventTemplate> {
+        self.find_one(TEMPLATES_COL, doc! { "_id": id, "created_by": user_id })
+            .await
+    }
+
+    async fn fetch_event_templates(&self, user_id: &str) -> Result<Vec<EventTemplate>> {
+        self.find(TEMPLATES_COL, doc! { "created_by": user_id })
+            .await
+    }
+
+    async fn delete_event_template(&self, user_id: &str, id: &str) -> Result<()> {
+        let result = self
+            .col::<Document>(TEMPLATES_COL)
+            .delete_one(doc! { "_id": id, "created_by": user_id }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "delete_one",
+                with: TEMPLATES_COL,
+            })?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn save_event_feedback(&self, feedback: &EventFeedback) -> Result<()> {
+        self.col::<EventFeedback>(FEEDBACK_COL)
+            .replace_one(
+                doc! { "_id": &feedback.id },
+                feedback,
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "replace_one",
+                with: FEEDBACK_COL,
+            })?;
+        Ok(())
+    }
+
+    async fn fetch_event_feedback(&self, event_id: &str, guest_id: &str) -> Result<EventFeedback> {
+        self.find_one(
+            FEEDBACK_COL,
+            doc! { "event_id": event_id, "guest_id": guest_id },
+        )
+        .await
+    }
+
+    async fn fetch_event_feedbacks(&self, event_id: &str) -> Result<Vec<EventFeedback>> {
+        self.find(FEEDBACK_COL, doc! { "event_id": event_id }).await
+    }
+
+    async fn record_event_activity(
+        &self,
+        event_id: &str,
+        date: &str,
+        kind: EventActivityKind,
+    ) -> Result<()> {
+        self.col::<Document>(ACTIVITY_COL)
+            .update_one(
+                doc! { "_id": EventActivityDay::id(event_id, date) },
+                doc! {
+                    "$inc": { kind.counter(): 1_i32 },
+                    "$setOnInsert": {
+                        "event_id": event_id,
+                        "date": date
+                    }
+                },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: ACTIVITY_COL,
+            })?;
+        Ok(())
+    }
+
+    async fn fetch_event_activity(&self, event_id: &str) -> Result<Vec<EventActivityDay>> {
+        self.find(ACTIVITY_COL, doc! { "event_id": event_id }).await
+    }
+
+    async fn count_event_saves(&self, event_id: &str) -> Result<i32> {
+        let count = self
+            .col::<Document>(SAVED_EVENTS_COL)
+            .count_documents(doc! { "event_id": event_id }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "count_documents",
+                with: SAVED_EVENTS_COL,
+            })?;
+
+        Ok(count as i32)
+    }
+
+    async fn fetch_calendar_feed(&self, user_id: &str) -> Result<CalendarFeed> {
+        self.find_one_by_id(CALENDAR_FEEDS_COL, user_id).await
+    }
+
+    async fn fetch_calendar_feed_by_token(&self, token: &str) -> Result<CalendarFeed> {
+        self.find_one(CALENDAR_FEEDS_COL, doc! { "token": token })
+            .await
+    }
+
+    async fn save_calendar_feed(&self, feed: &CalendarFeed) -> Result<()> {
+        self.col::<CalendarFeed>(CALENDAR_FEEDS_COL)
+            .replace_one(
+                doc! { "_id": &feed.id },
+                feed,
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "replace_one",
+                with: CALENDAR_FEEDS_COL,
+            })?;
+        Ok(())
+    }
+
+    async fn delete_calendar_feed(&self, user_id: &str) -> Result<()> {
+        self.delete_one_by_id(CALENDAR_FEEDS_COL, user_id)
+            .await
+            .map(|_| ())
+    }
+
+    async fn replace_event_reminders(
+        &self,
+        event_id: &str,
+        reminders: &[EventReminder],
+    ) -> Result<()> {
+        self.col::<Document>(REMINDERS_COL)
+            .delete_many(doc! { "event_id": event_id }, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "delete_many",
+                with: REMINDERS_COL,
+            })?;
+
+        if !reminders.is_empty() {
+            self.col::<EventReminder>(REMINDERS_COL)
+                .insert_many(reminders, None)
+                .await
+                .map_err(|_| Error::DatabaseError {
+                    operation: "insert_many",
+                    with: REMINDERS_COL,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    async fn claim_due_event_reminder(
+        &self,
+        now: DateTime<Utc>,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<EventReminder>> {
+        self.col::<EventReminder>(REMINDERS_COL)
+            .find_one_and_update(
+                doc! { "send_at": { "$lte": bson::DateTime::from_chrono(now) } },
+                doc! { "$set": { "send_at": bson::DateTime::from_chrono(retry_at) } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "find_one_and_update",
+                with: REMINDERS_COL,
+            })
+    }
+
+    async fn update_event_reminder(&self, reminder: &EventReminder) -> Result<()> {
+        self.col::<EventReminder>(REMINDERS_COL)
+            .replace_one(doc! { "_id": &reminder.id }, reminder, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "replace_one",
+                with: REMINDERS_COL,
+            })?;
+        Ok(())
+    }
+
+    async fn delete_event_reminder(&self, id: &str) -> Result<()> {
+        self.delete_one_by_id(REMINDERS_COL, id).await.map(|_| ())
+    }
+
+    async fn insert_event_message_job(&self, job: &EventMessageJob) -> Result<()> {
+        self.insert_one(MESSAGE_JOBS_COL, job).await.map(|_| ())
+    }
+
+    async fn fetch_event_message_job(&self, event_id: &str, id: &str) -> Result<EventMessageJob> {
+        self.find_one(
+            MESSAGE_JOBS_COL,
+            doc! {
+                "_id": id,
+                "event_id": event_id
+            },
+        )
+        .await
+    }
+
+    async fn claim_due_event_message_job(
+        &self,
+        now: DateTime<Utc>,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<EventMessageJob>> {
+        self.col::<EventMessageJob>(MESSAGE_JOBS_COL)
+            .find_one_and_update(
+                doc! {
+                    "status": { "$ne": to_bson(&MessageJobStatus::Completed, MESSAGE_JOBS_COL)? },
+                    "run_at": { "$lte": bson::DateTime::from_chrono(now) }
+                },
+                doc! {
+                    "$set": {
+                        "status": to_bson(&MessageJobStatus::Running, MESSAGE_JOBS_COL)?,
+                        "run_at": bson::DateTime::from_chrono(retry_at)
+                    }
+                },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "find_one_and_update",
+                with: MESSAGE_JOBS_COL,
+            })
+    }
+
+    async fn update_event_message_job(&self, job: &EventMessageJob) -> Result<()> {
+        self.col::<EventMessageJob>(MESSAGE_JOBS_COL)
+            .replace_one(doc! { "_id": &job.id }, job, None)
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "replace_one",
+                with: MESSAGE_JOBS_COL,
+            })?;
+        Ok(())
+    }
+
+    async fn increment_event_invited_count(&self, id: &str, count: i32) -> Result<()> {
+        self.col::<Document>(COL)
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$inc": { "invited_count": count } },
+                None,
+            )
+            .await
+            .map_err(|_| Error::DatabaseError {
+                operation: "update_one",
+                with: COL,
+            })?;
+        Ok(())
+    }
+
+    async fn fetch_unarchived_event_groups(&self) -> Result<Vec<Channel>> {
+        self.find(
+            CHANNELS_COL,
+            doc! {
+                "channel_type": "Group",
+                "event": { "$exists": true },
+                "archived": { "$ne": true }
+            },
+        )
+        .await
+    }
+
+    async fn fetch_scheduled_events(&self, now: DateTime<Utc>) -> Result<Vec<Event>> {
+        self.find(
+            COL,
+            doc! {
+                "status": to_bson(&EventStatus::Draft, COL)?,
+                "publish_at": { "$lte": bson::DateTime::from_chrono(now) }
+            },
+        )
+        .await
+    }
+
+    async fn fetch_ended_events(&self, now: DateTime<Utc>) -> Result<Vec<Event>> {
+        self.find(
+            COL,
+            doc! {
+                "status": status_filter(Some(&EventStatus::Published))?,
+                "end_date": { "$lte": bson::DateTime::from_chrono(now) }
+            },
+        )
+        .await
+    }
+}
+
+// Helper function to cancel the ticket matching a filter and give its seats back to the tier
+async fn cancel_ticket_matching(db: &MongoDb, filter: Document) -> Result<()> {
+    let ticket = db
+        .col::<Ticket>(TICKETS_COL)
+        .find_one_and_update(
+            filter,
+            doc! {
+                "$set": { "status": to_bson(&TicketStatus::Cancelled, TICKETS_COL)? },
+                "$unset": { "holder": "" }
+            },
+            None,
+        )
+        .await
+        .map_err(|_| Error::DatabaseError {
+            operation: "find_one_and_update",
+            with: TICKETS_COL,
+        })?
+        .ok_or(Error::InvalidOperation)?;
+
+    db.col::<Document>(COL)
+        .update_one(
+            doc! { "_id": &ticket.event_id },
+            doc! {
+                "$inc": {
+                    format!("ticket_counts.{}", ticket.tier.key()): -ticket.quantity
+                }
+            },
+            None,
+        )
+        .await
+        .map_err(|_| Error::DatabaseError {
+            operation: "update_one",
+            with: COL,
+        })?;
+
+    Ok(())
 }
 
-// Helper function to fetch user details
 async fn fetch_user_details(db: &MongoDb, user_ids: &[String]) -> Result<Vec<EventHost>> {
     let mut hosts = Vec::new();
 
@@ -251,3 +1316,38 @@ async fn fetch_user_details(db: &MongoDb, user_ids: &[String]) -> Result<Vec<Eve
 
     Ok(hosts)
 }
+
+// Helper function to restrict a list of events to what the viewer may see
+async fn restrict_events(
+    db: &MongoDb,
+    viewer: EventViewer<'_>,
+    events: Vec<Event>,
+) -> Result<Vec<Event>> {
+    Ok(match EventAccess::resolve(db, viewer).await? {
+        Some(access) => events
+            .into_iter()
+            .filter_map(|event| access.restrict(event))
+            .collect(),
+        None => events,
+    })
+}
+
+// Helper function to filter events by status, leaving out drafts unless asked for
+fn status_filter(status: Option<&EventStatus>) -> Result<Bson> {
+    Ok(match status {
+        // Events from before statuses existed count as published
+        Some(EventStatus::Published) => {
+            Bson::Document(doc! { "$in": [to_bson(&EventStatus::Published, COL)?, Bson::Null] })
+        }
+        Some(status) => to_bson(status, COL)?,
+        None => Bson::Document(doc! { "$ne": to_bson(&EventStatus::Draft, COL)? }),
+    })
+}
+
+// Helper function to serialise an enum for use in a query
+fn to_bson<T: serde::Serialize>(value: &T, with: &'static str) -> Result<bson::Bson> {
+    bson::to_bson(value).map_err(|_| Error::DatabaseError {
+        operation: "to_bson",
+        with,
+    })
+}
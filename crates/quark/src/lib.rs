@@ -22,6 +22,7 @@ pub use redis_kiss;
 pub mod events;
 pub mod r#impl;
 pub mod models;
+pub mod payments;
 pub mod tasks;
 pub mod types;
 pub mod util;
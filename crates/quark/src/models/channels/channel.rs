@@ -70,6 +70,13 @@ pub enum Channel {
         hide_title: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         password: Option<String>,
+
+        /// Id of the event this group was created for
+        #[serde(skip_serializing_if = "Option::is_none")]
+        event: Option<String>,
+        /// Whether this group is archived and can no longer be posted in
+        #[serde(skip_serializing_if = "if_false", default)]
+        archived: bool,
     },
     /// Text channel belonging to a server
     TextChannel {
@@ -206,6 +213,8 @@ pub struct PartialChannel {
     pub last_message_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
 }
 
 /// Optional fields on channel object
@@ -0,0 +1,395 @@
+use crate::models::event::Event;
+use crate::models::events::guest::{EventGuest, GuestStatus, GuestStatusChange};
+use crate::models::events::ticket::{Ticket, TicketStatus};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Kind of engagement recorded against an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum EventActivityKind {
+    /// Someone outside of the staff viewed the event
+    View,
+    /// Someone saved the event
+    Save,
+}
+
+impl EventActivityKind {
+    /// Counter the activity is added to
+    pub fn counter(&self) -> &'static str {
+        match self {
+            EventActivityKind::View => "views",
+            EventActivityKind::Save => "saves",
+        }
+    }
+}
+
+/// Engagement counted against an event on a single day, used for its analytics
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct EventActivityDay {
+    /// Id made up of the event and the day
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Event the activity happened on
+    pub event_id: String,
+    /// Day (YYYY-MM-DD), in the event's timezone at the time
+    pub date: String,
+    /// Views by anyone outside of the staff
+    #[serde(default)]
+    pub views: i32,
+    /// Times the event was saved
+    #[serde(default)]
+    pub saves: i32,
+}
+
+impl EventActivityDay {
+    /// Id of an event's counters for a given day
+    pub fn id(event_id: &str, date: &str) -> String {
+        format!("{}:{}", event_id, date)
+    }
+}
+
+/// Every step from seeing an event to turning up at it
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFunnel {
+    /// Views by anyone outside of the staff
+    pub views: i32,
+    /// Users who currently have the event saved
+    pub saves: i32,
+    /// Guests invited through bulk messages
+    pub invitations: i32,
+    /// Guests on the list, not counting plus-ones
+    pub rsvps: i32,
+    /// Guests currently approved, not counting plus-ones
+    pub approvals: i32,
+    /// Tickets reserved or bought, not counting cancelled ones
+    pub tickets: i32,
+    /// Guests checked in at the door
+    pub check_ins: i32,
+}
+
+/// Engagement on a single day, in the event's timezone
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyticsDay {
+    /// Day (YYYY-MM-DD)
+    pub date: String,
+    /// Views by anyone outside of the staff
+    pub views: i32,
+    /// Times the event was saved
+    pub saves: i32,
+    /// Guests added to the list, not counting plus-ones
+    pub rsvps: i32,
+    /// Guests moved to approved
+    pub approvals: i32,
+    /// Tickets reserved or bought, not counting cancelled ones
+    pub tickets: i32,
+    /// Guests checked in at the door
+    pub check_ins: i32,
+}
+
+/// Engagement analytics of an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventAnalytics {
+    /// Totals for each step of the funnel
+    pub funnel: EventFunnel,
+    /// Engagement per day, earliest first, leaving out days without any
+    ///
+    /// Invitations are only counted in the funnel.
+    pub days: Vec<AnalyticsDay>,
+}
+
+impl EventAnalytics {
+    /// Work out an event's analytics from everything recorded about it
+    pub fn build(
+        event: &Event,
+        activity: &[EventActivityDay],
+        saves: i32,
+        guests: &[EventGuest],
+        history: &[GuestStatusChange],
+        tickets: &[Ticket],
+    ) -> EventAnalytics {
+        let tz = event.tz();
+        let mut funnel = EventFunnel {
+            saves,
+            invitations: event.invited_count.unwrap_or(0),
+            ..Default::default()
+        };
+
+        let mut days: BTreeMap<NaiveDate, AnalyticsDay> = BTreeMap::new();
+
+        for entry in activity {
+            funnel.views += entry.views;
+            if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
+                let day = days.entry(date).or_default();
+                day.views += entry.views;
+                day.saves += entry.saves;
+            }
+        }
+
+        for guest in guests {
+            if guest.plus_one_of.is_none() {
+                funnel.rsvps += 1;
+                if guest.status == GuestStatus::Approved {
+                    funnel.approvals += 1;
+                }
+
+                if let Some(date) = parse_date(&guest.created_at) {
+                    day(&mut days, date, &tz).rsvps += 1;
+                }
+            }
+
+            if let Some(checked_in_at) = &guest.checked_in_at {
+                funnel.check_ins += 1;
+                if let Some(date) = parse_date(&checked_in_at.to_string()) {
+                    day(&mut days, date, &tz).check_ins += 1;
+                }
+            }
+        }
+
+        for change in history {
+            if change.to == GuestStatus::Approved {
+                day(&mut days, change.changed_at, &tz).approvals += 1;
+            }
+        }
+
+        for ticket in tickets {
+            if ticket.status != TicketStatus::Cancelled {
+                funnel.tickets += 1;
+                day(&mut days, ticket.created_at, &tz).tickets += 1;
+            }
+        }
+
+        EventAnalytics {
+            funnel,
+            days: days
+                .into_iter()
+                .map(|(date, entry)| AnalyticsDay {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    ..entry
+                })
+                .collect(),
+        }
+    }
+}
+
+// Helper function to find the entry of the day a moment falls on in the event's timezone
+fn day<'a>(
+    days: &'a mut BTreeMap<NaiveDate, AnalyticsDay>,
+    date: DateTime<Utc>,
+    tz: &Tz,
+) -> &'a mut AnalyticsDay {
+    days.entry(date.with_timezone(tz).naive_local().date())
+        .or_default()
+}
+
+// Helper function to read the RFC 3339 dates guests are stored with
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::event::{EventStatus, TicketConfig};
+    use crate::models::events::ticket::TicketTier;
+    use iso8601_timestamp::Timestamp;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn event() -> Event {
+        Event {
+            id: "event".into(),
+            created_by: Some("host".into()),
+            title: "Event".into(),
+            event_type: None,
+            start_date: at("2026-10-12T16:00:00Z"),
+            end_date: at("2026-10-12T20:00:00Z"),
+            timezone: "Africa/Nairobi".into(),
+            city: "Nairobi".into(),
+            country: None,
+            hide_address: false,
+            area: "Westlands".into(),
+            address: "1 Example Road".into(),
+            location: None,
+            description: None,
+            allow_plus_one: true,
+            allow_plus_one_amount: None,
+            requires_plus_one_info: false,
+            requires_rsvp_approval: true,
+            show_to_non_members: true,
+            disable_reminders: false,
+            public_ratings: false,
+            channel_id: None,
+            capacity: None,
+            hosts: vec![],
+            host_details: None,
+            managers: vec![],
+            staff_permissions: Default::default(),
+            sponsors: vec![],
+            sponsor_details: None,
+            questions: vec![],
+            ticket_config: TicketConfig::default(),
+            ticket_counts: Default::default(),
+            seats_taken: 0,
+            currency: None,
+            payment_type: None,
+            attachments: vec![],
+            gallery: vec![],
+            is_saved: None,
+            created_at: at("2026-10-01T09:00:00Z"),
+            series_id: None,
+            sequence: 0,
+            status: EventStatus::Published,
+            publish_at: None,
+            guests: None,
+            guest_stats: None,
+            distance: None,
+            thumbnail: None,
+            invited_count: Some(5),
+        }
+    }
+
+    fn activity(date: &str, views: i32, saves: i32) -> EventActivityDay {
+        EventActivityDay {
+            id: EventActivityDay::id("event", date),
+            event_id: "event".into(),
+            date: date.into(),
+            views,
+            saves,
+        }
+    }
+
+    fn guest(id: &str, status: GuestStatus, created_at: &str) -> EventGuest {
+        EventGuest {
+            id: id.into(),
+            event_id: "event".into(),
+            parent_guest_id: None,
+            plus_one_of: None,
+            user_id: None,
+            name: id.into(),
+            email: format!("{id}@example.com"),
+            phone: String::new(),
+            answers: Default::default(),
+            status,
+            is_plus_one: None,
+            created_at: created_at.into(),
+            checked_in_at: None,
+            checked_in_by: None,
+            checkin_pass: None,
+        }
+    }
+
+    fn ticket(status: TicketStatus, created_at: &str) -> Ticket {
+        Ticket {
+            id: created_at.into(),
+            event_id: "event".into(),
+            user_id: "buyer".into(),
+            tier: TicketTier::Member,
+            quantity: 2,
+            price: None,
+            currency: None,
+            promo_code: None,
+            status,
+            payment: None,
+            created_at: at(created_at),
+        }
+    }
+
+    #[test]
+    fn analytics_count_each_step_per_day_in_the_event_timezone() {
+        let mut approved = guest("approved", GuestStatus::Approved, "2026-10-10T08:00:00Z");
+        approved.checked_in_at = Timestamp::parse("2026-10-12T18:00:00Z");
+
+        let mut plus_one = guest("plus_one", GuestStatus::Approved, "2026-10-10T08:00:00Z");
+        plus_one.plus_one_of = Some("approved".into());
+
+        let guests = vec![
+            approved,
+            plus_one,
+            guest("pending", GuestStatus::Pending, "2026-10-11T09:00:00Z"),
+        ];
+
+        let history = vec![GuestStatusChange {
+            id: "change".into(),
+            event_id: "event".into(),
+            guest_id: "approved".into(),
+            from: GuestStatus::Pending,
+            to: GuestStatus::Approved,
+            changed_by: "host".into(),
+            // Already the next day in Nairobi
+            changed_at: at("2026-10-10T21:30:00Z"),
+        }];
+
+        let tickets = vec![
+            ticket(TicketStatus::Confirmed, "2026-10-10T22:30:00Z"),
+            ticket(TicketStatus::Cancelled, "2026-10-10T23:00:00Z"),
+        ];
+
+        let analytics = EventAnalytics::build(
+            &event(),
+            &[activity("2026-10-10", 10, 2), activity("2026-10-11", 4, 0)],
+            1,
+            &guests,
+            &history,
+            &tickets,
+        );
+
+        assert_eq!(
+            analytics.funnel,
+            EventFunnel {
+                views: 14,
+                saves: 1,
+                invitations: 5,
+                rsvps: 2,
+                approvals: 1,
+                tickets: 1,
+                check_ins: 1,
+            }
+        );
+
+        assert_eq!(
+            analytics.days,
+            vec![
+                AnalyticsDay {
+                    date: "2026-10-10".into(),
+                    views: 10,
+                    saves: 2,
+                    rsvps: 1,
+                    ..Default::default()
+                },
+                AnalyticsDay {
+                    date: "2026-10-11".into(),
+                    views: 4,
+                    rsvps: 1,
+                    approvals: 1,
+                    tickets: 1,
+                    ..Default::default()
+                },
+                AnalyticsDay {
+                    date: "2026-10-12".into(),
+                    check_ins: 1,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn analytics_without_engagement_are_empty() {
+        let analytics = EventAnalytics::build(&event(), &[], 0, &[], &[], &[]);
+
+        assert_eq!(
+            analytics.funnel,
+            EventFunnel {
+                invitations: 5,
+                ..Default::default()
+            }
+        );
+        assert!(analytics.days.is_empty());
+    }
+}
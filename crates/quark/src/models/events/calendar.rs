@@ -0,0 +1,248 @@
+use crate::models::event::{Event, EventStatus};
+use crate::variables::delta::APP_URL;
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+
+/// Format of UTC date-times in iCalendar documents
+static ICAL_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Maximum length of a content line in octets before it has to be folded
+const ICAL_LINE_LENGTH: usize = 75;
+
+/// Subscribable calendar feed of a user's events
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CalendarFeed {
+    /// Id of the user owning the feed
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// Secret token used in the feed URL
+    pub token: String,
+
+    /// When the token was issued
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Event {
+    /// Render this event as an iCalendar VEVENT component
+    pub fn to_ical(&self, show_address: bool) -> String {
+        let url = format!("{}/events/view/{}", *APP_URL, self.id);
+
+        let location = [
+            show_address.then(|| self.address.as_str()),
+            Some(self.area.as_str()),
+            Some(self.city.as_str()),
+            self.country.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(", ");
+
+        let description = match &self.description {
+            Some(description) if !description.is_empty() => format!("{}\n\n{}", description, url),
+            _ => url.clone(),
+        };
+
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@{}", self.id, ical_domain()),
+            format!("DTSTAMP:{}", Utc::now().format(ICAL_DATE_FORMAT)),
+            format!("CREATED:{}", self.created_at.format(ICAL_DATE_FORMAT)),
+            format!("DTSTART:{}", self.start_date.format(ICAL_DATE_FORMAT)),
+            format!("DTEND:{}", self.end_date.format(ICAL_DATE_FORMAT)),
+            format!("SEQUENCE:{}", self.sequence),
+            format!(
+                "STATUS:{}",
+                match self.status {
+                    EventStatus::Draft => "TENTATIVE",
+                    EventStatus::Cancelled => "CANCELLED",
+                    EventStatus::Published | EventStatus::Completed => "CONFIRMED",
+                }
+            ),
+            format!("SUMMARY:{}", escape_text(&self.title)),
+            format!("DESCRIPTION:{}", escape_text(&description)),
+            format!("URL:{}", url),
+        ];
+
+        if !location.is_empty() {
+            lines.push(format!("LOCATION:{}", escape_text(&location)));
+        }
+
+        lines.push("END:VEVENT".to_string());
+        fold_lines(lines)
+    }
+}
+
+/// Render VEVENT components into an iCalendar document
+pub fn render_calendar(name: &str, events: impl IntoIterator<Item = String>) -> String {
+    let mut calendar = fold_lines(vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Kimani//Events//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ]);
+
+    for event in events {
+        calendar.push_str(&event);
+    }
+
+    calendar.push_str("END:VCALENDAR\r\n");
+    calendar
+}
+
+/// Domain used to make event UIDs globally unique
+fn ical_domain() -> &'static str {
+    APP_URL
+        .split("://")
+        .last()
+        .and_then(|url| url.split('/').next())
+        .unwrap_or_default()
+}
+
+/// Escape a value of the TEXT type
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold content lines longer than 75 octets and terminate each with CRLF
+fn fold_lines(lines: Vec<String>) -> String {
+    let mut folded = String::new();
+    for line in lines {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > ICAL_LINE_LENGTH {
+                folded.push_str("\r\n ");
+                width = 1;
+            }
+
+            folded.push(c);
+            width += c.len_utf8();
+        }
+
+        folded.push_str("\r\n");
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::event::TicketConfig;
+
+    fn event() -> Event {
+        Event {
+            id: "event".into(),
+            created_by: Some("host".into()),
+            title: "Event".into(),
+            event_type: None,
+            start_date: "2026-10-12T16:00:00Z".parse().unwrap(),
+            end_date: "2026-10-12T20:00:00Z".parse().unwrap(),
+            timezone: "Africa/Nairobi".into(),
+            city: "Nairobi".into(),
+            country: None,
+            hide_address: false,
+            area: "Westlands".into(),
+            address: "1 Example Road".into(),
+            location: None,
+            description: None,
+            allow_plus_one: false,
+            allow_plus_one_amount: None,
+            requires_plus_one_info: false,
+            requires_rsvp_approval: false,
+            show_to_non_members: true,
+            disable_reminders: false,
+            public_ratings: false,
+            channel_id: None,
+            capacity: None,
+            hosts: vec![],
+            host_details: None,
+            managers: vec![],
+            staff_permissions: Default::default(),
+            sponsors: vec![],
+            sponsor_details: None,
+            questions: vec![],
+            ticket_config: TicketConfig::default(),
+            ticket_counts: Default::default(),
+            seats_taken: 0,
+            currency: None,
+            payment_type: None,
+            attachments: vec![],
+            gallery: vec![],
+            is_saved: None,
+            created_at: "2026-10-01T09:00:00Z".parse().unwrap(),
+            series_id: None,
+            sequence: 0,
+            status: EventStatus::Published,
+            publish_at: None,
+            guests: None,
+            guest_stats: None,
+            distance: None,
+            thumbnail: None,
+            invited_count: None,
+        }
+    }
+
+    #[test]
+    fn long_lines_are_folded_at_75_octets() {
+        let folded = fold_lines(vec!["a".repeat(160)]);
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines.concat().replace(' ', ""), "a".repeat(160));
+    }
+
+    #[test]
+    fn multibyte_characters_are_not_split_when_folding() {
+        let folded = fold_lines(vec!["é".repeat(50)]);
+
+        for line in folded.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+
+        assert_eq!(
+            folded.replace("\r\n ", ""),
+            format!("{}\r\n", "é".repeat(50))
+        );
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(escape_text("a,b;c\\d\ne\r\nf"), r"a\,b\;c\\d\ne\nf");
+    }
+
+    #[test]
+    fn cancelled_events_keep_their_sequence() {
+        std::env::set_var("REVOLT_APP_URL", "https://app.example.com");
+
+        let mut event = event();
+        event.status = EventStatus::Cancelled;
+        event.sequence = 3;
+        event.title = "Dinner, drinks; more".into();
+
+        let ical = event.to_ical(true);
+        let lines: Vec<&str> = ical.split("\r\n").collect();
+
+        assert!(lines.contains(&"STATUS:CANCELLED"));
+        assert!(lines.contains(&"SEQUENCE:3"));
+        assert!(lines.contains(&r"SUMMARY:Dinner\, drinks\; more"));
+        assert!(lines.contains(&"UID:event@app.example.com"));
+        assert!(lines.contains(&"DTSTART:20261012T160000Z"));
+    }
+}
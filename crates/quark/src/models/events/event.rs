@@ -1,6 +1,21 @@
 use crate::models::attachment::File;
-use crate::models::events::guest::EventGuest;
+use crate::models::events::feedback::FEEDBACK_WINDOW_DAYS;
+use crate::models::events::guest::{EventGuest, GuestStatus};
+use crate::models::events::questionnaire::RsvpQuestion;
+use crate::models::events::ticket::TicketCounts;
+use crate::{
+    Error, EventPermission, Result, ALL_EVENT_PERMISSIONS, DEFAULT_EVENT_PERMISSION_HOST,
+    DEFAULT_EVENT_PERMISSION_MANAGER,
+};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use revolt_database::util::iso_bson_chrono;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ulid::Ulid;
+
+#[cfg(feature = "rocket_impl")]
+use rocket::FromFormField;
 
 pub fn if_false(t: &bool) -> bool {
     !t
@@ -20,9 +35,10 @@ pub struct EventGuestStats {
     pub total_going: i32,
     pub total_pending: i32,
     pub total_rejected: i32,
+    pub total_waitlisted: i32,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, OptionalStruct, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, OptionalStruct)]
 #[optional_derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone)]
 #[optional_name = "PartialEvent"]
 #[opt_skip_serializing_none]
@@ -44,14 +60,22 @@ pub struct Event {
     pub event_type: Option<EventType>,
 
     /// Start date and time
-    pub start_date: String,
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub start_date: DateTime<Utc>,
 
     /// End date and time
-    pub end_date: String,
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub end_date: DateTime<Utc>,
 
-    /// Timezone for the event
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timezone: Option<String>,
+    /// IANA timezone the event takes place in (e.g. "Africa/Nairobi")
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
 
     /// City where event is held
     pub city: String,
@@ -68,8 +92,16 @@ pub struct Event {
     pub area: String,
 
     /// Full address
+    ///
+    /// Left empty for anyone outside of the staff and approved guests if the address is hidden.
     pub address: String,
 
+    /// Coordinates of the venue
+    ///
+    /// Rounded for anyone outside of the staff and approved guests if the address is hidden.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+
     /// Event description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
@@ -94,6 +126,22 @@ pub struct Event {
     #[serde(skip_serializing_if = "if_false", default)]
     pub show_to_non_members: bool,
 
+    /// Don't send reminders to guests ahead of the event
+    #[serde(skip_serializing_if = "if_false", default)]
+    pub disable_reminders: bool,
+
+    /// Show the aggregated guest ratings to anyone who can see the event
+    #[serde(skip_serializing_if = "if_false", default)]
+    pub public_ratings: bool,
+
+    /// Group channel where approved guests can talk to each other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+
+    /// Maximum number of guests (including plus-ones) that can attend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+
     /// Event hosts with their details
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub hosts: Vec<String>,
@@ -102,6 +150,16 @@ pub struct Event {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub host_details: Option<Vec<EventHost>>,
 
+    /// Event managers (user IDs)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub managers: Vec<String>,
+
+    /// Permissions the creator assigned to hosts and managers, by user id
+    ///
+    /// Staff without an entry hold the default permissions of their role.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub staff_permissions: HashMap<String, u32>,
+
     /// Event sponsors with their details
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub sponsors: Vec<String>,
@@ -110,9 +168,21 @@ pub struct Event {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sponsor_details: Option<Vec<EventHost>>,
 
+    /// Questions guests are asked when they RSVP
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub questions: Vec<RsvpQuestion>,
+
     /// Ticket configuration
     pub ticket_config: TicketConfig,
 
+    /// Seats held by active tickets in each tier
+    #[serde(default)]
+    pub ticket_counts: TicketCounts,
+
+    /// Seats taken by approved guests and their plus-ones
+    #[serde(default)]
+    pub seats_taken: i32,
+
     /// Currency type (e.g. "USD", "EUR")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub currency: Option<String>,
@@ -134,7 +204,32 @@ pub struct Event {
     pub is_saved: Option<bool>,
 
     /// Creation timestamp
-    pub created_at: String,
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+
+    /// Series this event is an occurrence of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<String>,
+
+    /// Revision of the event, bumped every time its details are edited
+    #[serde(default)]
+    pub sequence: i32,
+
+    /// Lifecycle status of the event
+    #[serde(default)]
+    pub status: EventStatus,
+
+    /// When the draft is due to be published
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "iso_bson_chrono::serialize_optional",
+        deserialize_with = "iso_bson_chrono::deserialize_optional"
+    )]
+    pub publish_at: Option<DateTime<Utc>>,
 
     /// List of guests
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -144,6 +239,10 @@ pub struct Event {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub guest_stats: Option<EventGuestStats>,
 
+    /// Distance in metres from the point events were searched near
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
+
     /// Thumbnail image ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail: Option<String>,
@@ -153,13 +252,271 @@ pub struct Event {
     pub invited_count: Option<i32>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+/// Timezone used for events which do not specify one
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+impl Event {
+    /// Timezone the event takes place in
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    /// Check whether a user created or hosts this event
+    pub fn is_host(&self, user_id: &str) -> bool {
+        self.created_by.as_deref() == Some(user_id) || self.hosts.iter().any(|id| id == user_id)
+    }
+
+    /// Check whether a user is part of this event's staff (creator, hosts or managers)
+    pub fn is_staff(&self, user_id: &str) -> bool {
+        self.is_host(user_id) || self.managers.iter().any(|id| id == user_id)
+    }
+
+    /// Calculate the event permissions a user holds
+    ///
+    /// The creator holds every permission, hosts and managers hold what the
+    /// creator assigned them or their role's defaults, anyone else holds none.
+    pub fn permissions_for(&self, user_id: &str) -> u32 {
+        if self.created_by.as_deref() == Some(user_id) {
+            return *ALL_EVENT_PERMISSIONS;
+        }
+
+        let default = if self.hosts.iter().any(|id| id == user_id) {
+            *DEFAULT_EVENT_PERMISSION_HOST
+        } else if self.managers.iter().any(|id| id == user_id) {
+            *DEFAULT_EVENT_PERMISSION_MANAGER
+        } else {
+            return 0;
+        };
+
+        self.staff_permissions
+            .get(user_id)
+            .copied()
+            .unwrap_or(default)
+            & *ALL_EVENT_PERMISSIONS
+    }
+
+    /// Check whether a user holds a given permission on this event
+    pub fn has_permission(&self, user_id: &str, permission: EventPermission) -> bool {
+        self.permissions_for(user_id) & permission as u32 == permission as u32
+    }
+
+    /// Throw an error if a user does not hold a given permission on this event
+    ///
+    /// Users outside of the event's staff are told the event does not exist.
+    pub fn throw_permission(&self, user_id: &str, permission: EventPermission) -> Result<()> {
+        if self.has_permission(user_id, permission) {
+            Ok(())
+        } else if self.is_staff(user_id) {
+            Err(Error::MissingEventPermission { permission })
+        } else {
+            Err(Error::NotFound)
+        }
+    }
+
+    /// Throw an error if a user did not create this event
+    pub fn throw_owner(&self, user_id: &str) -> Result<()> {
+        if self.created_by.as_deref() == Some(user_id) {
+            Ok(())
+        } else if self.is_staff(user_id) {
+            Err(Error::NotOwner)
+        } else {
+            Err(Error::NotFound)
+        }
+    }
+
+    /// Throw an error unless the event is published and open to guests
+    pub fn check_open(&self) -> Result<()> {
+        if self.status == EventStatus::Published {
+            Ok(())
+        } else {
+            Err(Error::EventNotOpen)
+        }
+    }
+
+    /// Throw an error unless guests can currently leave feedback
+    ///
+    /// Feedback opens once the event has ended and stays open for
+    /// `FEEDBACK_WINDOW_DAYS`, cancelled events never take any.
+    pub fn check_feedback_open(&self) -> Result<()> {
+        let now = Utc::now();
+        if self.status != EventStatus::Cancelled
+            && now >= self.end_date
+            && now < self.end_date + Duration::days(FEEDBACK_WINDOW_DAYS)
+        {
+            Ok(())
+        } else {
+            Err(Error::FeedbackNotOpen)
+        }
+    }
+
+    /// Remove the address and round the coordinates so only the rough area is given away
+    pub fn redact_address(&mut self) {
+        self.address = String::new();
+        self.location = self.location.as_ref().map(GeoPoint::rounded);
+    }
+
+    /// Point the event is found by in location searches
+    ///
+    /// Hidden addresses are searched by their rounded coordinates, so search
+    /// results cannot narrow the venue down any further than the rounding does.
+    pub fn search_location(&self) -> Option<GeoPoint> {
+        if self.hide_address {
+            self.location.as_ref().map(GeoPoint::rounded)
+        } else {
+            self.location.clone()
+        }
+    }
+
+    /// Ids of everyone on this event's staff, without duplicates
+    pub fn staff_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = vec![];
+        for id in self
+            .created_by
+            .iter()
+            .chain(self.hosts.iter())
+            .chain(self.managers.iter())
+        {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+
+        ids
+    }
+
+    /// Copy this event to new dates
+    ///
+    /// Guests, tickets sold, invitations and anything specific to the
+    /// original (its channel, series and schedule) are left behind.
+    pub fn duplicate(&self, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Event {
+        Event {
+            id: Ulid::new().to_string(),
+            start_date,
+            end_date,
+            channel_id: None,
+            host_details: None,
+            sponsor_details: None,
+            ticket_counts: Default::default(),
+            seats_taken: 0,
+            is_saved: None,
+            created_at: Utc::now(),
+            series_id: None,
+            sequence: 0,
+            status: EventStatus::Published,
+            publish_at: None,
+            guests: None,
+            guest_stats: None,
+            distance: None,
+            invited_count: None,
+            ..self.clone()
+        }
+    }
+
+    /// Check whether the given number of seats is still free
+    ///
+    /// Only a hint, seats are taken atomically when guests are approved.
+    pub fn has_room(&self, seats: i32) -> bool {
+        self.capacity
+            .map_or(true, |capacity| self.seats_taken + seats <= capacity)
+    }
+
+    /// Pick the status for a party asking for the given number of seats
+    pub fn admission_status(&self, seats: i32) -> GuestStatus {
+        if !self.has_room(seats) {
+            GuestStatus::Waitlisted
+        } else if self.requires_rsvp_approval {
+            GuestStatus::Pending
+        } else {
+            GuestStatus::Approved
+        }
+    }
+}
+
+/// Parse an IANA timezone name
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone.parse().map_err(|_| Error::InvalidRequest {
+        code: "invalid_timezone".to_string(),
+        errors: vec![format!("timezone: unknown timezone {timezone}")],
+    })
+}
+
+/// Parse a date supplied by a client
+///
+/// Dates with an explicit offset are taken as-is, dates without
+/// one are treated as local time in the given timezone.
+pub fn parse_event_date(field: &str, value: &str, timezone: &Tz) -> Result<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .and_then(|naive| timezone.from_local_datetime(&naive).earliest())
+    .map(|date| date.with_timezone(&Utc))
+    .ok_or_else(|| Error::InvalidRequest {
+        code: "invalid_date".to_string(),
+        errors: vec![format!("{field}: expected an ISO 8601 date")],
+    })
+}
+
+/// Ensure an event ends after it starts
+pub fn validate_event_dates(start_date: &DateTime<Utc>, end_date: &DateTime<Utc>) -> Result<()> {
+    if end_date <= start_date {
+        return Err(Error::InvalidRequest {
+            code: "invalid_date_range".to_string(),
+            errors: vec!["end_date: must be after start_date".to_string()],
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "rocket_impl", derive(FromFormField))]
 pub enum EventType {
     KimaniEvent,
     MembersEvent,
     Other,
 }
 
+/// Lifecycle status of an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "rocket_impl", derive(FromFormField))]
+pub enum EventStatus {
+    /// Only visible to staff until it is published
+    Draft,
+    /// Listed and open to guests
+    #[default]
+    Published,
+    /// Called off, kept so guests can see what happened
+    Cancelled,
+    /// Over, set automatically once the event has ended
+    Completed,
+}
+
+impl EventStatus {
+    /// Check whether staff may move an event from this status to another
+    ///
+    /// Events are completed automatically, so staff can never move them there.
+    pub fn can_transition_to(&self, status: &EventStatus) -> bool {
+        matches!(
+            (self, status),
+            (
+                EventStatus::Draft,
+                EventStatus::Published | EventStatus::Cancelled
+            ) | (EventStatus::Published, EventStatus::Cancelled)
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct TicketConfig {
     /// Type of ticket (free or paid)
@@ -187,3 +544,227 @@ pub struct TicketConfig {
     /// Processing fee percentage
     pub processing_fee_percentage: Option<String>,
 }
+
+/// # Event Viewer
+///
+/// Who events are being fetched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventViewer<'a> {
+    /// Internal lookups, which see every event in full
+    System,
+    /// Someone who is not logged in
+    Anonymous,
+    /// A logged in user
+    User(&'a str),
+}
+
+impl<'a> EventViewer<'a> {
+    /// Id of the user viewing, if any
+    pub fn user_id(&self) -> Option<&'a str> {
+        match self {
+            EventViewer::User(user_id) => Some(user_id),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> From<Option<&'a str>> for EventViewer<'a> {
+    fn from(user_id: Option<&'a str>) -> Self {
+        user_id.map_or(EventViewer::Anonymous, EventViewer::User)
+    }
+}
+
+/// # Event Access
+///
+/// What a viewer other than the system is allowed to see
+#[derive(Debug, Clone, Default)]
+pub struct EventAccess {
+    /// Id of the user viewing, if logged in
+    pub user_id: Option<String>,
+    /// Whether the user is a member of the community server
+    pub is_member: bool,
+    /// Ids of events the user is an approved guest of
+    pub attending: Vec<String>,
+}
+
+impl EventAccess {
+    /// Check whether the viewer is on an event's staff
+    pub fn is_staff(&self, event: &Event) -> bool {
+        self.user_id
+            .as_deref()
+            .map_or(false, |user_id| event.is_staff(user_id))
+    }
+
+    /// Check whether the viewer is an approved guest of an event
+    pub fn is_attending(&self, event: &Event) -> bool {
+        self.attending.contains(&event.id)
+    }
+
+    /// Check whether the viewer may see an event at all
+    ///
+    /// Drafts are only shown to staff. Non-members only see events shown to
+    /// non-members or which they were approved for, and never see members' events
+    /// unless they were approved for them.
+    pub fn can_view(&self, event: &Event) -> bool {
+        if self.is_staff(event) {
+            return true;
+        }
+
+        if event.status == EventStatus::Draft {
+            return false;
+        }
+
+        self.is_member
+            || self.is_attending(event)
+            || (event.show_to_non_members
+                && !matches!(event.event_type, Some(EventType::MembersEvent)))
+    }
+
+    /// Check whether the viewer may see an event's address
+    pub fn can_view_address(&self, event: &Event) -> bool {
+        !event.hide_address || self.is_staff(event) || self.is_attending(event)
+    }
+
+    /// Restrict an event to what the viewer may see, `None` if they may not see it at all
+    pub fn restrict(&self, mut event: Event) -> Option<Event> {
+        if !self.can_view(&event) {
+            return None;
+        }
+
+        if !self.can_view_address(&event) {
+            event.redact_address();
+        }
+
+        Some(event)
+    }
+}
+
+/// Mean radius of the Earth in metres, as used by MongoDB for spherical queries
+pub const EARTH_RADIUS: f64 = 6_378_100.0;
+
+/// Kind of GeoJSON geometry
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq, Default)]
+pub enum GeoPointType {
+    #[default]
+    Point,
+}
+
+/// # Geographic Point
+///
+/// Stored as a GeoJSON point so it can be indexed
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct GeoPoint {
+    /// Geometry type, always `Point`
+    #[serde(rename = "type", default)]
+    pub kind: GeoPointType,
+    /// Longitude and latitude in degrees, in that order
+    pub coordinates: [f64; 2],
+}
+
+impl GeoPoint {
+    /// Create a point from its latitude and longitude
+    pub fn new(latitude: f64, longitude: f64) -> GeoPoint {
+        GeoPoint {
+            kind: GeoPointType::Point,
+            coordinates: [longitude, latitude],
+        }
+    }
+
+    /// Latitude in degrees
+    pub fn latitude(&self) -> f64 {
+        self.coordinates[1]
+    }
+
+    /// Longitude in degrees
+    pub fn longitude(&self) -> f64 {
+        self.coordinates[0]
+    }
+
+    /// Round the point to two decimal places, roughly a kilometre
+    pub fn rounded(&self) -> GeoPoint {
+        GeoPoint::new(
+            (self.latitude() * 100.0).round() / 100.0,
+            (self.longitude() * 100.0).round() / 100.0,
+        )
+    }
+
+    /// Great-circle distance to another point in metres
+    pub fn distance_to(&self, other: &GeoPoint) -> f64 {
+        let (lat_a, lat_b) = (self.latitude().to_radians(), other.latitude().to_radians());
+        let d_lat = lat_b - lat_a;
+        let d_lon = (other.longitude() - self.longitude()).to_radians();
+
+        let a =
+            (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// # Geographic Radius
+///
+/// Area around a point to search for events in
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct GeoRadius {
+    /// Point to search around
+    pub center: GeoPoint,
+    /// Radius in metres
+    pub radius: f64,
+}
+
+/// Default number of events returned per page
+pub const DEFAULT_EVENTS_PER_PAGE: i64 = 20;
+
+/// # Event Query
+///
+/// Filter and paginate events
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct EventQuery {
+    /// Event type
+    pub event_type: Option<EventType>,
+    /// Only include events starting at or after this date
+    pub start_date_from: Option<DateTime<Utc>>,
+    /// Only include events starting at or before this date
+    pub start_date_to: Option<DateTime<Utc>>,
+    /// City where event is held (case-insensitive)
+    pub city: Option<String>,
+    /// Only include events with this status, defaults to every status but drafts
+    pub status: Option<EventStatus>,
+    /// Only include events within this area, nearest first
+    pub near: Option<GeoRadius>,
+    /// Page number, starting at 1
+    pub page: Option<i64>,
+    /// Number of events per page
+    pub per_page: Option<i64>,
+}
+
+impl EventQuery {
+    /// Page number to fetch
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Number of events to fetch per page
+    pub fn per_page(&self) -> i64 {
+        self.per_page.unwrap_or(DEFAULT_EVENTS_PER_PAGE).max(1)
+    }
+
+    /// Number of events to skip before the requested page
+    pub fn skip(&self) -> u64 {
+        ((self.page() - 1) * self.per_page()) as u64
+    }
+}
+
+/// # Event List Response
+///
+/// Paginated list of events
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct EventListResponse {
+    /// Events on the requested page
+    pub events: Vec<Event>,
+    /// Total number of events matching the query
+    pub total: u64,
+    /// Current page number
+    pub page: i64,
+    /// Number of events per page
+    pub per_page: i64,
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+
+/// Number of days after an event ends during which guests can leave feedback
+pub const FEEDBACK_WINDOW_DAYS: i64 = 30;
+
+/// Feedback a guest left after attending an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventFeedback {
+    /// Feedback Id
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Event the feedback is for
+    pub event_id: String,
+    /// Guest who left the feedback
+    pub guest_id: String,
+    /// User who left the feedback, if the guest is a registered user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Rating from 1 to 5
+    pub rating: i32,
+    /// Free text comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Whether to hide who left the feedback from the event's staff
+    #[serde(default)]
+    pub anonymous: bool,
+    /// When the feedback was first left
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+    /// When the feedback was last edited
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "iso_bson_chrono::serialize_optional",
+        deserialize_with = "iso_bson_chrono::deserialize_optional",
+        default
+    )]
+    pub edited_at: Option<DateTime<Utc>>,
+}
+
+/// Ratings given to an event, aggregated
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct FeedbackSummary {
+    /// Number of ratings given
+    pub count: i32,
+    /// Average rating, if any were given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average: Option<f64>,
+    /// Number of times each rating was given, from 1 to 5
+    pub distribution: Vec<i32>,
+}
+
+impl FeedbackSummary {
+    /// Aggregate the ratings of an event's feedback
+    pub fn from_feedback(feedback: &[EventFeedback]) -> FeedbackSummary {
+        let mut distribution = vec![0; 5];
+        for entry in feedback {
+            if let Some(count) = distribution.get_mut((entry.rating - 1) as usize) {
+                *count += 1;
+            }
+        }
+
+        let count: i32 = distribution.iter().sum();
+        let total: i32 = distribution
+            .iter()
+            .zip(1..)
+            .map(|(count, rating)| count * rating)
+            .sum();
+
+        FeedbackSummary {
+            count,
+            average: (count > 0).then(|| (total as f64 / count as f64 * 100.0).round() / 100.0),
+            distribution,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feedback(rating: i32) -> EventFeedback {
+        EventFeedback {
+            id: "feedback".into(),
+            event_id: "event".into(),
+            guest_id: "guest".into(),
+            user_id: None,
+            rating,
+            comment: None,
+            anonymous: false,
+            created_at: Utc::now(),
+            edited_at: None,
+        }
+    }
+
+    #[test]
+    fn summary_aggregates_ratings() {
+        let summary =
+            FeedbackSummary::from_feedback(&[feedback(5), feedback(4), feedback(4), feedback(2)]);
+
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.average, Some(3.75));
+        assert_eq!(summary.distribution, vec![0, 1, 0, 2, 1]);
+    }
+
+    #[test]
+    fn summary_without_ratings_has_no_average() {
+        let summary = FeedbackSummary::from_feedback(&[]);
+
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.average, None);
+        assert_eq!(summary.distribution, vec![0; 5]);
+    }
+}
@@ -1,4 +1,16 @@
+use crate::models::events::questionnaire::RsvpAnswer;
+use crate::util::signing;
+use chrono::{DateTime, Utc};
+use iso8601_timestamp::Timestamp;
+use revolt_database::util::iso_bson_chrono;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Purpose used when signing check-in passes
+static CHECKIN_PASS_PURPOSE: &str = "checkin";
+
+/// Purpose used when signing RSVP links for guests without an account
+static RSVP_TOKEN_PURPOSE: &str = "rsvp";
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EventGuest {
@@ -30,7 +42,11 @@ pub struct EventGuest {
     /// Guest's phone number
     pub phone: String,
 
-    /// Guest status (pending/approved/rejected)
+    /// Answers to the event's RSVP questions, by question id
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub answers: HashMap<String, RsvpAnswer>,
+
+    /// Guest status (pending/approved/rejected/waitlisted/cancelled)
     pub status: GuestStatus,
 
     /// Is this guest a plus one
@@ -39,6 +55,92 @@ pub struct EventGuest {
 
     /// When the guest was added
     pub created_at: String,
+
+    /// When the guest was checked in at the door
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_in_at: Option<Timestamp>,
+
+    /// User who checked the guest in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_in_by: Option<String>,
+
+    /// QR payload of the guest's check-in pass, only shown to the guest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkin_pass: Option<String>,
+}
+
+impl EventGuest {
+    /// Check whether this guest attended the event, or was approved to
+    pub fn attended(&self) -> bool {
+        self.status == GuestStatus::Approved || self.checked_in_at.is_some()
+    }
+
+    /// Create a signed check-in pass for this guest
+    ///
+    /// Format: `<event id>.<guest id>.<signature>`
+    pub fn create_checkin_pass(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.event_id,
+            self.id,
+            signing::sign(CHECKIN_PASS_PURPOSE, &[&self.event_id, &self.id])
+        )
+    }
+
+    /// Create a signed token letting this guest answer their invitation without an account
+    ///
+    /// Format: `<event id>.<guest id>.<signature>`
+    pub fn create_rsvp_token(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.event_id,
+            self.id,
+            signing::sign(RSVP_TOKEN_PURPOSE, &[&self.event_id, &self.id])
+        )
+    }
+}
+
+/// Count the seats taken at an event
+///
+/// Approved guests take a seat, as do the plus-ones of approved
+/// guests while their own RSVP is still pending.
+pub fn count_seats(guests: &[EventGuest]) -> i32 {
+    guests
+        .iter()
+        .filter(|guest| match guest.status {
+            GuestStatus::Approved => true,
+            GuestStatus::Pending => guest.plus_one_of.as_ref().map_or(false, |main_id| {
+                guests
+                    .iter()
+                    .any(|main| &main.id == main_id && main.status == GuestStatus::Approved)
+            }),
+            _ => false,
+        })
+        .count() as i32
+}
+
+/// Verify a check-in pass, returning the event and guest ids it was issued for
+pub fn verify_checkin_pass(pass: &str) -> Option<(String, String)> {
+    verify_guest_token(CHECKIN_PASS_PURPOSE, pass)
+}
+
+/// Verify an RSVP token, returning the event and guest ids it was issued for
+///
+/// Tokens do not carry an expiry, callers must check the event has not ended.
+pub fn verify_rsvp_token(token: &str) -> Option<(String, String)> {
+    verify_guest_token(RSVP_TOKEN_PURPOSE, token)
+}
+
+/// Verify a token of the form `<event id>.<guest id>.<signature>`
+fn verify_guest_token(purpose: &str, token: &str) -> Option<(String, String)> {
+    let mut parts = token.trim().split('.');
+    let (event_id, guest_id, signature) = (parts.next()?, parts.next()?, parts.next()?);
+
+    if parts.next().is_some() || !signing::verify(purpose, &[event_id, guest_id], signature) {
+        return None;
+    }
+
+    Some((event_id.to_string(), guest_id.to_string()))
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
@@ -48,6 +150,30 @@ pub enum GuestStatus {
     Pending,
     Approved,
     Rejected,
+    Waitlisted,
+    Cancelled,
+}
+
+impl GuestStatus {
+    /// Check whether a guest may be moved from this status to another
+    pub fn can_transition_to(&self, status: &GuestStatus) -> bool {
+        matches!(
+            (self, status),
+            (
+                GuestStatus::Pending,
+                GuestStatus::Approved
+                    | GuestStatus::Rejected
+                    | GuestStatus::Waitlisted
+                    | GuestStatus::Cancelled
+            ) | (
+                GuestStatus::Waitlisted,
+                GuestStatus::Approved | GuestStatus::Rejected | GuestStatus::Cancelled
+            ) | (
+                GuestStatus::Approved,
+                GuestStatus::Rejected | GuestStatus::Cancelled
+            ) | (GuestStatus::Rejected, GuestStatus::Pending)
+        )
+    }
 }
 
 impl From<GuestStatus> for String {
@@ -56,6 +182,8 @@ impl From<GuestStatus> for String {
             GuestStatus::Pending => "Pending".to_string(),
             GuestStatus::Approved => "Approved".to_string(),
             GuestStatus::Rejected => "Rejected".to_string(),
+            GuestStatus::Waitlisted => "Waitlisted".to_string(),
+            GuestStatus::Cancelled => "Cancelled".to_string(),
         }
     }
 }
@@ -66,7 +194,80 @@ impl From<String> for GuestStatus {
             "Pending" => GuestStatus::Pending,
             "Approved" => GuestStatus::Approved,
             "Rejected" => GuestStatus::Rejected,
+            "Waitlisted" => GuestStatus::Waitlisted,
+            "Cancelled" => GuestStatus::Cancelled,
             _ => GuestStatus::Pending, // Default case
         }
     }
 }
+
+/// Record of a change made to a guest's status
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct GuestStatusChange {
+    /// Change Id
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// Event the guest belongs to
+    pub event_id: String,
+
+    /// Guest whose status changed
+    pub guest_id: String,
+
+    /// Previous status
+    pub from: GuestStatus,
+
+    /// New status
+    pub to: GuestStatus,
+
+    /// User who made the change, or the guest if they answered an invitation link
+    pub changed_by: String,
+
+    /// When the change was made
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub changed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUSES: [GuestStatus; 5] = [
+        GuestStatus::Pending,
+        GuestStatus::Approved,
+        GuestStatus::Rejected,
+        GuestStatus::Waitlisted,
+        GuestStatus::Cancelled,
+    ];
+
+    #[test]
+    fn status_transitions() {
+        use GuestStatus::*;
+
+        let allowed = [
+            (Pending, Approved),
+            (Pending, Rejected),
+            (Pending, Waitlisted),
+            (Pending, Cancelled),
+            (Approved, Rejected),
+            (Approved, Cancelled),
+            (Rejected, Pending),
+            (Waitlisted, Approved),
+            (Waitlisted, Rejected),
+            (Waitlisted, Cancelled),
+        ];
+
+        for from in &STATUSES {
+            for to in &STATUSES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from.clone(), to.clone())),
+                    "{from:?} -> {to:?}"
+                );
+            }
+        }
+    }
+}
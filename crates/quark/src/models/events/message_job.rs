@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+
+/// What a message job sends to its recipients
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum MessageJobKind {
+    /// Invitations to the event, counted towards its invited count
+    Invitation,
+    /// Notification to the event's guests
+    Notification,
+}
+
+/// Progress of a message job as a whole
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum MessageJobStatus {
+    /// Waiting for a worker to pick it up
+    Queued,
+    /// Being sent, or waiting to retry failed recipients
+    Running,
+    /// Every recipient was either sent to or has run out of attempts
+    Completed,
+}
+
+/// Delivery status of a single recipient
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Not sent yet, or will be retried
+    Pending,
+    /// Sent successfully
+    Sent,
+    /// Failed too many times to be retried
+    Failed,
+}
+
+/// Recipient of a message job
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MessageRecipient {
+    /// User to send a DM to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Guest the recipient was picked from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_id: Option<String>,
+    /// Email address to send to, looked up from the user's account if missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Personal note added below the job's content
+    #[serde(skip_serializing_if = "Option::is_none", alias = "content")]
+    pub note: Option<String>,
+    /// Link sent along with the message
+    pub url: String,
+    /// Delivery status
+    pub status: DeliveryStatus,
+    /// Whether the DM went out, or there was none to send
+    #[serde(default)]
+    pub dm_sent: bool,
+    /// Whether the email went out, or there was none to send
+    #[serde(default)]
+    pub email_sent: bool,
+    /// Number of attempts made so far
+    pub attempts: u32,
+    /// Reason the last attempt failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Messages queued up to be sent to an event's guests in the background
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventMessageJob {
+    /// Job Id
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Event the messages are about
+    pub event_id: String,
+    /// User sending the messages
+    pub created_by: String,
+    /// What is being sent
+    pub kind: MessageJobKind,
+    /// Email subject
+    pub title: String,
+    /// Message content, shared by every recipient
+    #[serde(default)]
+    pub content: String,
+    /// Recipients and their delivery status
+    pub recipients: Vec<MessageRecipient>,
+    /// Job status
+    pub status: MessageJobStatus,
+
+    /// When the job was created
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+    /// When the job is next due to be worked on
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub run_at: DateTime<Utc>,
+}
+
+impl MessageRecipient {
+    /// Create a recipient waiting to be sent to
+    pub fn new(
+        user_id: Option<String>,
+        guest_id: Option<String>,
+        email: Option<String>,
+        note: Option<String>,
+        url: String,
+    ) -> MessageRecipient {
+        MessageRecipient {
+            user_id,
+            guest_id,
+            email,
+            note,
+            url,
+            status: DeliveryStatus::Pending,
+            dm_sent: false,
+            email_sent: false,
+            attempts: 0,
+            error: None,
+        }
+    }
+}
+
+impl EventMessageJob {
+    /// Count the recipients with the given delivery status
+    pub fn count(&self, status: &DeliveryStatus) -> usize {
+        self.recipients
+            .iter()
+            .filter(|recipient| &recipient.status == status)
+            .count()
+    }
+
+    /// Message content to send a recipient, followed by their personal note
+    pub fn content_for(&self, recipient: &MessageRecipient) -> String {
+        [Some(&self.content), recipient.note.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
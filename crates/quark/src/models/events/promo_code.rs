@@ -0,0 +1,157 @@
+use crate::models::events::ticket::{parse_amount, TicketTier};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+/// Discount given by a promo code
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum PromoDiscount {
+    /// Percentage taken off the price of the seats
+    Percentage { percentage: String },
+    /// Fixed amount taken off the price of the seats
+    Fixed { amount: String },
+}
+
+/// Promo code giving a discount on an event's tickets
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PromoCode {
+    /// Promo code Id
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Event the code is for
+    pub event_id: String,
+    /// Code buyers enter, always upper case
+    pub code: String,
+    /// Discount given
+    pub discount: PromoDiscount,
+    /// Tier the code is restricted to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<TicketTier>,
+    /// Partner the code was handed out to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partner: Option<String>,
+    /// Maximum number of times the code can be redeemed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<i32>,
+    /// Number of times the code was redeemed
+    #[serde(default)]
+    pub uses: i32,
+    /// When the code stops working
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "iso_bson_chrono::serialize_optional",
+        deserialize_with = "iso_bson_chrono::deserialize_optional",
+        default
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// User who created the code
+    pub created_by: String,
+    /// When the code was created
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Ticket bought with a promo code
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PromoRedemption {
+    /// Redemption Id
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Event the ticket is for
+    pub event_id: String,
+    /// Promo code redeemed
+    pub promo_code_id: String,
+    /// Code as entered
+    pub code: String,
+    /// Partner the code was handed out to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partner: Option<String>,
+    /// Ticket bought with the code
+    pub ticket_id: String,
+    /// User who bought the ticket
+    pub user_id: String,
+    /// Guest entry of the buyer, if they are on the guest list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_id: Option<String>,
+    /// Number of seats bought
+    pub quantity: i32,
+    /// Amount taken off the price
+    pub discount: String,
+    /// When the code was redeemed
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+impl PromoDiscount {
+    /// Check the discount is a valid amount, percentages going up to 100
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            PromoDiscount::Percentage { percentage } => {
+                let percentage = parse_amount(percentage)?;
+                if percentage.is_zero() || percentage > Decimal::ONE_HUNDRED {
+                    return Err(Error::InvalidTicketPrice);
+                }
+            }
+            PromoDiscount::Fixed { amount } => {
+                if parse_amount(amount)?.is_zero() {
+                    return Err(Error::InvalidTicketPrice);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Amount taken off a price, rounded to the cent and never more than the price itself
+    pub fn apply(&self, price: Decimal) -> Result<Decimal> {
+        let discount = match self {
+            PromoDiscount::Percentage { percentage } => {
+                let percentage = parse_amount(percentage)?;
+                (price * percentage / Decimal::ONE_HUNDRED)
+                    .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+            }
+            PromoDiscount::Fixed { amount } => parse_amount(amount)?,
+        };
+
+        Ok(discount.min(price))
+    }
+}
+
+impl PromoCode {
+    /// Normalise a code as entered by a buyer or host
+    pub fn normalise(code: &str) -> String {
+        code.trim().to_uppercase()
+    }
+
+    /// Check the code can be redeemed for a ticket in the given tier
+    pub fn check_redeemable(&self, tier: TicketTier) -> Result<()> {
+        if self
+            .expires_at
+            .map_or(false, |expires_at| expires_at <= Utc::now())
+        {
+            return Err(Error::PromoCodeExpired);
+        }
+
+        if self.tier.map_or(false, |restricted| restricted != tier) {
+            return Err(Error::InvalidPromoCode);
+        }
+
+        if self
+            .max_uses
+            .map_or(false, |max_uses| self.uses >= max_uses)
+        {
+            return Err(Error::PromoCodeUsedUp);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum number of questions an event can ask
+pub const MAX_QUESTIONS: usize = 20;
+
+/// Maximum number of options a choice question can offer
+pub const MAX_QUESTION_OPTIONS: usize = 20;
+
+/// Maximum length of a text answer
+pub const MAX_ANSWER_LENGTH: usize = 1000;
+
+/// Kind of answer a question takes
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum QuestionKind {
+    /// Free text
+    Text,
+    /// Exactly one of the options
+    SingleChoice,
+    /// Any number of the options
+    MultiChoice,
+    /// Yes or no
+    Boolean,
+}
+
+/// Question guests are asked when they RSVP
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct RsvpQuestion {
+    /// Question Id, answers are keyed by it
+    pub id: String,
+    /// Question as shown to guests
+    pub label: String,
+    /// Kind of answer the question takes
+    pub kind: QuestionKind,
+    /// Options to pick from, for choice questions
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub options: Vec<String>,
+    /// Whether guests have to answer the question
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Answer a guest gave to a question
+///
+/// Text and single choice questions take a string, multiple choice
+/// questions a list of strings and boolean questions `true` or `false`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RsvpAnswer {
+    Boolean(bool),
+    Text(String),
+    Choices(Vec<String>),
+}
+
+impl RsvpQuestion {
+    /// Check a host's questions are well formed
+    pub fn validate_all(questions: &[RsvpQuestion]) -> Result<()> {
+        let mut errors = vec![];
+        if questions.len() > MAX_QUESTIONS {
+            errors.push(format!(
+                "questions: at most {MAX_QUESTIONS} questions can be asked"
+            ));
+        }
+
+        for (index, question) in questions.iter().enumerate() {
+            if question.id.is_empty()
+                || question.id.len() > 32
+                || !question
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                errors.push(format!(
+                    "questions[{index}].id: expected 1 to 32 letters, numbers, dashes or underscores"
+                ));
+            }
+
+            if questions[..index]
+                .iter()
+                .any(|other| other.id == question.id)
+            {
+                errors.push(format!(
+                    "questions[{index}].id: duplicate id {}",
+                    question.id
+                ));
+            }
+
+            if question.label.trim().is_empty() || question.label.len() > 200 {
+                errors.push(format!(
+                    "questions[{index}].label: expected 1 to 200 characters"
+                ));
+            }
+
+            match question.kind {
+                QuestionKind::SingleChoice | QuestionKind::MultiChoice => {
+                    if question.options.is_empty() || question.options.len() > MAX_QUESTION_OPTIONS
+                    {
+                        errors.push(format!(
+                            "questions[{index}].options: expected 1 to {MAX_QUESTION_OPTIONS} options"
+                        ));
+                    }
+
+                    for (position, option) in question.options.iter().enumerate() {
+                        if option.trim().is_empty() || option.len() > 100 {
+                            errors.push(format!(
+                                "questions[{index}].options[{position}]: expected 1 to 100 characters"
+                            ));
+                        } else if question.options[..position].contains(option) {
+                            errors.push(format!(
+                                "questions[{index}].options[{position}]: duplicate option {option}"
+                            ));
+                        }
+                    }
+                }
+                QuestionKind::Text | QuestionKind::Boolean => {
+                    if !question.options.is_empty() {
+                        errors.push(format!(
+                            "questions[{index}].options: only choice questions take options"
+                        ));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidRequest {
+                code: "invalid_questions".to_string(),
+                errors,
+            })
+        }
+    }
+
+    /// Check a guest's answers against an event's questions
+    ///
+    /// Every required question must be answered and every answer must fit
+    /// its question. Blank answers to optional questions are dropped.
+    pub fn check_answers(
+        questions: &[RsvpQuestion],
+        mut answers: HashMap<String, RsvpAnswer>,
+    ) -> Result<HashMap<String, RsvpAnswer>> {
+        let mut errors: Vec<String> = answers
+            .keys()
+            .filter(|id| !questions.iter().any(|question| &question.id == *id))
+            .map(|id| format!("answers.{id}: no such question"))
+            .collect();
+
+        for question in questions {
+            let answer = answers.remove(&question.id).filter(|answer| match answer {
+                RsvpAnswer::Text(text) => !text.trim().is_empty(),
+                RsvpAnswer::Choices(choices) => !choices.is_empty(),
+                RsvpAnswer::Boolean(_) => true,
+            });
+
+            match answer {
+                Some(answer) => match question.check_answer(&answer) {
+                    Ok(()) => {
+                        answers.insert(question.id.clone(), answer);
+                    }
+                    Err(error) => errors.push(format!("answers.{}: {error}", question.id)),
+                },
+                None if question.required => {
+                    errors.push(format!("answers.{}: an answer is required", question.id))
+                }
+                None => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(answers)
+        } else {
+            Err(Error::InvalidRequest {
+                code: "invalid_answers".to_string(),
+                errors,
+            })
+        }
+    }
+
+    // Check a single, non-blank answer fits this question
+    fn check_answer(&self, answer: &RsvpAnswer) -> std::result::Result<(), String> {
+        match (&self.kind, answer) {
+            (QuestionKind::Text, RsvpAnswer::Text(text)) => {
+                if text.len() > MAX_ANSWER_LENGTH {
+                    return Err(format!("at most {MAX_ANSWER_LENGTH} characters"));
+                }
+            }
+            (QuestionKind::SingleChoice, RsvpAnswer::Text(choice)) => {
+                if !self.options.contains(choice) {
+                    return Err(format!("{choice} is not one of the options"));
+                }
+            }
+            (QuestionKind::MultiChoice, RsvpAnswer::Choices(choices)) => {
+                for (index, choice) in choices.iter().enumerate() {
+                    if !self.options.contains(choice) {
+                        return Err(format!("{choice} is not one of the options"));
+                    }
+
+                    if choices[..index].contains(choice) {
+                        return Err(format!("{choice} was picked more than once"));
+                    }
+                }
+            }
+            (QuestionKind::Boolean, RsvpAnswer::Boolean(_)) => {}
+            (QuestionKind::Text | QuestionKind::SingleChoice, _) => {
+                return Err("expected a string".to_string())
+            }
+            (QuestionKind::MultiChoice, _) => return Err("expected a list of strings".to_string()),
+            (QuestionKind::Boolean, _) => return Err("expected true or false".to_string()),
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for RsvpAnswer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RsvpAnswer::Boolean(true) => write!(f, "yes"),
+            RsvpAnswer::Boolean(false) => write!(f, "no"),
+            RsvpAnswer::Text(text) => write!(f, "{text}"),
+            RsvpAnswer::Choices(choices) => write!(f, "{}", choices.join("; ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn questions() -> Vec<RsvpQuestion> {
+        vec![
+            RsvpQuestion {
+                id: "dietary".into(),
+                label: "Dietary restrictions".into(),
+                kind: QuestionKind::Text,
+                options: vec![],
+                required: false,
+            },
+            RsvpQuestion {
+                id: "size".into(),
+                label: "T-shirt size".into(),
+                kind: QuestionKind::SingleChoice,
+                options: vec!["S".into(), "M".into(), "L".into()],
+                required: true,
+            },
+            RsvpQuestion {
+                id: "heard".into(),
+                label: "How did you hear about us?".into(),
+                kind: QuestionKind::MultiChoice,
+                options: vec!["Friends".into(), "Social media".into()],
+                required: false,
+            },
+            RsvpQuestion {
+                id: "first_time".into(),
+                label: "First time attending?".into(),
+                kind: QuestionKind::Boolean,
+                options: vec![],
+                required: true,
+            },
+        ]
+    }
+
+    fn answers(json: &str) -> HashMap<String, RsvpAnswer> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn valid_answers_are_kept() {
+        let checked = RsvpQuestion::check_answers(
+            &questions(),
+            answers(
+                r#"{ "dietary": "Vegan", "size": "M", "heard": ["Friends"], "first_time": true }"#,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(checked.len(), 4);
+        assert_eq!(checked["size"], RsvpAnswer::Text("M".into()));
+        assert_eq!(checked["first_time"], RsvpAnswer::Boolean(true));
+    }
+
+    #[test]
+    fn blank_optional_answers_are_dropped() {
+        let checked = RsvpQuestion::check_answers(
+            &questions(),
+            answers(r#"{ "dietary": " ", "size": "S", "heard": [], "first_time": false }"#),
+        )
+        .unwrap();
+
+        assert!(!checked.contains_key("dietary"));
+        assert!(!checked.contains_key("heard"));
+    }
+
+    #[test]
+    fn invalid_answers_are_rejected() {
+        for json in [
+            r#"{ "first_time": true }"#,
+            r#"{ "size": "XL", "first_time": true }"#,
+            r#"{ "size": ["M"], "first_time": true }"#,
+            r#"{ "size": "M", "heard": ["Friends", "Friends"], "first_time": true }"#,
+            r#"{ "size": "M", "first_time": "yes" }"#,
+            r#"{ "size": "M", "first_time": true, "unknown": "value" }"#,
+        ] {
+            assert!(
+                RsvpQuestion::check_answers(&questions(), answers(json)).is_err(),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_questions_are_rejected() {
+        assert!(RsvpQuestion::validate_all(&questions()).is_ok());
+
+        let mut duplicate = questions();
+        duplicate[1].id = "dietary".into();
+        assert!(RsvpQuestion::validate_all(&duplicate).is_err());
+
+        let mut no_options = questions();
+        no_options[1].options.clear();
+        assert!(RsvpQuestion::validate_all(&no_options).is_err());
+
+        let mut text_options = questions();
+        text_options[0].options = vec!["A".into()];
+        assert!(RsvpQuestion::validate_all(&text_options).is_err());
+    }
+}
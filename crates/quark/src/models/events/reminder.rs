@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+
+/// Reminder scheduled to go out to guests ahead of an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventReminder {
+    /// Reminder Id, made up of the event id and offset
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// Event the reminder is for
+    pub event_id: String,
+
+    /// Minutes before the start of the event the reminder is for
+    pub offset: i64,
+
+    /// When the reminder is due to be sent
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub send_at: DateTime<Utc>,
+
+    /// Number of failed attempts at sending the reminder so far
+    #[serde(default)]
+    pub attempts: u32,
+}
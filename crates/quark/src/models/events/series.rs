@@ -0,0 +1,310 @@
+use crate::{Error, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rocket_impl")]
+use rocket::FromFormField;
+
+/// Maximum number of occurrences a series can expand into
+pub const MAX_SERIES_OCCURRENCES: usize = 100;
+
+/// Series of events repeating on a recurrence rule
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventSeries {
+    /// Series Id
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// User who created the series
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+
+    /// Rule the occurrences were expanded from
+    pub rule: RecurrenceRule,
+
+    /// Creation timestamp
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Subset of the iCalendar RRULE
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct RecurrenceRule {
+    /// How often the event repeats
+    pub frequency: RecurrenceFrequency,
+
+    /// Number of periods between occurrences, defaults to 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+
+    /// Total number of occurrences, including skipped ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+
+    /// Last moment an occurrence may start at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Local dates on which the event does not take place
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub exceptions: Vec<NaiveDate>,
+}
+
+/// Frequency of a recurrence rule
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurrenceFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Which occurrences of a series an edit applies to
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rocket_impl", derive(FromFormField))]
+pub enum EditScope {
+    /// Only the occurrence being edited
+    This,
+    /// The occurrence being edited and every later one
+    Future,
+}
+
+impl RecurrenceRule {
+    /// Ensure the rule is bounded and well-formed for a series starting at `start`
+    pub fn check(&self, start: &DateTime<Utc>, timezone: &Tz) -> Result<()> {
+        let mut errors = vec![];
+
+        if self.count.is_none() && self.until.is_none() {
+            errors.push("recurrence: either count or until is required".to_string());
+        }
+
+        if let Some(count) = self.count {
+            if count == 0 || count as usize > MAX_SERIES_OCCURRENCES {
+                errors.push(format!(
+                    "recurrence.count: must be between 1 and {MAX_SERIES_OCCURRENCES}"
+                ));
+            }
+        }
+
+        if matches!(self.interval, Some(interval) if interval == 0 || interval > 99) {
+            errors.push("recurrence.interval: must be between 1 and 99".to_string());
+        }
+
+        if matches!(self.until, Some(until) if until < *start) {
+            errors.push("recurrence.until: must be after start_date".to_string());
+        } else if self.count.is_none()
+            && self.until.is_some()
+            && self
+                .expand(start, timezone, MAX_SERIES_OCCURRENCES + 1)
+                .len()
+                > MAX_SERIES_OCCURRENCES
+        {
+            errors.push(format!(
+                "recurrence.until: must not allow more than {MAX_SERIES_OCCURRENCES} occurrences"
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidRequest {
+                code: "invalid_recurrence".to_string(),
+                errors,
+            })
+        }
+    }
+
+    /// Expand the rule into the start of each occurrence
+    ///
+    /// Occurrences are computed in local time so they keep their
+    /// wall-clock time across daylight saving changes. Monthly rules
+    /// skip months which do not have the starting day of the month.
+    pub fn occurrences(&self, start: &DateTime<Utc>, timezone: &Tz) -> Vec<DateTime<Utc>> {
+        let limit = self
+            .count
+            .map_or(MAX_SERIES_OCCURRENCES, |count| count as usize)
+            .min(MAX_SERIES_OCCURRENCES);
+
+        self.expand(start, timezone, limit)
+            .into_iter()
+            .filter(|(date, _)| !self.exceptions.contains(date))
+            .map(|(_, occurrence)| occurrence)
+            .collect()
+    }
+
+    /// Expand the rule into at most `limit` occurrences, exceptions included,
+    /// along with the local date each of them falls on
+    fn expand(
+        &self,
+        start: &DateTime<Utc>,
+        timezone: &Tz,
+        limit: usize,
+    ) -> Vec<(NaiveDate, DateTime<Utc>)> {
+        let local = start.with_timezone(timezone).naive_local();
+        let interval = self.interval.unwrap_or(1).max(1) as i64;
+
+        let mut occurrences = vec![];
+        let mut step = 0;
+
+        // Monthly rules may skip up to 11 months in a row
+        while occurrences.len() < limit && step < (limit as i64 + 1) * 12 {
+            let candidate = match self.frequency {
+                RecurrenceFrequency::Daily => Some(local + Duration::days(step * interval)),
+                RecurrenceFrequency::Weekly => Some(local + Duration::weeks(step * interval)),
+                RecurrenceFrequency::Monthly => add_months(local, step * interval),
+            };
+
+            step += 1;
+
+            let (candidate, date) = match candidate.and_then(|candidate| {
+                timezone
+                    .from_local_datetime(&candidate)
+                    .earliest()
+                    .map(|date| (candidate, date.with_timezone(&Utc)))
+            }) {
+                Some(occurrence) => occurrence,
+                None => continue,
+            };
+
+            if matches!(self.until, Some(until) if date > until) {
+                break;
+            }
+
+            occurrences.push((candidate.date(), date));
+        }
+
+        occurrences
+    }
+}
+
+/// Move a local date-time by a number of months, keeping the day of the month
+fn add_months(date: NaiveDateTime, months: i64) -> Option<NaiveDateTime> {
+    let month = date.year() as i64 * 12 + date.month0() as i64 + months;
+    NaiveDate::from_ymd_opt((month / 12) as i32, (month % 12) as u32 + 1, date.day())
+        .map(|day| day.and_time(date.time()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn rule(frequency: RecurrenceFrequency) -> RecurrenceRule {
+        RecurrenceRule {
+            frequency,
+            interval: None,
+            count: None,
+            until: None,
+            exceptions: vec![],
+        }
+    }
+
+    #[test]
+    fn occurrences_keep_their_local_time_across_daylight_saving() {
+        let rule = RecurrenceRule {
+            count: Some(3),
+            ..rule(RecurrenceFrequency::Weekly)
+        };
+
+        // Clocks in London go back on the 25th of October 2026
+        let occurrences = rule.occurrences(&at("2026-10-17T18:00:00Z"), &chrono_tz::Europe::London);
+
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2026-10-17T18:00:00Z"),
+                at("2026-10-24T18:00:00Z"),
+                at("2026-10-31T19:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_occurrences_skip_months_without_the_day() {
+        let rule = RecurrenceRule {
+            count: Some(4),
+            ..rule(RecurrenceFrequency::Monthly)
+        };
+
+        let occurrences = rule.occurrences(&at("2026-01-31T12:00:00Z"), &Tz::UTC);
+
+        // February, April and June have no 31st so they are skipped
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2026-01-31T12:00:00Z"),
+                at("2026-03-31T12:00:00Z"),
+                at("2026-05-31T12:00:00Z"),
+                at("2026-07-31T12:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn exceptions_are_left_out_but_counted() {
+        let rule = RecurrenceRule {
+            count: Some(3),
+            exceptions: vec![NaiveDate::from_ymd_opt(2026, 10, 2).unwrap()],
+            ..rule(RecurrenceFrequency::Daily)
+        };
+
+        let occurrences = rule.occurrences(&at("2026-10-01T09:00:00Z"), &Tz::UTC);
+
+        assert_eq!(
+            occurrences,
+            vec![at("2026-10-01T09:00:00Z"), at("2026-10-03T09:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn occurrences_stop_at_until() {
+        let rule = RecurrenceRule {
+            until: Some(at("2026-10-15T09:00:00Z")),
+            ..rule(RecurrenceFrequency::Weekly)
+        };
+
+        let start = at("2026-10-01T09:00:00Z");
+        assert!(rule.check(&start, &Tz::UTC).is_ok());
+        assert_eq!(
+            rule.occurrences(&start, &Tz::UTC),
+            vec![
+                at("2026-10-01T09:00:00Z"),
+                at("2026-10-08T09:00:00Z"),
+                at("2026-10-15T09:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn until_may_not_allow_too_many_occurrences() {
+        let start = at("2026-10-01T09:00:00Z");
+        let until = start + Duration::days(MAX_SERIES_OCCURRENCES as i64);
+        let rule = RecurrenceRule {
+            until: Some(until),
+            ..rule(RecurrenceFrequency::Daily)
+        };
+
+        assert!(matches!(
+            rule.check(&start, &Tz::UTC),
+            Err(Error::InvalidRequest { .. })
+        ));
+
+        let rule = RecurrenceRule {
+            until: Some(until - Duration::days(1)),
+            ..rule
+        };
+
+        assert!(rule.check(&start, &Tz::UTC).is_ok());
+        assert_eq!(
+            rule.occurrences(&start, &Tz::UTC).len(),
+            MAX_SERIES_OCCURRENCES
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::models::event::{Event, EventStatus, EventType, GeoPoint, TicketConfig};
+use crate::models::events::questionnaire::RsvpQuestion;
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ulid::Ulid;
+
+/// Named template a host can turn into new events
+///
+/// Keeps everything about an event that carries over from one edition to
+/// the next, leaving out its dates, guests and tickets sold.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventTemplate {
+    /// Template Id
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Name the host gave the template
+    pub name: String,
+    /// User who owns the template
+    pub created_by: String,
+    /// Event the template was saved from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_event_id: Option<String>,
+
+    /// Event title
+    pub title: String,
+    /// Event type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<EventType>,
+    /// Event description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// IANA timezone the event takes place in
+    pub timezone: String,
+    /// City where event is held
+    pub city: String,
+    /// Country where event is held
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Area/neighborhood
+    pub area: String,
+    /// Full address
+    pub address: String,
+    /// Coordinates of the venue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    /// Whether to hide the address
+    pub hide_address: bool,
+    /// Allow +1 guests
+    pub allow_plus_one: bool,
+    /// Maximum number of +1 guests allowed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_plus_one_amount: Option<i32>,
+    /// Require full information for +1 guests
+    pub requires_plus_one_info: bool,
+    /// Require RSVP approval by host
+    pub requires_rsvp_approval: bool,
+    /// Show events to non-members
+    pub show_to_non_members: bool,
+    /// Don't send reminders to guests ahead of the event
+    pub disable_reminders: bool,
+    /// Show the aggregated guest ratings to anyone who can see the event
+    #[serde(default)]
+    pub public_ratings: bool,
+    /// Maximum number of guests (including plus-ones)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    /// Event hosts (user IDs)
+    pub hosts: Vec<String>,
+    /// Event managers (user IDs)
+    pub managers: Vec<String>,
+    /// Permissions assigned to hosts and managers, by user id
+    #[serde(default)]
+    pub staff_permissions: HashMap<String, u32>,
+    /// Event sponsors (user IDs)
+    pub sponsors: Vec<String>,
+    /// Currency type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Payment type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_type: Option<String>,
+    /// Questions guests are asked when they RSVP
+    #[serde(default)]
+    pub questions: Vec<RsvpQuestion>,
+    /// Ticket configuration
+    pub ticket_config: TicketConfig,
+    /// Attachment URLs
+    pub attachments: Vec<String>,
+    /// Gallery image URLs
+    pub gallery: Vec<String>,
+    /// Thumbnail image ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+
+    /// Creation timestamp
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+impl EventTemplate {
+    /// Save an event's reusable details as a template owned by the given user
+    pub fn from_event(event: &Event, name: String, created_by: String) -> EventTemplate {
+        EventTemplate {
+            id: Ulid::new().to_string(),
+            name,
+            created_by,
+            source_event_id: Some(event.id.clone()),
+            title: event.title.clone(),
+            event_type: event.event_type.clone(),
+            description: event.description.clone(),
+            timezone: event.timezone.clone(),
+            city: event.city.clone(),
+            country: event.country.clone(),
+            area: event.area.clone(),
+            address: event.address.clone(),
+            location: event.location.clone(),
+            hide_address: event.hide_address,
+            allow_plus_one: event.allow_plus_one,
+            allow_plus_one_amount: event.allow_plus_one_amount,
+            requires_plus_one_info: event.requires_plus_one_info,
+            requires_rsvp_approval: event.requires_rsvp_approval,
+            show_to_non_members: event.show_to_non_members,
+            disable_reminders: event.disable_reminders,
+            public_ratings: event.public_ratings,
+            capacity: event.capacity,
+            hosts: event.hosts.clone(),
+            managers: event.managers.clone(),
+            staff_permissions: event.staff_permissions.clone(),
+            sponsors: event.sponsors.clone(),
+            currency: event.currency.clone(),
+            payment_type: event.payment_type.clone(),
+            questions: event.questions.clone(),
+            ticket_config: event.ticket_config.clone(),
+            attachments: event.attachments.clone(),
+            gallery: event.gallery.clone(),
+            thumbnail: event.thumbnail.clone(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Turn the template into a new draft event owned by its creator
+    pub fn to_draft(&self, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Event {
+        Event {
+            id: Ulid::new().to_string(),
+            created_by: Some(self.created_by.clone()),
+            title: self.title.clone(),
+            event_type: self.event_type.clone(),
+            start_date,
+            end_date,
+            timezone: self.timezone.clone(),
+            city: self.city.clone(),
+            country: self.country.clone(),
+            hide_address: self.hide_address,
+            area: self.area.clone(),
+            address: self.address.clone(),
+            location: self.location.clone(),
+            description: self.description.clone(),
+            allow_plus_one: self.allow_plus_one,
+            allow_plus_one_amount: self.allow_plus_one_amount,
+            requires_plus_one_info: self.requires_plus_one_info,
+            requires_rsvp_approval: self.requires_rsvp_approval,
+            show_to_non_members: self.show_to_non_members,
+            disable_reminders: self.disable_reminders,
+            public_ratings: self.public_ratings,
+            channel_id: None,
+            capacity: self.capacity,
+            hosts: self.hosts.clone(),
+            host_details: None,
+            managers: self.managers.clone(),
+            staff_permissions: self.staff_permissions.clone(),
+            sponsors: self.sponsors.clone(),
+            sponsor_details: None,
+            questions: self.questions.clone(),
+            ticket_config: self.ticket_config.clone(),
+            ticket_counts: Default::default(),
+            seats_taken: 0,
+            currency: self.currency.clone(),
+            payment_type: self.payment_type.clone(),
+            attachments: self.attachments.clone(),
+            gallery: self.gallery.clone(),
+            is_saved: None,
+            created_at: Utc::now(),
+            series_id: None,
+            sequence: 0,
+            status: EventStatus::Draft,
+            publish_at: None,
+            guests: None,
+            guest_stats: None,
+            distance: None,
+            thumbnail: self.thumbnail.clone(),
+            invited_count: None,
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use crate::models::event::TicketConfig;
+use crate::models::events::promo_code::PromoDiscount;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use revolt_database::util::iso_bson_chrono;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Ticket held by a user for an event
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Ticket {
+    /// Ticket Id
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// Event this ticket is for
+    pub event_id: String,
+
+    /// User holding the ticket
+    pub user_id: String,
+
+    /// Pricing tier the ticket was issued under
+    pub tier: TicketTier,
+
+    /// Number of seats held by this ticket
+    pub quantity: i32,
+
+    /// Price per seat at the time of reservation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+
+    /// Currency of the price (e.g. "USD", "EUR")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+
+    /// Promo code redeemed for the ticket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub promo_code: Option<String>,
+
+    /// Current ticket status
+    pub status: TicketStatus,
+
+    /// Payment taken for the ticket, if it is paid for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment: Option<TicketPayment>,
+
+    /// When the ticket was reserved
+    #[serde(
+        serialize_with = "iso_bson_chrono::serialize",
+        deserialize_with = "iso_bson_chrono::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Pricing tier of a ticket
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketTier {
+    /// Buyer is a member of the community server
+    Member,
+    /// Buyer is not a member of the community server
+    NonMember,
+}
+
+/// Status of a ticket
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketStatus {
+    /// Seats are held but the ticket has not been confirmed yet
+    Reserved,
+    /// Ticket has been paid for or otherwise confirmed
+    Confirmed,
+    /// Ticket was cancelled and its seats released
+    Cancelled,
+}
+
+/// Payment taken for a ticket through a payment provider
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct TicketPayment {
+    /// Provider handling the payment
+    pub provider: String,
+    /// Checkout session opened with the provider
+    pub session_id: String,
+    /// Where the buyer completes the payment
+    pub checkout_url: String,
+    /// Price of the seats
+    pub subtotal: String,
+    /// Amount taken off the price by a promo code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discount: Option<String>,
+    /// Processing fee charged on top of the price
+    pub processing_fee: String,
+    /// Amount charged
+    pub total: String,
+    /// Currency the amount is charged in
+    pub currency: String,
+    /// Payment status
+    pub status: PaymentStatus,
+    /// Refund issued by the provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_id: Option<String>,
+}
+
+/// Status of a ticket payment
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentStatus {
+    /// Waiting for the buyer to complete checkout
+    Pending,
+    /// Provider confirmed the payment
+    Paid,
+    /// Checkout failed or expired
+    Failed,
+    /// Payment was refunded
+    Refunded,
+}
+
+/// Amounts charged for a ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketTotals {
+    /// Price of the seats
+    pub subtotal: Decimal,
+    /// Amount taken off the price by a promo code
+    pub discount: Decimal,
+    /// Processing fee charged on top of the price
+    pub processing_fee: Decimal,
+    /// Amount charged
+    pub total: Decimal,
+}
+
+/// Number of seats held by active tickets in each tier
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct TicketCounts {
+    /// Seats held by members
+    #[serde(default)]
+    pub member: i32,
+    /// Seats held by non-members
+    #[serde(default)]
+    pub non_member: i32,
+}
+
+/// Ticket totals for a single tier
+#[derive(Serialize, JsonSchema, Debug, Clone, Default)]
+pub struct TicketTierSummary {
+    /// Maximum number of seats available in this tier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tickets: Option<i32>,
+    /// Seats held by reserved tickets
+    pub reserved: i32,
+    /// Seats held by confirmed tickets
+    pub confirmed: i32,
+    /// Seats released by cancelled tickets
+    pub cancelled: i32,
+}
+
+/// Overview of tickets issued for an event
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct TicketSummary {
+    /// Member tier totals
+    pub member: TicketTierSummary,
+    /// Non-member tier totals
+    pub non_member: TicketTierSummary,
+    /// All tickets issued for the event
+    pub tickets: Vec<Ticket>,
+}
+
+impl TicketTier {
+    /// Key of this tier within `TicketCounts`
+    pub fn key(&self) -> &'static str {
+        match self {
+            TicketTier::Member => "member",
+            TicketTier::NonMember => "non_member",
+        }
+    }
+
+    /// Maximum number of seats available in this tier
+    pub fn max_tickets(&self, config: &TicketConfig) -> Option<i32> {
+        match self {
+            TicketTier::Member => config.member_max_tickets,
+            TicketTier::NonMember => config.non_member_max_tickets,
+        }
+    }
+
+    /// Price per seat in this tier
+    pub fn price(&self, config: &TicketConfig) -> Option<String> {
+        match self {
+            TicketTier::Member => config.member_price.clone(),
+            TicketTier::NonMember => config.non_member_price.clone(),
+        }
+    }
+
+    /// Currency of the price in this tier
+    pub fn currency(&self, config: &TicketConfig) -> Option<String> {
+        match self {
+            TicketTier::Member => config.member_price_currency.clone(),
+            TicketTier::NonMember => config.non_member_price_currency.clone(),
+        }
+    }
+
+    /// Work out what is charged for a number of seats in this tier
+    ///
+    /// Any discount comes off the price before the processing fee, which is a
+    /// percentage of what is left, rounded to the cent. Returns `None` if the
+    /// seats have no price, a discount can still bring the total down to zero.
+    pub fn totals(
+        &self,
+        config: &TicketConfig,
+        quantity: i32,
+        discount: Option<&PromoDiscount>,
+    ) -> Result<Option<TicketTotals>> {
+        if !config.is_paid {
+            return Ok(None);
+        }
+
+        let price = self
+            .price(config)
+            .as_deref()
+            .map(parse_amount)
+            .transpose()?
+            .unwrap_or_default();
+
+        let percentage = config
+            .processing_fee_percentage
+            .as_deref()
+            .map(parse_amount)
+            .transpose()?
+            .unwrap_or_default();
+
+        let subtotal = price * Decimal::from(quantity);
+        if subtotal.is_zero() {
+            return Ok(None);
+        }
+
+        let discount = discount
+            .map(|discount| discount.apply(subtotal))
+            .transpose()?
+            .unwrap_or_default();
+
+        let processing_fee = ((subtotal - discount) * percentage / Decimal::ONE_HUNDRED)
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+
+        Ok(Some(TicketTotals {
+            subtotal,
+            discount,
+            processing_fee,
+            total: subtotal - discount + processing_fee,
+        }))
+    }
+}
+
+/// Parse a price or percentage, which may not be negative
+pub(crate) fn parse_amount(value: &str) -> Result<Decimal> {
+    Decimal::from_str(value.trim())
+        .ok()
+        .filter(|amount| !amount.is_sign_negative())
+        .ok_or(Error::InvalidTicketPrice)
+}
+
+impl TicketSummary {
+    /// Summarise the tickets issued for an event
+    pub fn from_tickets(config: &TicketConfig, tickets: Vec<Ticket>) -> TicketSummary {
+        let mut member = TicketTierSummary {
+            max_tickets: TicketTier::Member.max_tickets(config),
+            ..Default::default()
+        };
+
+        let mut non_member = TicketTierSummary {
+            max_tickets: TicketTier::NonMember.max_tickets(config),
+            ..Default::default()
+        };
+
+        for ticket in &tickets {
+            let summary = match ticket.tier {
+                TicketTier::Member => &mut member,
+                TicketTier::NonMember => &mut non_member,
+            };
+
+            match ticket.status {
+                TicketStatus::Reserved => summary.reserved += ticket.quantity,
+                TicketStatus::Confirmed => summary.confirmed += ticket.quantity,
+                TicketStatus::Cancelled => summary.cancelled += ticket.quantity,
+            }
+        }
+
+        TicketSummary {
+            member,
+            non_member,
+            tickets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> TicketConfig {
+        TicketConfig {
+            is_paid: true,
+            member_price: Some("10.00".into()),
+            non_member_price: Some("15.50".into()),
+            processing_fee_percentage: Some("2.9".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn totals_use_the_price_of_the_tier() {
+        let member = TicketTier::Member
+            .totals(&config(), 2, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(member.subtotal, Decimal::from_str("20.00").unwrap());
+        assert_eq!(member.processing_fee, Decimal::from_str("0.58").unwrap());
+        assert_eq!(member.total, Decimal::from_str("20.58").unwrap());
+
+        let non_member = TicketTier::NonMember
+            .totals(&config(), 3, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(non_member.subtotal, Decimal::from_str("46.50").unwrap());
+        assert_eq!(
+            non_member.processing_fee,
+            Decimal::from_str("1.35").unwrap()
+        );
+        assert_eq!(non_member.total, Decimal::from_str("47.85").unwrap());
+    }
+
+    #[test]
+    fn totals_are_exact() {
+        let config = TicketConfig {
+            is_paid: true,
+            member_price: Some("0.10".into()),
+            ..Default::default()
+        };
+
+        let totals = TicketTier::Member
+            .totals(&config, 3, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(totals.total, Decimal::from_str("0.30").unwrap());
+    }
+
+    #[test]
+    fn discounts_come_off_before_the_processing_fee() {
+        let percentage = PromoDiscount::Percentage {
+            percentage: "25".into(),
+        };
+
+        let totals = TicketTier::Member
+            .totals(&config(), 2, Some(&percentage))
+            .unwrap()
+            .unwrap();
+        assert_eq!(totals.discount, Decimal::from_str("5.00").unwrap());
+        assert_eq!(totals.processing_fee, Decimal::from_str("0.44").unwrap());
+        assert_eq!(totals.total, Decimal::from_str("15.44").unwrap());
+
+        let fixed = PromoDiscount::Fixed {
+            amount: "50".into(),
+        };
+
+        let totals = TicketTier::Member
+            .totals(&config(), 2, Some(&fixed))
+            .unwrap()
+            .unwrap();
+        assert_eq!(totals.discount, Decimal::from_str("20.00").unwrap());
+        assert!(totals.total.is_zero());
+    }
+
+    #[test]
+    fn free_tickets_have_no_totals() {
+        let mut config = config();
+        config.member_price = None;
+        assert_eq!(TicketTier::Member.totals(&config, 1, None).unwrap(), None);
+
+        config.is_paid = false;
+        assert_eq!(
+            TicketTier::NonMember.totals(&config, 1, None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_prices_are_rejected() {
+        let mut config = config();
+        config.member_price = Some("ten".into());
+        assert!(TicketTier::Member.totals(&config, 1, None).is_err());
+
+        config.member_price = Some("-1".into());
+        assert!(TicketTier::Member.totals(&config, 1, None).is_err());
+    }
+}
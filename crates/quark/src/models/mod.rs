@@ -32,9 +32,19 @@ mod safety {
 }
 
 pub mod events {
+    pub mod analytics;
+    pub mod calendar;
     pub mod event;
+    pub mod feedback;
     pub mod guest;
+    pub mod message_job;
+    pub mod promo_code;
+    pub mod questionnaire;
+    pub mod reminder;
     pub mod saved_event;
+    pub mod series;
+    pub mod template;
+    pub mod ticket;
 }
 
 pub use admin::*;
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use crate::models::attachment::File;
 
 /// Composite primary key consisting of server and user id
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct MemberCompositeKey {
     /// Server Id
     pub server: String,
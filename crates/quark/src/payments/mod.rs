@@ -0,0 +1,100 @@
+//! Taking payments for event tickets
+use crate::models::events::ticket::TicketPayment;
+use crate::variables::delta::PAYMENT_PROVIDER;
+use crate::{Error, Result};
+
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+mod fake;
+
+pub use fake::FakeProvider;
+
+/// Payment to take from a buyer
+#[derive(Debug, Clone)]
+pub struct CheckoutRequest {
+    /// Our own reference for the payment, the ticket id
+    pub reference: String,
+    /// What the buyer is paying for
+    pub description: String,
+    /// Amount to charge
+    pub amount: Decimal,
+    /// Currency to charge in
+    pub currency: String,
+}
+
+/// Checkout session opened with a provider
+#[derive(Debug, Clone)]
+pub struct CheckoutSession {
+    /// Provider's id for the session
+    pub id: String,
+    /// Where the buyer completes the payment
+    pub url: String,
+}
+
+/// What a provider's webhook told us about a checkout session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum PaymentNotification {
+    /// The buyer paid
+    Completed { session_id: String },
+    /// Checkout failed or expired without payment
+    Failed { session_id: String },
+    /// The payment was refunded
+    Refunded { session_id: String },
+}
+
+impl PaymentNotification {
+    /// Checkout session the notification is about
+    pub fn session_id(&self) -> &str {
+        match self {
+            PaymentNotification::Completed { session_id }
+            | PaymentNotification::Failed { session_id }
+            | PaymentNotification::Refunded { session_id } => session_id,
+        }
+    }
+}
+
+/// Service which takes payments for tickets
+#[async_trait]
+pub trait PaymentProvider: Sync + Send {
+    /// Name stored on payments taken through this provider
+    fn name(&self) -> &'static str;
+
+    /// Open a checkout session for the buyer to pay through
+    async fn create_checkout(&self, request: &CheckoutRequest) -> Result<CheckoutSession>;
+
+    /// Check a webhook was sent by the provider and read what it says
+    fn verify_webhook(&self, signature: &str, body: &str) -> Result<PaymentNotification>;
+
+    /// Refund a completed payment in full, returning the provider's id for the refund
+    async fn refund(&self, payment: &TicketPayment) -> Result<String>;
+}
+
+static PROVIDER: Lazy<Option<Box<dyn PaymentProvider>>> =
+    Lazy::new(|| match PAYMENT_PROVIDER.as_deref()? {
+        #[cfg(debug_assertions)]
+        "fake" => Some(Box::new(FakeProvider)),
+        #[cfg(not(debug_assertions))]
+        "fake" => panic!("The fake payment provider is only available in debug builds."),
+        name => panic!("Unknown payment provider `{}`.", name),
+    });
+
+/// Resolve the configured payment provider, so a bad name fails on startup
+pub fn init() {
+    match provider() {
+        Ok(provider) => info!("Taking payments through the {} provider.", provider.name()),
+        Err(_) => warn!("No payment provider configured! Paid tickets cannot be reserved."),
+    }
+}
+
+/// Payment provider configured for this instance
+pub fn provider() -> Result<&'static dyn PaymentProvider> {
+    PROVIDER.as_deref().ok_or(Error::PaymentsUnavailable)
+}
+
+/// Whether checkouts are completed through the fake provider
+pub fn is_fake() -> bool {
+    matches!(provider(), Ok(provider) if provider.name() == "fake")
+}
@@ -0,0 +1,38 @@
+use num_enum::TryFromPrimitive;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::ops;
+
+/// Event permission definitions
+///
+/// Capabilities held by an event's staff, the creator always holds all of them.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, TryFromPrimitive, Copy, Clone, JsonSchema,
+)]
+#[repr(u32)]
+pub enum EventPermission {
+    /// Edit the event's details
+    EditEvent = 1 << 0,
+    /// Add guests, change their status and manage tickets
+    ManageGuests = 1 << 1,
+    /// Message and notify guests
+    MessageGuests = 1 << 2,
+    /// Check guests in at the door
+    CheckIn = 1 << 3,
+    /// View the event's analytics
+    ViewAnalytics = 1 << 4,
+}
+
+impl_op_ex!(+ |a: &EventPermission, b: &EventPermission| -> u32 { *a as u32 | *b as u32 });
+impl_op_ex_commutative!(+ |a: &u32, b: &EventPermission| -> u32 { *a | *b as u32 });
+
+pub static ALL_EVENT_PERMISSIONS: Lazy<u32> = Lazy::new(|| {
+    EventPermission::EditEvent
+        + EventPermission::ManageGuests
+        + EventPermission::MessageGuests
+        + EventPermission::CheckIn
+        + EventPermission::ViewAnalytics
+});
+pub static DEFAULT_EVENT_PERMISSION_HOST: Lazy<u32> = Lazy::new(|| *ALL_EVENT_PERMISSIONS);
+pub static DEFAULT_EVENT_PERMISSION_MANAGER: Lazy<u32> =
+    Lazy::new(|| EventPermission::ManageGuests + EventPermission::CheckIn);
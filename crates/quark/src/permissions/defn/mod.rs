@@ -1,7 +1,9 @@
+mod event;
 mod permission;
 mod user;
 
 use bson::Bson;
+pub use event::*;
 pub use permission::*;
 pub use user::*;
 
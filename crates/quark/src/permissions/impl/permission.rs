@@ -144,26 +144,34 @@ async fn calculate_channel_permission(
             }
         }
         Channel::Group {
-            owner,
             permissions,
             recipients,
+            archived,
             ..
         } => {
-            // 2. Check if user is owner.
-            if &data.perspective.id == owner {
-                (Permission::GrantAllSafe as u64).into()
-            } else {
-                // 3. Check that we are actually in the group.
-                if recipients.contains(&data.perspective.id) {
-                    // 4. Pull out group permissions.
-                    permissions
-                        .map(|x| x as u64)
-                        .unwrap_or(*DEFAULT_PERMISSION_DIRECT_MESSAGE)
-                        .into()
+            // 2. Check if user is owner (or staff of the group's event).
+            let mut value: PermissionValue =
+                if channel.is_group_owner(db, &data.perspective.id).await? {
+                    (Permission::GrantAllSafe as u64).into()
                 } else {
-                    0_u64.into()
-                }
+                    // 3. Check that we are actually in the group.
+                    if recipients.contains(&data.perspective.id) {
+                        // 4. Pull out group permissions.
+                        permissions
+                            .map(|x| x as u64)
+                            .unwrap_or(*DEFAULT_PERMISSION_DIRECT_MESSAGE)
+                            .into()
+                    } else {
+                        0_u64.into()
+                    }
+                };
+
+            // 5. Archived groups are read-only.
+            if *archived {
+                value.restrict(*DEFAULT_PERMISSION_VIEW_ONLY);
             }
+
+            value
         }
         Channel::TextChannel {
             default_permissions,
@@ -0,0 +1,55 @@
+//! Archive event group channels once their event is over
+use crate::models::channel::{Channel, PartialChannel};
+use crate::models::event::{EventStatus, EventViewer};
+use crate::{Database, Error, Result};
+
+use async_std::task;
+use chrono::Utc;
+
+/// Seconds to wait between checks for finished events
+static POLL_INTERVAL: u64 = 300;
+
+/// Start a new worker
+pub async fn worker(db: Database) {
+    loop {
+        if let Err(err) = archive_finished(&db).await {
+            error!("Failed to archive event groups! {:?}", err);
+        }
+
+        task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await;
+    }
+}
+
+/// Archive every event group whose event has ended, was cancelled or no longer exists
+async fn archive_finished(db: &Database) -> Result<()> {
+    let now = Utc::now();
+    for mut channel in db.fetch_unarchived_event_groups().await? {
+        let event_id = match &channel {
+            Channel::Group {
+                event: Some(event), ..
+            } => event.clone(),
+            _ => continue,
+        };
+
+        let ended = match db.fetch_event(EventViewer::System, &event_id).await {
+            Ok(event) => event.end_date <= now || event.status == EventStatus::Cancelled,
+            Err(Error::NotFound) => true,
+            Err(err) => return Err(err),
+        };
+
+        if ended {
+            channel
+                .update(
+                    db,
+                    PartialChannel {
+                        archived: Some(true),
+                        ..Default::default()
+                    },
+                    vec![],
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Publish scheduled drafts and complete events once they are over
+use crate::events::client::EventV1;
+use crate::models::event::{Event, EventStatus, PartialEvent};
+use crate::{Database, Result};
+
+use async_std::task;
+use chrono::Utc;
+
+/// Seconds to wait between checks for events changing status
+static POLL_INTERVAL: u64 = 60;
+
+/// Start a new worker
+pub async fn worker(db: Database) {
+    loop {
+        if let Err(err) = update_statuses(&db).await {
+            error!("Failed to update event statuses! {:?}", err);
+        }
+
+        task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await;
+    }
+}
+
+/// Move an event to a new status and let its subscribers know
+pub async fn set_status(db: &Database, event: &Event, status: EventStatus) -> Result<()> {
+    let partial = PartialEvent {
+        status: Some(status),
+        // Let calendar subscribers know the event changed
+        sequence: Some(event.sequence + 1),
+        ..Default::default()
+    };
+
+    db.update_event(&event.id, &partial).await?;
+
+    EventV1::EventUpdate {
+        id: event.id.clone(),
+        data: partial,
+    }
+    .p(event.id.clone())
+    .await;
+
+    Ok(())
+}
+
+/// Publish drafts which are due and complete events which have ended
+async fn update_statuses(db: &Database) -> Result<()> {
+    let now = Utc::now();
+
+    for event in db.fetch_scheduled_events(now).await? {
+        set_status(db, &event, EventStatus::Published).await?;
+    }
+
+    for event in db.fetch_ended_events(now).await? {
+        set_status(db, &event, EventStatus::Completed).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! Send queued messages to an event's guests
+use crate::authifier::config::{EmailVerificationConfig, Template};
+use crate::models::channel::{Channel, PartialChannel};
+use crate::models::event::{Event, EventViewer};
+use crate::models::events::message_job::{
+    DeliveryStatus, EventMessageJob, MessageJobKind, MessageJobStatus, MessageRecipient,
+};
+use crate::models::message::Message;
+use crate::models::user::User;
+use crate::types::push::MessageAuthor;
+use crate::util::authifier::config;
+use crate::util::templates::{EVENT_HTML, EVENT_TEXT};
+use crate::{Database, Error, Result};
+
+use async_std::task;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use ulid::Ulid;
+
+/// Seconds to wait between checks for due jobs
+static POLL_INTERVAL: u64 = 10;
+
+/// Milliseconds to wait between recipients, so large lists don't flood the mail server
+static SEND_INTERVAL: u64 = 250;
+
+/// Number of recipients to get through before saving a job's progress
+static BATCH_SIZE: usize = 20;
+
+/// Attempts made at sending to a recipient before giving up on them
+static MAX_ATTEMPTS: u32 = 3;
+
+/// Minutes to wait before retrying recipients which failed
+static RETRY_DELAY: i64 = 5;
+
+/// Minutes a claimed job is held for before it is picked up again, in case the worker stopped
+static CLAIM_TIMEOUT: i64 = 10;
+
+/// Queue up messages to an event's guests
+pub async fn queue(
+    db: &Database,
+    event: &Event,
+    user: &User,
+    kind: MessageJobKind,
+    title: String,
+    content: String,
+    recipients: Vec<MessageRecipient>,
+) -> Result<EventMessageJob> {
+    let now = Utc::now();
+    let job = EventMessageJob {
+        id: Ulid::new().to_string(),
+        event_id: event.id.clone(),
+        created_by: user.id.clone(),
+        kind,
+        title,
+        content,
+        recipients,
+        status: MessageJobStatus::Queued,
+        created_at: now,
+        run_at: now,
+    };
+
+    db.insert_event_message_job(&job).await?;
+    Ok(job)
+}
+
+/// Start a new worker
+pub async fn worker(db: Database) {
+    loop {
+        let now = Utc::now();
+        match db
+            .claim_due_event_message_job(now, now + Duration::minutes(CLAIM_TIMEOUT))
+            .await
+        {
+            Ok(Some(job)) => {
+                let id = job.id.clone();
+                if let Err(err) = run(&db, job).await {
+                    error!("Failed to run message job {}! {:?}", id, err);
+                }
+            }
+            Ok(None) => task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await,
+            Err(err) => {
+                error!("Failed to fetch due message jobs! {:?}", err);
+                task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await;
+            }
+        }
+    }
+}
+
+/// Work through the pending recipients of a job
+async fn run(db: &Database, mut job: EventMessageJob) -> Result<()> {
+    job.status = MessageJobStatus::Running;
+
+    let (event, sender) = match resolve(db, &job).await {
+        Ok(resolved) => resolved,
+        Err(Error::NotFound) => {
+            // Nothing can be sent once the event or sender is gone
+            for recipient in job
+                .recipients
+                .iter_mut()
+                .filter(|recipient| recipient.status == DeliveryStatus::Pending)
+            {
+                recipient.status = DeliveryStatus::Failed;
+                recipient.error = Some("Event or sender no longer exists".into());
+            }
+
+            job.status = MessageJobStatus::Completed;
+            return db.update_event_message_job(&job).await;
+        }
+        Err(err) => return Err(err),
+    };
+
+    let accounts: authifier::Database = db.clone().into();
+    let mut invited = 0;
+    let mut unsaved = 0;
+
+    for index in 0..job.recipients.len() {
+        if job.recipients[index].status != DeliveryStatus::Pending {
+            continue;
+        }
+
+        let mut recipient = job.recipients[index].clone();
+        let result = send(db, &accounts, &sender, &event, &job, &mut recipient).await;
+
+        recipient.attempts += 1;
+        match result {
+            Ok(_) => {
+                recipient.status = DeliveryStatus::Sent;
+                recipient.error = None;
+
+                if job.kind == MessageJobKind::Invitation {
+                    invited += 1;
+                }
+            }
+            Err(err) => {
+                if recipient.attempts >= MAX_ATTEMPTS {
+                    recipient.status = DeliveryStatus::Failed;
+                }
+
+                recipient.error = Some(err);
+            }
+        }
+
+        job.recipients[index] = recipient;
+        unsaved += 1;
+        if unsaved >= BATCH_SIZE {
+            job.run_at = Utc::now() + Duration::minutes(CLAIM_TIMEOUT);
+            save(db, &job, &mut invited).await?;
+            unsaved = 0;
+        }
+
+        task::sleep(std::time::Duration::from_millis(SEND_INTERVAL)).await;
+    }
+
+    if job.count(&DeliveryStatus::Pending) > 0 {
+        job.run_at = Utc::now() + Duration::minutes(RETRY_DELAY);
+    } else {
+        job.status = MessageJobStatus::Completed;
+    }
+
+    save(db, &job, &mut invited).await?;
+
+    info!(
+        "Worked through message job {} for event {}: {} sent, {} failed, {} pending.",
+        job.id,
+        job.event_id,
+        job.count(&DeliveryStatus::Sent),
+        job.count(&DeliveryStatus::Failed),
+        job.count(&DeliveryStatus::Pending)
+    );
+
+    Ok(())
+}
+
+/// Fetch the event and sender of a job
+async fn resolve(db: &Database, job: &EventMessageJob) -> Result<(Event, User)> {
+    let event = db.fetch_event(EventViewer::System, &job.event_id).await?;
+    let sender = db.fetch_user(&job.created_by).await?;
+    Ok((event, sender))
+}
+
+/// Save a job's progress, counting sent invitations towards the event
+async fn save(db: &Database, job: &EventMessageJob, invited: &mut i32) -> Result<()> {
+    if *invited > 0 {
+        db.increment_event_invited_count(&job.event_id, *invited)
+            .await?;
+
+        *invited = 0;
+    }
+
+    db.update_event_message_job(job).await
+}
+
+/// Send a single recipient their DM and email
+///
+/// Each is only sent once, so retrying a recipient only retries the one which failed.
+async fn send(
+    db: &Database,
+    accounts: &authifier::Database,
+    sender: &User,
+    event: &Event,
+    job: &EventMessageJob,
+    recipient: &mut MessageRecipient,
+) -> std::result::Result<(), String> {
+    let content = job.content_for(recipient);
+
+    if !recipient.dm_sent {
+        if let Some(user_id) = recipient.user_id.as_ref().filter(|id| *id != &sender.id) {
+            let message = format!("{}\n\n{}", content, recipient.url);
+            send_direct_message(db, sender, user_id, &job.kind, message)
+                .await
+                .map_err(|err| format!("Failed to send DM: {:?}", err))?;
+        }
+
+        recipient.dm_sent = true;
+    }
+
+    if recipient.email_sent {
+        return Ok(());
+    }
+
+    let email = match (&recipient.email, &recipient.user_id) {
+        (Some(email), _) => Some(email.clone()),
+        (None, Some(user_id)) => accounts
+            .find_account(user_id)
+            .await
+            .ok()
+            .map(|account| account.email),
+        (None, None) => None,
+    };
+
+    if let (Some(email), EmailVerificationConfig::Enabled { smtp, .. }) = (
+        email.filter(|email| !email.is_empty()),
+        config().email_verification,
+    ) {
+        smtp.send_email(
+            email.clone(),
+            &Template {
+                title: job.title.clone(),
+                text: EVENT_TEXT.into(),
+                url: recipient.url.clone(),
+                html: Some(EVENT_HTML.into()),
+            },
+            json!({
+                "email": email,
+                "url": recipient.url.clone(),
+                "title": event.title.clone(),
+                "content": content,
+            }),
+        )
+        .map_err(|err| format!("Failed to send email: {:?}", err))?;
+    }
+
+    recipient.email_sent = true;
+    Ok(())
+}
+
+/// Find or open a DM with the recipient and post the message to it
+///
+/// Invitations open the DM and notify the recipient like any other message,
+/// notifications are only stored.
+async fn send_direct_message(
+    db: &Database,
+    sender: &User,
+    user_id: &str,
+    kind: &MessageJobKind,
+    content: String,
+) -> Result<()> {
+    let is_invitation = kind == &MessageJobKind::Invitation;
+    let channel = match db.find_direct_message_channel(&sender.id, user_id).await {
+        Ok(channel) => channel,
+        Err(_) => {
+            let channel = Channel::DirectMessage {
+                id: Ulid::new().to_string(),
+                active: is_invitation,
+                recipients: vec![sender.id.clone(), user_id.to_string()],
+                last_message_id: None,
+            };
+
+            channel.create(db).await?;
+            channel
+        }
+    };
+
+    let mut message = Message {
+        id: Ulid::new().to_string(),
+        channel: channel.id().to_string(),
+        author: sender.id.clone(),
+        content: Some(content),
+        ..Default::default()
+    };
+
+    if !is_invitation {
+        return db.insert_message(&message).await;
+    }
+
+    message
+        .create(db, &channel, Some(MessageAuthor::User(sender)))
+        .await?;
+
+    if let Channel::DirectMessage { active: false, .. } = &channel {
+        db.update_channel(
+            channel.id(),
+            &PartialChannel {
+                active: Some(true),
+                ..Default::default()
+            },
+            vec![],
+        )
+        .await?;
+    }
+
+    Ok(())
+}
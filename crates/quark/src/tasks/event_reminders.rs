@@ -0,0 +1,185 @@
+//! Remind approved guests of upcoming events
+use crate::authifier::config::{EmailVerificationConfig, Template};
+use crate::models::event::{Event, EventStatus, EventViewer};
+use crate::models::events::guest::GuestStatus;
+use crate::models::events::reminder::EventReminder;
+use crate::types::push::PushNotification;
+use crate::util::authifier::config;
+use crate::util::templates::{EVENT_HTML, EVENT_TEXT};
+use crate::util::variables::delta::{APP_URL, AUTUMN_URL, EVENT_REMINDER_OFFSETS};
+use crate::{Database, Error, Result};
+
+use async_std::task;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use std::time::SystemTime;
+
+/// Seconds to wait between checks for due reminders
+static POLL_INTERVAL: u64 = 60;
+
+/// Minutes to wait before retrying a reminder which failed to send
+static RETRY_DELAY: i64 = 10;
+
+/// Attempts made at sending a reminder before giving up on it
+static MAX_ATTEMPTS: u32 = 3;
+
+/// Schedule the reminders for an event, replacing any pending ones
+pub async fn schedule(db: &Database, event: &Event) -> Result<()> {
+    let now = Utc::now();
+    let reminders: Vec<EventReminder> = if event.disable_reminders {
+        vec![]
+    } else {
+        EVENT_REMINDER_OFFSETS
+            .iter()
+            .map(|offset| EventReminder {
+                id: format!("{}:{}", event.id, offset),
+                event_id: event.id.clone(),
+                offset: *offset,
+                send_at: event.start_date - Duration::minutes(*offset),
+                attempts: 0,
+            })
+            .filter(|reminder| reminder.send_at > now)
+            .collect()
+    };
+
+    db.replace_event_reminders(&event.id, &reminders).await
+}
+
+/// Start a new worker
+pub async fn worker(db: Database) {
+    loop {
+        let now = Utc::now();
+        let retry_at = now + Duration::minutes(RETRY_DELAY);
+        match db.claim_due_event_reminder(now, retry_at).await {
+            Ok(Some(mut reminder)) => match send(&db, &reminder).await {
+                // Reminders of drafts are kept for when the event is published
+                Ok(false) => {}
+                Ok(true) => {
+                    if let Err(err) = db.delete_event_reminder(&reminder.id).await {
+                        error!("Failed to clear sent reminder {}! {:?}", reminder.id, err);
+                    }
+                }
+                Err(err) => {
+                    error!("Failed to send reminder {}! {:?}", reminder.id, err);
+
+                    reminder.attempts += 1;
+                    reminder.send_at = retry_at;
+                    let result = if reminder.attempts >= MAX_ATTEMPTS {
+                        info!(
+                            "Dropping reminder {} after {} attempts.",
+                            reminder.id, reminder.attempts
+                        );
+
+                        db.delete_event_reminder(&reminder.id).await
+                    } else {
+                        db.update_event_reminder(&reminder).await
+                    };
+
+                    if let Err(err) = result {
+                        error!("Failed to save reminder {}! {:?}", reminder.id, err);
+                    }
+                }
+            },
+            Ok(None) => task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await,
+            Err(err) => {
+                error!("Failed to fetch due reminders! {:?}", err);
+                task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await;
+            }
+        }
+    }
+}
+
+/// Send a reminder to every approved guest of its event
+///
+/// Returns whether the reminder is done with, reminders of drafts are
+/// held on to until the event is published or has started.
+async fn send(db: &Database, reminder: &EventReminder) -> Result<bool> {
+    let event = match db
+        .fetch_event(EventViewer::System, &reminder.event_id)
+        .await
+    {
+        Ok(event) => event,
+        Err(Error::NotFound) => return Ok(true),
+        Err(err) => return Err(err),
+    };
+
+    // Reminders may have been turned off or the event moved since scheduling
+    if event.disable_reminders || event.start_date <= Utc::now() {
+        return Ok(true);
+    }
+
+    // Drafts may still be published, cancelled events have nobody to remind
+    if event.status != EventStatus::Published {
+        return Ok(event.status != EventStatus::Draft);
+    }
+
+    let guests: Vec<_> = db
+        .get_event_guests(&event.id)
+        .await?
+        .into_iter()
+        .filter(|guest| guest.status == GuestStatus::Approved)
+        .collect();
+
+    let url = format!("{}/events/view/{}", *APP_URL, event.id);
+    let body = format!(
+        "{} starts {}",
+        event.title,
+        event
+            .start_date
+            .with_timezone(&event.tz())
+            .format("on %A %-d %B at %H:%M %Z")
+    );
+
+    crate::tasks::web_push::queue(
+        guests
+            .iter()
+            .filter_map(|guest| guest.user_id.clone())
+            .collect(),
+        json!(PushNotification {
+            author: event.title.clone(),
+            icon: format!("{}/assets/logo.png", *APP_URL),
+            image: event
+                .thumbnail
+                .as_ref()
+                .map(|id| format!("{}/attachments/{}", *AUTUMN_URL, id)),
+            body: body.clone(),
+            tag: format!("event-reminder-{}", event.id),
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs(),
+            url: url.clone(),
+        })
+        .to_string(),
+    )
+    .await;
+
+    if let EmailVerificationConfig::Enabled { smtp, .. } = config().email_verification {
+        for guest in guests.iter().filter(|guest| !guest.email.is_empty()) {
+            smtp.send_email(
+                guest.email.clone(),
+                &Template {
+                    title: format!("Reminder: {}", event.title),
+                    text: EVENT_TEXT.into(),
+                    url: url.clone(),
+                    html: Some(EVENT_HTML.into()),
+                },
+                json!({
+                    "email": guest.email.clone(),
+                    "url": url.clone(),
+                    "title": event.title.clone(),
+                    "content": format!("This is a reminder that {}.", body),
+                }),
+            );
+        }
+    }
+
+    info!(
+        "Sent {} minute reminder for event {} to {} guests.",
+        reminder.offset,
+        event.id,
+        guests.len()
+    );
+
+    Ok(true)
+}
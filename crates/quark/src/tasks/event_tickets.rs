@@ -0,0 +1,43 @@
+//! Release reservations which were never paid for
+use crate::{Database, Error, Result};
+
+use async_std::task;
+use chrono::{Duration, Utc};
+
+/// Seconds to wait between checks for expired reservations
+static POLL_INTERVAL: u64 = 60;
+
+/// Minutes a buyer has to pay for a reservation before its seats are released
+pub static RESERVATION_TIMEOUT: i64 = 30;
+
+/// Start a new worker
+pub async fn worker(db: Database) {
+    loop {
+        if let Err(err) = expire_reservations(&db).await {
+            error!("Failed to expire ticket reservations! {:?}", err);
+        }
+
+        task::sleep(std::time::Duration::from_secs(POLL_INTERVAL)).await;
+    }
+}
+
+/// Cancel reservations left unpaid for too long, giving back their seats and promo codes
+///
+/// Payments which still come through afterwards are refunded when their webhook arrives.
+async fn expire_reservations(db: &Database) -> Result<()> {
+    let before = Utc::now() - Duration::minutes(RESERVATION_TIMEOUT);
+
+    for ticket in db.fetch_expired_reservations(before).await? {
+        match db.expire_reservation(&ticket.event_id, &ticket.id).await {
+            Ok(()) => {
+                db.release_promo_redemption(&ticket.event_id, &ticket.id)
+                    .await?
+            }
+            // Confirmed or cancelled since it was fetched
+            Err(Error::InvalidOperation) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
@@ -8,6 +8,11 @@ use std::time::Instant;
 const WORKER_COUNT: usize = 5;
 
 pub mod ack;
+pub mod event_channels;
+pub mod event_lifecycle;
+pub mod event_messages;
+pub mod event_reminders;
+pub mod event_tickets;
 pub mod last_message_id;
 pub mod process_embeds;
 pub mod web_push;
@@ -20,6 +25,12 @@ pub async fn start_workers(db: Database) {
         task::spawn(process_embeds::worker(db.clone()));
         task::spawn(web_push::worker(db.clone().into()));
     }
+
+    task::spawn(event_reminders::worker(db.clone()));
+    task::spawn(event_channels::worker(db.clone()));
+    task::spawn(event_lifecycle::worker(db.clone()));
+    task::spawn(event_messages::worker(db.clone()));
+    task::spawn(event_tickets::worker(db.clone()));
 }
 
 /// Task with additional information on when it should run
@@ -1,35 +1,272 @@
-use crate::models::event::{Event, PartialEvent};
-use crate::models::events::guest::{EventGuest, GuestStatus};
+use crate::models::channel::Channel;
+use crate::models::event::{Event, EventQuery, EventStatus, EventViewer, PartialEvent};
+use crate::models::events::analytics::{EventActivityDay, EventActivityKind};
+use crate::models::events::calendar::CalendarFeed;
+use crate::models::events::feedback::EventFeedback;
+use crate::models::events::guest::{EventGuest, GuestStatus, GuestStatusChange};
+use crate::models::events::message_job::EventMessageJob;
+use crate::models::events::promo_code::{PromoCode, PromoRedemption};
+use crate::models::events::reminder::EventReminder;
+use crate::models::events::series::EventSeries;
+use crate::models::events::template::EventTemplate;
+use crate::models::events::ticket::{Ticket, TicketPayment};
 use crate::models::user::User;
 use crate::Result;
+use chrono::{DateTime, Utc};
 
 #[async_trait]
 pub trait AbstractEvents: Sync + Send {
-    async fn fetch_event(&self, user_id: Option<&str>, id: &str) -> Result<Event>;
+    /// Fetch an event as the viewer may see it
+    ///
+    /// Fails with `NotFound` if the event is hidden from the viewer.
+    async fn fetch_event(&self, viewer: EventViewer<'_>, id: &str) -> Result<Event>;
+    /// Fetch events by their ids, leaving out any hidden from the viewer
     async fn fetch_events<'a>(
         &self,
-        user_id: Option<&str>,
+        viewer: EventViewer<'_>,
         ids: &'a [String],
     ) -> Result<Vec<Event>>;
+    /// Fetch a page of events visible to the viewer matching the given query, along with the total number of matches
+    async fn query_events(
+        &self,
+        viewer: EventViewer<'_>,
+        query: &EventQuery,
+    ) -> Result<(Vec<Event>, u64)>;
     async fn insert_event(&self, event: &Event) -> Result<()>;
     async fn update_event(&self, id: &str, event: &PartialEvent) -> Result<()>;
     async fn delete_event(&self, id: &str) -> Result<()>;
     async fn toggle_saved_event(&self, user_id: &str, event_id: &str) -> Result<(Event, bool)>;
     async fn is_event_saved(&self, user_id: &str, event_id: &str) -> Result<bool>;
-    async fn get_saved_events(&self, user_id: &str) -> Result<Vec<Event>>;
+    /// Get a user's saved events, every status but drafts unless a status is given
+    async fn get_saved_events(
+        &self,
+        user_id: &str,
+        status: Option<&EventStatus>,
+    ) -> Result<Vec<Event>>;
     /// Get all events created by a user
     async fn get_user_events(&self, user_id: &str) -> Result<Vec<Event>>;
+    /// Insert a new event series
+    async fn insert_event_series(&self, series: &EventSeries) -> Result<()>;
+    /// Fetch an event series by its id
+    async fn fetch_event_series(&self, id: &str) -> Result<EventSeries>;
+    /// Fetch all occurrences of a series visible to the viewer, earliest first
+    async fn fetch_series_events(
+        &self,
+        viewer: EventViewer<'_>,
+        series_id: &str,
+    ) -> Result<Vec<Event>>;
+    /// Get ids of all events a user is an approved guest of
+    async fn fetch_attending_event_ids(&self, user_id: &str) -> Result<Vec<String>>;
+    /// Get ids of all events a user created, hosts or manages
+    async fn fetch_staffed_event_ids(&self, user_id: &str) -> Result<Vec<String>>;
+    /// Get all events a user saved, is an approved guest of or hosts
+    async fn fetch_calendar_events(&self, user_id: &str) -> Result<Vec<Event>>;
     /// Add a guest to an event
     async fn add_guest(&self, guest: &EventGuest) -> Result<()>;
-    /// Update guest status
+    /// Move a guest from one status to another
+    ///
+    /// Returns whether the guest was moved, which it is not if its
+    /// status is no longer `from`.
     async fn update_guest_status(
         &self,
         event_id: &str,
         guest_id: &str,
-        status: GuestStatus,
-    ) -> Result<()>;
+        from: GuestStatus,
+        to: GuestStatus,
+    ) -> Result<bool>;
+    /// Atomically take seats at an event
+    ///
+    /// Returns whether the seats were taken, which they are not if they
+    /// would take the event past `capacity`.
+    async fn take_event_seats(&self, id: &str, seats: i32, capacity: Option<i32>) -> Result<bool>;
+    /// Give back seats taken at an event
+    async fn release_event_seats(&self, id: &str, seats: i32) -> Result<()>;
+    /// Update a guest's contact details
+    async fn update_guest_details(&self, guest: &EventGuest) -> Result<()>;
+    /// Record a change to a guest's status
+    async fn insert_guest_status_change(&self, change: &GuestStatusChange) -> Result<()>;
+    /// Fetch the status history of a guest, oldest first
+    async fn fetch_guest_status_history(
+        &self,
+        event_id: &str,
+        guest_id: &str,
+    ) -> Result<Vec<GuestStatusChange>>;
+    /// Fetch the status history of every guest of an event, oldest first
+    async fn fetch_event_status_history(&self, event_id: &str) -> Result<Vec<GuestStatusChange>>;
     /// Get event guests
     async fn get_event_guests(&self, event_id: &str) -> Result<Vec<EventGuest>>;
     /// Get guest by ID
     async fn get_guest(&self, event_id: &str, guest_id: &str) -> Result<EventGuest>;
+    /// Mark an approved guest as checked in
+    ///
+    /// Fails with `AlreadyCheckedIn` if the guest was checked in before.
+    async fn check_in_guest(
+        &self,
+        event_id: &str,
+        guest_id: &str,
+        checked_in_by: &str,
+    ) -> Result<()>;
+
+    /// Reserve a ticket, holding its seats against the tier limit
+    ///
+    /// Fails with `TicketsSoldOut` if the seats would exceed `max_tickets`,
+    /// or with `TooManyTickets` if `single` is set and the user already
+    /// holds a ticket which was not cancelled.
+    async fn reserve_ticket(
+        &self,
+        ticket: &Ticket,
+        max_tickets: Option<i32>,
+        single: bool,
+    ) -> Result<()>;
+
+    /// Fetch a ticket by its id
+    async fn fetch_ticket(&self, event_id: &str, ticket_id: &str) -> Result<Ticket>;
+
+    /// Fetch all tickets issued for an event
+    async fn fetch_tickets(&self, event_id: &str) -> Result<Vec<Ticket>>;
+
+    /// Fetch all tickets a user holds for an event
+    async fn fetch_user_tickets(&self, event_id: &str, user_id: &str) -> Result<Vec<Ticket>>;
+
+    /// Confirm a reserved ticket
+    async fn confirm_ticket(&self, event_id: &str, ticket_id: &str) -> Result<()>;
+
+    /// Cancel a ticket and release its seats
+    async fn cancel_ticket(&self, event_id: &str, ticket_id: &str) -> Result<()>;
+
+    /// Fetch reserved tickets created before the given time, across all events
+    async fn fetch_expired_reservations(&self, before: DateTime<Utc>) -> Result<Vec<Ticket>>;
+
+    /// Cancel a ticket and release its seats, only if it is still reserved
+    async fn expire_reservation(&self, event_id: &str, ticket_id: &str) -> Result<()>;
+
+    /// Record the payment taken for a ticket
+    async fn set_ticket_payment(
+        &self,
+        event_id: &str,
+        ticket_id: &str,
+        payment: &TicketPayment,
+    ) -> Result<()>;
+
+    /// Fetch the ticket paid for through a checkout session
+    async fn fetch_ticket_by_payment(&self, session_id: &str) -> Result<Ticket>;
+
+    /// Create a promo code
+    async fn insert_promo_code(&self, promo_code: &PromoCode) -> Result<()>;
+
+    /// Fetch a promo code of an event by the code buyers enter
+    async fn fetch_promo_code(&self, event_id: &str, code: &str) -> Result<PromoCode>;
+
+    /// Fetch all promo codes of an event
+    async fn fetch_promo_codes(&self, event_id: &str) -> Result<Vec<PromoCode>>;
+
+    /// Delete a promo code
+    async fn delete_promo_code(&self, event_id: &str, id: &str) -> Result<()>;
+
+    /// Count a use of a promo code, failing with `PromoCodeUsedUp` if none are left
+    async fn redeem_promo_code(&self, promo_code: &PromoCode) -> Result<()>;
+
+    /// Record a ticket bought with a promo code
+    async fn insert_promo_redemption(&self, redemption: &PromoRedemption) -> Result<()>;
+
+    /// Fetch all redemptions of an event's promo codes
+    async fn fetch_promo_redemptions(&self, event_id: &str) -> Result<Vec<PromoRedemption>>;
+
+    /// Give back the use of a promo code redeemed for a ticket, if one was
+    async fn release_promo_redemption(&self, event_id: &str, ticket_id: &str) -> Result<()>;
+
+    /// Save an event template
+    async fn insert_event_template(&self, template: &EventTemplate) -> Result<()>;
+
+    /// Fetch an event template owned by a user
+    async fn fetch_event_template(&self, user_id: &str, id: &str) -> Result<EventTemplate>;
+
+    /// Fetch all event templates owned by a user
+    async fn fetch_event_templates(&self, user_id: &str) -> Result<Vec<EventTemplate>>;
+
+    /// Delete an event template owned by a user
+    async fn delete_event_template(&self, user_id: &str, id: &str) -> Result<()>;
+
+    /// Create or replace a guest's feedback on an event
+    async fn save_event_feedback(&self, feedback: &EventFeedback) -> Result<()>;
+
+    /// Fetch the feedback a guest left on an event
+    async fn fetch_event_feedback(&self, event_id: &str, guest_id: &str) -> Result<EventFeedback>;
+
+    /// Fetch all feedback left on an event
+    async fn fetch_event_feedbacks(&self, event_id: &str) -> Result<Vec<EventFeedback>>;
+
+    /// Count engagement on an event towards the given day (YYYY-MM-DD)
+    async fn record_event_activity(
+        &self,
+        event_id: &str,
+        date: &str,
+        kind: EventActivityKind,
+    ) -> Result<()>;
+
+    /// Fetch the engagement counted on an event, per day
+    async fn fetch_event_activity(&self, event_id: &str) -> Result<Vec<EventActivityDay>>;
+
+    /// Count the users who have an event saved
+    async fn count_event_saves(&self, event_id: &str) -> Result<i32>;
+
+    /// Fetch a user's calendar feed
+    async fn fetch_calendar_feed(&self, user_id: &str) -> Result<CalendarFeed>;
+
+    /// Fetch a calendar feed by its secret token
+    async fn fetch_calendar_feed_by_token(&self, token: &str) -> Result<CalendarFeed>;
+
+    /// Create or replace a user's calendar feed
+    async fn save_calendar_feed(&self, feed: &CalendarFeed) -> Result<()>;
+
+    /// Revoke a user's calendar feed
+    async fn delete_calendar_feed(&self, user_id: &str) -> Result<()>;
+
+    /// Replace the pending reminders of an event
+    async fn replace_event_reminders(
+        &self,
+        event_id: &str,
+        reminders: &[EventReminder],
+    ) -> Result<()>;
+
+    /// Claim a reminder which is due, pushing it back to `retry_at` in case it is not sent
+    async fn claim_due_event_reminder(
+        &self,
+        now: DateTime<Utc>,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<EventReminder>>;
+
+    /// Update a reminder which failed to send
+    async fn update_event_reminder(&self, reminder: &EventReminder) -> Result<()>;
+
+    /// Delete a reminder once it was sent
+    async fn delete_event_reminder(&self, id: &str) -> Result<()>;
+
+    /// Queue up a message job
+    async fn insert_event_message_job(&self, job: &EventMessageJob) -> Result<()>;
+
+    /// Fetch a message job of an event
+    async fn fetch_event_message_job(&self, event_id: &str, id: &str) -> Result<EventMessageJob>;
+
+    /// Claim a message job which is due, pushing it back to `retry_at` in case the worker stops
+    async fn claim_due_event_message_job(
+        &self,
+        now: DateTime<Utc>,
+        retry_at: DateTime<Utc>,
+    ) -> Result<Option<EventMessageJob>>;
+
+    /// Save the progress of a message job
+    async fn update_event_message_job(&self, job: &EventMessageJob) -> Result<()>;
+
+    /// Atomically add sent invitations to an event's invited count
+    async fn increment_event_invited_count(&self, id: &str, count: i32) -> Result<()>;
+
+    /// Fetch the group channels of events which have not been archived yet
+    async fn fetch_unarchived_event_groups(&self) -> Result<Vec<Channel>>;
+
+    /// Fetch drafts which are due to be published
+    async fn fetch_scheduled_events(&self, now: DateTime<Utc>) -> Result<Vec<Event>>;
+
+    /// Fetch published events which have ended
+    async fn fetch_ended_events(&self, now: DateTime<Utc>) -> Result<Vec<Event>>;
 }
@@ -5,5 +5,7 @@ pub mod pfp;
 pub mod r#ref;
 pub mod regex;
 pub mod result;
+pub mod signing;
+pub mod templates;
 pub mod value;
 pub mod variables;
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use validator::ValidationErrors;
 
-use crate::{Permission, UserPermission};
+use crate::{EventPermission, Permission, UserPermission};
 /// Possible API Errors
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(tag = "type")]
@@ -87,6 +87,43 @@ pub enum Error {
     // ? User safety related errors
     CannotReportYourself,
 
+    // ? Event related errors
+    TicketsSoldOut,
+    TooManyTickets {
+        max: usize,
+    },
+    InvalidCheckInPass,
+    CheckInPassForOtherEvent,
+    AlreadyCheckedIn,
+    InvalidGuestStatusTransition {
+        from: String,
+        to: String,
+    },
+    AlreadyRsvped,
+    PlusOnesNotAllowed,
+    TooManyPlusOnes {
+        max: usize,
+    },
+    MissingPlusOneInfo,
+    EventChannelExists,
+    InvalidInvitation,
+    InvitationExpired,
+    EventNotOpen,
+    InvalidEventStatusTransition {
+        from: String,
+        to: String,
+    },
+    InvalidTicketPrice,
+    PaymentFailed,
+    PaymentsUnavailable,
+    InvalidPaymentWebhook,
+    InvalidPromoCode,
+    PromoCodeExpired,
+    PromoCodeUsedUp,
+    PromoCodeExists,
+    FeedbackNotOpen,
+    NotEventAttendee,
+
     // ? Permission errors
     MissingPermission {
         permission: Permission,
@@ -94,6 +131,9 @@ pub enum Error {
     MissingUserPermission {
         permission: UserPermission,
     },
+    MissingEventPermission {
+        permission: EventPermission,
+    },
     NotElevated,
     NotPrivileged,
     CannotGiveMissingPermissions,
@@ -207,8 +247,35 @@ impl<'r> Responder<'r, 'static> for Error {
 
             Error::CannotReportYourself => Status::BadRequest,
 
+            Error::TicketsSoldOut => Status::Conflict,
+            Error::TooManyTickets { .. } => Status::BadRequest,
+            Error::InvalidCheckInPass => Status::BadRequest,
+            Error::CheckInPassForOtherEvent => Status::BadRequest,
+            Error::AlreadyCheckedIn => Status::Conflict,
+            Error::InvalidGuestStatusTransition { .. } => Status::BadRequest,
+            Error::AlreadyRsvped => Status::Conflict,
+            Error::PlusOnesNotAllowed => Status::BadRequest,
+            Error::TooManyPlusOnes { .. } => Status::BadRequest,
+            Error::MissingPlusOneInfo => Status::BadRequest,
+            Error::EventChannelExists => Status::Conflict,
+            Error::InvalidInvitation => Status::NotFound,
+            Error::InvitationExpired => Status::Gone,
+            Error::EventNotOpen => Status::BadRequest,
+            Error::InvalidEventStatusTransition { .. } => Status::BadRequest,
+            Error::InvalidTicketPrice => Status::BadRequest,
+            Error::PaymentFailed => Status::BadGateway,
+            Error::PaymentsUnavailable => Status::ServiceUnavailable,
+            Error::InvalidPaymentWebhook => Status::BadRequest,
+            Error::InvalidPromoCode => Status::BadRequest,
+            Error::PromoCodeExpired => Status::Gone,
+            Error::PromoCodeUsedUp => Status::Conflict,
+            Error::PromoCodeExists => Status::Conflict,
+            Error::FeedbackNotOpen => Status::BadRequest,
+            Error::NotEventAttendee => Status::Forbidden,
+
             Error::MissingPermission { .. } => Status::Forbidden,
             Error::MissingUserPermission { .. } => Status::Forbidden,
+            Error::MissingEventPermission { .. } => Status::Forbidden,
             Error::NotElevated => Status::Forbidden,
             Error::NotPrivileged => Status::Forbidden,
             Error::CannotGiveMissingPermissions => Status::Forbidden,
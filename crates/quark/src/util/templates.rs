@@ -0,0 +1,5 @@
+/// Plain text body of emails sent about events
+pub const EVENT_TEXT: &str = include_str!(crate::asset!("templates/event.txt"));
+
+/// HTML body of emails sent about events
+pub const EVENT_HTML: &str = include_str!(crate::asset!("templates/event.html"));
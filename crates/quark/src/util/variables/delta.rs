@@ -43,6 +43,10 @@ pub static VAPID_PUBLIC_KEY: Lazy<String> = Lazy::new(|| {
     env::var("REVOLT_VAPID_PUBLIC_KEY")
         .expect("Missing REVOLT_VAPID_PUBLIC_KEY environment variable.")
 });
+pub static EVENT_TOKEN_SECRET: Lazy<String> = Lazy::new(|| {
+    env::var("REVOLT_EVENT_TOKEN_SECRET")
+        .expect("Missing REVOLT_EVENT_TOKEN_SECRET environment variable.")
+});
 pub static AUTHIFIER_SHIELD_KEY: Lazy<Option<String>> =
     Lazy::new(|| env::var("REVOLT_AUTHIFIER_SHIELD_KEY").ok());
 
@@ -75,6 +79,18 @@ pub static SMTP_FROM: Lazy<String> =
     Lazy::new(|| env::var("REVOLT_SMTP_FROM").unwrap_or_else(|_| "".to_string()));
 
 // Application Logic Settings
+pub static EVENT_REMINDER_OFFSETS: Lazy<Vec<i64>> = Lazy::new(|| {
+    env::var("REVOLT_EVENT_REMINDER_OFFSETS")
+        .unwrap_or_else(|_| "1440,120".to_string())
+        .split(',')
+        .map(|offset| offset.trim().parse().unwrap())
+        .collect()
+});
+pub static PAYMENT_PROVIDER: Lazy<Option<String>> = Lazy::new(|| {
+    env::var("REVOLT_PAYMENT_PROVIDER")
+        .ok()
+        .filter(|provider| !provider.is_empty())
+});
 pub static MAX_GROUP_SIZE: Lazy<usize> = Lazy::new(|| {
     env::var("REVOLT_MAX_GROUP_SIZE")
         .unwrap_or_else(|_| "50".to_string())
@@ -144,6 +160,7 @@ pub fn preflight_checks() {
 
     format!("privkey = {}", *VAPID_PRIVATE_KEY);
     format!("pubkey = {}", *VAPID_PUBLIC_KEY);
+    format!("event token secret = {}", *EVENT_TOKEN_SECRET);
 
     if !(*USE_EMAIL) {
         #[cfg(not(debug_assertions))]